@@ -0,0 +1,297 @@
+// src/advisories.rs
+//
+// arch-audit style vulnerability report: loads an advisory feed in the Arch
+// security tracker JSON format and matches it against the installed packages.
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::kernel::run_command;
+use crate::vercmp::vercmp;
+
+// -----------------------------------------------------------------------------
+// Configuration
+// -----------------------------------------------------------------------------
+/// Default feed; any URL or local file with the same format can be passed instead.
+const DEFAULT_ADVISORY_FEED: &str = "https://security.archlinux.org/issues/all.json";
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Unknown,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+/// One AVG (advisory group) entry of the tracker feed.
+#[derive(Deserialize, Debug, Clone)]
+pub struct AdvisoryGroup {
+    pub name: String,
+    pub packages: Vec<String>,
+    pub status: String,
+    pub severity: Severity,
+    #[serde(rename = "type")]
+    pub vuln_type: String,
+    pub affected: String,
+    pub fixed: Option<String>,
+    #[serde(default)]
+    pub issues: Vec<String>,
+    #[serde(default)]
+    pub advisories: Vec<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct VulnerablePackage {
+    pub name: String,
+    pub installed_version: String,
+    /// Highest severity over all matching advisory groups.
+    pub severity: Severity,
+    pub groups: Vec<String>,
+    /// Versions the tracker confirmed as affected, one per group.
+    pub affected_versions: Vec<String>,
+    pub cves: Vec<String>,
+    pub vuln_types: Vec<String>,
+    pub advisories: Vec<String>,
+    /// Version that fixes every matching group, `None` while any group is unfixed.
+    pub fixed_version: Option<String>,
+    pub repo_version: Option<String>,
+    /// True when the sync repos already carry `fixed_version` or newer.
+    pub fix_available: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AuditReport {
+    pub feed_source: String,
+    pub checked_packages: usize,
+    pub vulnerable: Vec<VulnerablePackage>,
+}
+
+// -----------------------------------------------------------------------------
+// Feed loading
+// -----------------------------------------------------------------------------
+pub async fn load_feed(source: &str) -> Result<Vec<AdvisoryGroup>, String> {
+    let raw = if source.starts_with("http://") || source.starts_with("https://") {
+        run_command("curl", &["-fsSL", "--max-time", "30", source]).await?
+    } else {
+        let path = source.strip_prefix("file://").unwrap_or(source);
+        tokio::fs::read_to_string(path)
+            .await
+            .map_err(|e| format!("Failed to read advisory feed {}: {}", path, e))?
+    };
+
+    parse_feed(&raw)
+}
+
+pub fn parse_feed(raw: &str) -> Result<Vec<AdvisoryGroup>, String> {
+    serde_json::from_str(raw).map_err(|e| format!("Invalid advisory feed: {}", e))
+}
+
+// -----------------------------------------------------------------------------
+// Matching
+// -----------------------------------------------------------------------------
+/// An installed version is affected by a group unless the group is marked
+/// "Not affected" or the version is at least the group's `fixed` version.
+/// The tracker's `affected` field is the version the issue was confirmed
+/// against, not a lower bound, so older versions are affected as well.
+fn is_affected(group: &AdvisoryGroup, installed_version: &str) -> bool {
+    if group.status.eq_ignore_ascii_case("Not affected") {
+        return false;
+    }
+    match &group.fixed {
+        Some(fixed) => vercmp(installed_version, fixed) == Ordering::Less,
+        None => true,
+    }
+}
+
+/// Matches the feed against `installed` (name -> version) and `repo` (name -> sync version).
+pub fn audit(
+    groups: &[AdvisoryGroup],
+    installed: &HashMap<String, String>,
+    repo: &HashMap<String, String>,
+) -> Vec<VulnerablePackage> {
+    // BTreeMap keeps the report sorted by package name.
+    let mut matches: BTreeMap<&str, Vec<&AdvisoryGroup>> = BTreeMap::new();
+
+    for group in groups {
+        for pkg in &group.packages {
+            if let Some(version) = installed.get(pkg) {
+                if is_affected(group, version) {
+                    matches.entry(pkg.as_str()).or_default().push(group);
+                }
+            }
+        }
+    }
+
+    matches
+        .into_iter()
+        .map(|(name, groups)| {
+            let installed_version = installed[name].clone();
+            let repo_version = repo.get(name).cloned();
+
+            let severity = groups.iter().map(|g| g.severity).max().unwrap_or(Severity::Unknown);
+
+            // Every group needs a fix; the package is fixed by the newest of them.
+            let fixed_version = if groups.iter().all(|g| g.fixed.is_some()) {
+                groups
+                    .iter()
+                    .filter_map(|g| g.fixed.as_deref())
+                    .max_by(|a, b| vercmp(a, b))
+                    .map(|v| v.to_string())
+            } else {
+                None
+            };
+
+            let fix_available = match (&fixed_version, &repo_version) {
+                (Some(fixed), Some(repo_ver)) => vercmp(repo_ver, fixed) != Ordering::Less,
+                _ => false,
+            };
+
+            VulnerablePackage {
+                name: name.to_string(),
+                installed_version,
+                severity,
+                groups: groups.iter().map(|g| g.name.clone()).collect(),
+                affected_versions: dedup(groups.iter().map(|g| &g.affected)),
+                cves: dedup(groups.iter().flat_map(|g| g.issues.iter())),
+                vuln_types: dedup(groups.iter().map(|g| &g.vuln_type)),
+                advisories: dedup(groups.iter().flat_map(|g| g.advisories.iter())),
+                fixed_version,
+                repo_version,
+                fix_available,
+            }
+        })
+        .collect()
+}
+
+fn dedup<'a>(items: impl Iterator<Item = &'a String>) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for item in items {
+        if !out.contains(item) {
+            out.push(item.clone());
+        }
+    }
+    out
+}
+
+// -----------------------------------------------------------------------------
+// Package database queries
+// -----------------------------------------------------------------------------
+/// `pacman -Q` → name -> version
+async fn installed_packages() -> Result<HashMap<String, String>, String> {
+    let out = run_command("pacman", &["-Q"]).await?;
+    Ok(out
+        .lines()
+        .filter_map(|l| {
+            let mut parts = l.split_whitespace();
+            Some((parts.next()?.to_string(), parts.next()?.to_string()))
+        })
+        .collect())
+}
+
+/// `pacman -Sl` → name -> version of the first repo (highest priority) carrying it
+async fn repo_packages() -> Result<HashMap<String, String>, String> {
+    let out = run_command("pacman", &["-Sl"]).await?;
+    let mut map = HashMap::new();
+    for line in out.lines() {
+        // "core openssl 3.1.1-1 [installed]"
+        let mut parts = line.split_whitespace();
+        if let (Some(_repo), Some(name), Some(version)) = (parts.next(), parts.next(), parts.next()) {
+            map.entry(name.to_string()).or_insert_with(|| version.to_string());
+        }
+    }
+    Ok(map)
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: audit installed packages
+// -----------------------------------------------------------------------------
+#[tauri::command]
+pub async fn audit_installed_packages(feed_source: Option<String>) -> Result<AuditReport, String> {
+    let source = feed_source.unwrap_or_else(|| DEFAULT_ADVISORY_FEED.to_string());
+
+    let groups = load_feed(&source).await?;
+    let installed = installed_packages().await?;
+    let repo = repo_packages().await?;
+
+    Ok(AuditReport {
+        checked_packages: installed.len(),
+        vulnerable: audit(&groups, &installed, &repo),
+        feed_source: source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = include_str!("../tests/fixtures/arch-security-feed.json");
+
+    fn map(entries: &[(&str, &str)]) -> HashMap<String, String> {
+        entries.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    fn report() -> Vec<VulnerablePackage> {
+        let groups = parse_feed(FIXTURE).expect("fixture parses");
+        let installed = map(&[
+            ("openssl", "3.1.0-1"),
+            ("vim", "9.0.1350-1"),
+            ("curl", "7.88.1-1"),
+            ("sudo", "1.9.13.p1-1"),
+            ("glibc", "2.36-1"),
+        ]);
+        let repo = map(&[
+            ("openssl", "3.1.1-1"),
+            ("vim", "9.0.1420-1"),
+            ("curl", "1:7.88.1-1"),
+            ("sudo", "1.9.13.p1-1"),
+            ("glibc", "2.36-1"),
+        ]);
+        audit(&groups, &installed, &repo)
+    }
+
+    fn find<'a>(report: &'a [VulnerablePackage], name: &str) -> Option<&'a VulnerablePackage> {
+        report.iter().find(|p| p.name == name)
+    }
+
+    #[test]
+    fn reports_only_affected_installed_packages() {
+        let report = report();
+        let names: Vec<_> = report.iter().map(|p| p.name.as_str()).collect();
+        // sudo is newer than the fix, glibc is "Not affected", lib32-* are not installed.
+        assert_eq!(names, ["curl", "openssl", "vim"]);
+    }
+
+    #[test]
+    fn fixed_upstream_and_in_repos() {
+        let report = report();
+        let openssl = find(&report, "openssl").unwrap();
+        assert_eq!(openssl.severity, Severity::High);
+        assert_eq!(openssl.cves, ["CVE-2023-0464", "CVE-2023-0465"]);
+        assert_eq!(openssl.fixed_version.as_deref(), Some("3.1.1-1"));
+        assert!(openssl.fix_available);
+    }
+
+    #[test]
+    fn epoch_takes_precedence_over_version() {
+        let report = report();
+        let curl = find(&report, "curl").unwrap();
+        assert_eq!(curl.fixed_version.as_deref(), Some("1:7.88.1-1"));
+        assert!(curl.fix_available);
+    }
+
+    #[test]
+    fn merges_groups_and_requires_every_fix() {
+        let report = report();
+        let vim = find(&report, "vim").unwrap();
+        assert_eq!(vim.groups, ["AVG-2845", "AVG-2850"]);
+        assert_eq!(vim.severity, Severity::Critical);
+        assert_eq!(vim.cves, ["CVE-2023-1264", "CVE-2023-1127", "CVE-2023-1170"]);
+        // AVG-2845 has no fix yet, so the repo version can't resolve it.
+        assert_eq!(vim.fixed_version, None);
+        assert!(!vim.fix_available);
+    }
+}
@@ -0,0 +1,234 @@
+// src/audio.rs
+//
+// Sound cards and their HD Audio codecs from /proc/asound, tied back to the
+// PCI or USB device behind each card through /sys/class/sound. Audio
+// controllers that ended up without a card are where the known problems
+// show, most often Intel DSPs missing Sound Open Firmware.
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::pci::{self, link_name, PciDevice};
+
+// -----------------------------------------------------------------------------
+// Configuration
+// -----------------------------------------------------------------------------
+pub const FIRMWARE_ROOT: &str = "/usr/lib/firmware";
+
+/// Firmware directories shipped by sof-firmware, for IPC3 and IPC4 platforms.
+const SOF_FIRMWARE_DIRS: [&str; 2] = ["intel/sof", "intel/sof-ipc4"];
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SoundCodec {
+    /// Codec address on the HDA link, from the codec#N file name.
+    pub address: u32,
+    /// e.g. "Realtek ALC3266"
+    pub name: String,
+    /// e.g. "0x10ec0298"
+    pub vendor_id: Option<String>,
+    pub subsystem_id: Option<String>,
+    /// HDMI/DisplayPort audio of a GPU rather than speakers and jacks.
+    pub hdmi: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SoundCard {
+    pub index: u32,
+    /// ALSA card ID, e.g. "PCH".
+    pub id: String,
+    /// ALSA driver name, e.g. "HDA-Intel" or "USB-Audio".
+    pub alsa_driver: String,
+    pub name: String,
+    pub long_name: Option<String>,
+    /// PCI address or USB interface the card belongs to.
+    pub device: Option<String>,
+    /// Kernel driver bound to that device, e.g. "snd_hda_intel".
+    pub driver: Option<String>,
+    pub codecs: Vec<SoundCodec>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct AudioInfo {
+    pub cards: Vec<SoundCard>,
+    /// Known problems with the audio hardware, in plain words.
+    pub hints: Vec<String>,
+}
+
+// -----------------------------------------------------------------------------
+// Helpers
+// -----------------------------------------------------------------------------
+/// /proc/asound/cards: two lines per card,
+/// ` 0 [PCH            ]: HDA-Intel - HDA Intel PCH` and the long name.
+fn parse_cards(contents: &str) -> Vec<SoundCard> {
+    let mut cards: Vec<SoundCard> = Vec::new();
+    for line in contents.lines() {
+        let header = line.trim_start().split_once(" [").and_then(|(index, rest)| {
+            let index = index.parse().ok()?;
+            let (id, rest) = rest.split_once("]:")?;
+            let (alsa_driver, name) = rest.split_once(" - ")?;
+            Some(SoundCard {
+                index,
+                id: id.trim().to_string(),
+                alsa_driver: alsa_driver.trim().to_string(),
+                name: name.trim().to_string(),
+                ..Default::default()
+            })
+        });
+        match (header, cards.last_mut()) {
+            (Some(card), _) => cards.push(card),
+            (None, Some(card)) if card.long_name.is_none() && !line.trim().is_empty() => {
+                card.long_name = Some(line.trim().to_string());
+            }
+            _ => {}
+        }
+    }
+    cards
+}
+
+fn parse_codec(address: u32, contents: &str) -> Option<SoundCodec> {
+    let field = |key: &str| contents.lines().find_map(|l| l.strip_prefix(key)).map(|v| v.trim().to_string());
+    let name = field("Codec:")?;
+    Some(SoundCodec {
+        address,
+        hdmi: name.contains("HDMI") || name.contains("DisplayPort"),
+        vendor_id: field("Vendor Id:"),
+        subsystem_id: field("Subsystem Id:"),
+        name,
+    })
+}
+
+fn read_codecs(card_dir: &Path) -> Vec<SoundCodec> {
+    let Ok(entries) = std::fs::read_dir(card_dir) else { return Vec::new() };
+    let mut codecs: Vec<SoundCodec> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let address = e.file_name().to_string_lossy().strip_prefix("codec#")?.parse().ok()?;
+            parse_codec(address, &std::fs::read_to_string(e.path()).ok()?)
+        })
+        .collect();
+    codecs.sort_by_key(|c| c.address);
+    codecs
+}
+
+/// Sound cards from `<proc_root>/asound`, with their devices and drivers
+/// from `<sysfs_root>/class/sound`.
+pub fn enumerate(proc_root: &Path, sysfs_root: &Path) -> Vec<SoundCard> {
+    let asound = proc_root.join("asound");
+    let mut cards = parse_cards(&std::fs::read_to_string(asound.join("cards")).unwrap_or_default());
+    for card in cards.iter_mut() {
+        let device = sysfs_root.join("class/sound").join(format!("card{}", card.index)).join("device");
+        card.device = link_name(&device);
+        card.driver = link_name(&device.join("driver"));
+        card.codecs = read_codecs(&asound.join(format!("card{}", card.index)));
+    }
+    cards
+}
+
+/// Whether sof-firmware's files are under `firmware_root`.
+pub fn sof_firmware_installed(firmware_root: &Path) -> bool {
+    SOF_FIRMWARE_DIRS.iter().any(|d| firmware_root.join(d).is_dir())
+}
+
+/// Intel audio DSPs (class 0401, or bound to a SOF driver) can only run with
+/// Sound Open Firmware; without it the probe fails and no card appears.
+fn needs_sof(device: &PciDevice) -> bool {
+    device.vendor_id == "8086"
+        && (device.class.starts_with("0401") || device.driver.as_deref().is_some_and(|d| d.starts_with("sof-audio")))
+}
+
+/// Problems with the audio controllers in `devices` given the cards that
+/// were created for them.
+pub fn hints(cards: &[SoundCard], devices: &[PciDevice], sof_installed: bool) -> Vec<String> {
+    let mut hints = Vec::new();
+    for device in devices.iter().filter(|d| d.is_audio()) {
+        let label = format!("{} ({})", device.model(), device.address);
+        let has_card = cards.iter().any(|c| c.device.as_deref() == Some(device.address.as_str()));
+        if needs_sof(device) && !sof_installed {
+            hints.push(format!(
+                "{} is an Intel audio DSP that needs Sound Open Firmware. Install sof-firmware and reboot to get the internal speakers and microphones working.",
+                label
+            ));
+        } else if has_card {
+            continue;
+        } else if let Some(driver) = &device.driver {
+            hints.push(format!(
+                "{} is bound to {} but no sound card was created; `journalctl -k` usually shows the missing firmware or topology file.",
+                label, driver
+            ));
+        } else {
+            hints.push(format!("{} has no driver bound.", label));
+        }
+    }
+    if cards.is_empty() && hints.is_empty() {
+        hints.push("No sound cards were found.".to_string());
+    }
+    hints
+}
+
+/// Cards and hints for this machine.
+pub fn read(devices: &[PciDevice]) -> AudioInfo {
+    let cards = enumerate(Path::new(pci::PROC_ROOT), Path::new(pci::SYSFS_ROOT));
+    let hints = hints(&cards, devices, sof_firmware_installed(Path::new(FIRMWARE_ROOT)));
+    AudioInfo { cards, hints }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn controller(address: &str, class: &str, driver: Option<&str>) -> PciDevice {
+        PciDevice {
+            address: address.to_string(),
+            class: class.to_string(),
+            vendor_id: "8086".to_string(),
+            device_id: "a0c8".to_string(),
+            driver: driver.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reads_cards_and_codecs_from_fixture_tree() {
+        let cards = enumerate(&pci::proc_fixture_root(), &pci::fixture_root());
+        assert_eq!(cards.len(), 2);
+
+        let pch = &cards[0];
+        assert_eq!((pch.index, pch.id.as_str(), pch.alsa_driver.as_str()), (0, "PCH", "HDA-Intel"));
+        assert_eq!(pch.name, "HDA Intel PCH");
+        assert_eq!(pch.long_name.as_deref(), Some("HDA Intel PCH at 0xed218000 irq 158"));
+        assert_eq!(pch.device.as_deref(), Some("0000:00:1f.3"));
+        assert_eq!(pch.driver.as_deref(), Some("snd_hda_intel"));
+        let codecs: Vec<(&str, bool)> = pch.codecs.iter().map(|c| (c.name.as_str(), c.hdmi)).collect();
+        assert_eq!(codecs, [("Realtek ALC3266", false), ("Intel Kabylake HDMI", true)]);
+        assert_eq!(pch.codecs[0].vendor_id.as_deref(), Some("0x10ec0298"));
+
+        let dock = &cards[1];
+        assert_eq!(dock.alsa_driver, "USB-Audio");
+        assert_eq!(dock.long_name.as_deref(), Some("Dell-WD15-Dock at usb-0000:00:14.0-4.1, high speed"));
+        assert!(dock.codecs.is_empty());
+        assert_eq!(dock.driver, None);
+    }
+
+    #[test]
+    fn intel_dsp_without_sof_firmware_is_reported() {
+        let dsp = controller("0000:00:1f.3", "040100", None);
+        let reported = hints(&[], &[dsp], false);
+        assert_eq!(reported.len(), 1);
+        assert!(reported[0].contains("sof-firmware"));
+
+        let bound = controller("0000:00:1f.3", "040380", Some("sof-audio-pci-intel-tgl"));
+        assert!(hints(&[], std::slice::from_ref(&bound), false)[0].contains("sof-firmware"));
+        assert!(hints(&[], &[bound], true)[0].contains("no sound card was created"));
+        assert!(!sof_firmware_installed(&pci::fixture_root()));
+    }
+
+    #[test]
+    fn working_hda_controller_has_no_hints() {
+        let cards = enumerate(&pci::proc_fixture_root(), &pci::fixture_root());
+        let hda = controller("0000:00:1f.3", "040300", Some("snd_hda_intel"));
+        assert!(hints(&cards, &[hda], false).is_empty());
+        assert_eq!(hints(&[], &[], true), ["No sound cards were found."]);
+    }
+}
@@ -0,0 +1,627 @@
+// src/bootloader.rs
+//
+// Boot entry listing and default selection for GRUB, systemd-boot and rEFInd.
+// Config files are parsed directly; changes go through `privileged` and GRUB's
+// config is regenerated when `GRUB_DEFAULT` itself changes.
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::privileged::{self, RootWrite};
+
+// -----------------------------------------------------------------------------
+// Configuration
+// -----------------------------------------------------------------------------
+pub const GRUB_DEFAULTS: &str = "/etc/default/grub";
+pub const GRUB_CFG: &str = "/boot/grub/grub.cfg";
+const GRUB_ENV: &str = "/boot/grub/grubenv";
+/// Mount points where the ESP (or XBOOTLDR) is usually found.
+pub const ESP_CANDIDATES: [&str; 3] = ["/efi", "/boot", "/boot/efi"];
+const REFIND_CONFIGS: [&str; 2] = ["EFI/refind/refind.conf", "EFI/BOOT/refind.conf"];
+/// Set by systemd-boot (and only by it) for the current boot.
+const LOADER_INFO_EFIVAR: &str = "/sys/firmware/efi/efivars/LoaderInfo-4a67b082-0a4c-41cf-b6c7-440b29bb8c4f";
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BootloaderKind {
+    Grub,
+    SystemdBoot,
+    Refind,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BootEntry {
+    /// What `set_default_boot_entry` expects: the GRUB title path
+    /// ("Submenu>Entry"), the systemd-boot entry file name, or the rEFInd
+    /// loader/title.
+    pub id: String,
+    pub title: String,
+    /// GRUB `$menuentry_id_option` id, when present.
+    pub grub_id: Option<String>,
+    pub submenu: Option<String>,
+    pub kernel: Option<String>,
+    pub version: Option<String>,
+    pub options: Option<String>,
+    pub is_default: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BootloaderInfo {
+    pub kind: BootloaderKind,
+    pub config_path: String,
+    /// The raw default setting, e.g. "saved", "0", "arch.conf" or "vmlinuz-linux".
+    pub default_setting: Option<String>,
+    pub default_entry: Option<String>,
+    pub entries: Vec<BootEntry>,
+    pub warnings: Vec<String>,
+}
+
+// -----------------------------------------------------------------------------
+// Shell-style (KEY="value") and directive-style (key value) config helpers
+// -----------------------------------------------------------------------------
+fn unquote(value: &str) -> String {
+    let v = value.trim();
+    if v.len() >= 2 && ((v.starts_with('"') && v.ends_with('"')) || (v.starts_with('\'') && v.ends_with('\''))) {
+        v[1..v.len() - 1].to_string()
+    } else {
+        v.to_string()
+    }
+}
+
+/// Last assignment of `key` in a shell-sourced file like /etc/default/grub.
+pub fn read_shell_var(contents: &str, key: &str) -> Option<String> {
+    contents
+        .lines()
+        .rev()
+        .find_map(|l| l.trim().strip_prefix(key)?.strip_prefix('='))
+        .map(unquote)
+}
+
+/// Replaces the (last) assignment of `key`, appending it when missing.
+pub fn set_shell_var(contents: &str, key: &str, value: &str) -> String {
+    let escaped = value.replace('\\', "\\\\").replace('"', "\\\"").replace('$', "\\$").replace('`', "\\`");
+    let assignment = format!("{}=\"{}\"", key, escaped);
+    let mut lines: Vec<String> = contents.lines().map(str::to_string).collect();
+    let last = lines
+        .iter()
+        .rposition(|l| l.trim().strip_prefix(key).is_some_and(|r| r.starts_with('=')));
+    match last {
+        Some(i) => lines[i] = assignment,
+        None => lines.push(assignment),
+    }
+    lines.join("\n") + "\n"
+}
+
+fn read_directive(contents: &str, key: &str) -> Option<String> {
+    contents.lines().find_map(|l| {
+        let l = l.trim();
+        let (k, v) = l.split_once(char::is_whitespace)?;
+        (k == key).then(|| v.trim().to_string())
+    })
+}
+
+fn set_directive(contents: &str, key: &str, value: &str) -> String {
+    let line = format!("{} {}", key, value);
+    let mut replaced = false;
+    let mut lines: Vec<String> = contents
+        .lines()
+        .map(|l| {
+            let is_key = l.split_whitespace().next() == Some(key);
+            if is_key && !replaced {
+                replaced = true;
+                line.clone()
+            } else {
+                l.to_string()
+            }
+        })
+        .collect();
+    if !replaced {
+        lines.push(line);
+    }
+    lines.join("\n") + "\n"
+}
+
+fn glob_match(pattern: &str, text: &str) -> bool {
+    fn inner(p: &[char], t: &[char]) -> bool {
+        match (p.first(), t.first()) {
+            (None, None) => true,
+            (Some('*'), _) => inner(&p[1..], t) || (!t.is_empty() && inner(p, &t[1..])),
+            (Some('?'), Some(_)) => inner(&p[1..], &t[1..]),
+            (Some(a), Some(b)) if a == b => inner(&p[1..], &t[1..]),
+            _ => false,
+        }
+    }
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    inner(&p, &t)
+}
+
+fn read(path: &Path, warnings: &mut Vec<String>) -> Option<String> {
+    match std::fs::read_to_string(path) {
+        Ok(s) => Some(s),
+        Err(e) => {
+            warnings.push(format!("Could not read {}: {}", path.display(), e));
+            None
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Detection
+// -----------------------------------------------------------------------------
+fn find_in_esp(relative: &str) -> Option<PathBuf> {
+    ESP_CANDIDATES.iter().map(|esp| Path::new(esp).join(relative)).find(|p| p.exists())
+}
+
+pub fn detect() -> Option<BootloaderKind> {
+    let booted_by_systemd_boot = std::fs::read(LOADER_INFO_EFIVAR)
+        .map(|raw| String::from_utf8_lossy(&raw).replace('\0', "").contains("systemd-boot"))
+        .unwrap_or(false);
+    if booted_by_systemd_boot || (find_in_esp("loader/loader.conf").is_some() && !Path::new(GRUB_CFG).exists()) {
+        return Some(BootloaderKind::SystemdBoot);
+    }
+    if REFIND_CONFIGS.iter().any(|c| find_in_esp(c).is_some()) {
+        return Some(BootloaderKind::Refind);
+    }
+    if Path::new(GRUB_CFG).exists() || Path::new(GRUB_DEFAULTS).exists() {
+        return Some(BootloaderKind::Grub);
+    }
+    None
+}
+
+// -----------------------------------------------------------------------------
+// GRUB
+// -----------------------------------------------------------------------------
+/// Reads one quoted word ('...' with '\'' escapes, or "...") from the start of `s`.
+fn take_quoted(s: &str) -> Option<(String, &str)> {
+    let s = s.trim_start();
+    let quote = s.chars().next().filter(|c| *c == '\'' || *c == '"')?;
+    let mut out = String::new();
+    let mut rest = &s[1..];
+    loop {
+        let end = rest.find(quote)?;
+        out.push_str(&rest[..end]);
+        rest = &rest[end + 1..];
+        // grub-mkconfig writes a literal ' as '\''
+        if quote == '\'' && rest.starts_with("\\''") {
+            out.push('\'');
+            rest = &rest[3..];
+        } else {
+            return Some((out, rest));
+        }
+    }
+}
+
+fn grub_entry_id(rest: &str) -> Option<String> {
+    let idx = rest.find("$menuentry_id_option").map(|i| i + "$menuentry_id_option".len())
+        .or_else(|| rest.find("--id").map(|i| i + "--id".len()))?;
+    let tail = rest[idx..].trim_start().trim_start_matches('=');
+    take_quoted(tail).map(|(id, _)| id)
+}
+
+/// A top-level item of the GRUB menu; a submenu counts as one.
+pub struct GrubMenuItem {
+    /// Entry id, or the title of a submenu.
+    pub id: String,
+    pub grub_id: Option<String>,
+}
+
+enum GrubFrame {
+    Submenu(String),
+    Entry(usize),
+    Other,
+}
+
+/// Menu entries from grub.cfg in menu order; submenu entries carry their parent.
+pub fn parse_grub_cfg(cfg: &str) -> (Vec<BootEntry>, Vec<GrubMenuItem>) {
+    let mut entries: Vec<BootEntry> = Vec::new();
+    // Top-level items as GRUB numbers them (submenus count as one item).
+    let mut top_level: Vec<GrubMenuItem> = Vec::new();
+    let mut stack: Vec<GrubFrame> = Vec::new();
+
+    for line in cfg.lines() {
+        let trimmed = line.trim();
+        if trimmed == "}" {
+            stack.pop();
+            continue;
+        }
+
+        let keyword = trimmed.split_whitespace().next().unwrap_or("");
+        if keyword == "menuentry" || keyword == "submenu" {
+            let Some((title, rest)) = take_quoted(&trimmed[keyword.len()..]) else { continue };
+            let parent = stack.iter().rev().find_map(|f| match f {
+                GrubFrame::Submenu(t) => Some(t.clone()),
+                _ => None,
+            });
+            let in_submenu = parent.is_some();
+
+            if keyword == "submenu" {
+                if !in_submenu {
+                    top_level.push(GrubMenuItem { id: title.clone(), grub_id: grub_entry_id(rest) });
+                }
+                stack.push(GrubFrame::Submenu(title));
+            } else {
+                let id = match &parent {
+                    Some(p) => format!("{}>{}", p, title),
+                    None => title.clone(),
+                };
+                if !in_submenu {
+                    top_level.push(GrubMenuItem { id: id.clone(), grub_id: grub_entry_id(rest) });
+                }
+                entries.push(BootEntry {
+                    id,
+                    grub_id: grub_entry_id(rest),
+                    title,
+                    submenu: parent,
+                    kernel: None,
+                    version: None,
+                    options: None,
+                    is_default: false,
+                });
+                stack.push(GrubFrame::Entry(entries.len() - 1));
+            }
+            continue;
+        }
+
+        if trimmed.ends_with('{') {
+            stack.push(GrubFrame::Other);
+            continue;
+        }
+
+        if keyword == "linux" || keyword == "linuxefi" {
+            if let Some(GrubFrame::Entry(i)) = stack.iter().rev().find(|f| matches!(f, GrubFrame::Entry(_))) {
+                let mut parts = trimmed.split_whitespace().skip(1);
+                let entry = &mut entries[*i];
+                entry.kernel = parts.next().map(str::to_string);
+                let options = parts.collect::<Vec<_>>().join(" ");
+                entry.options = (!options.is_empty()).then_some(options);
+            }
+        }
+    }
+
+    (entries, top_level)
+}
+
+/// Resolves GRUB_DEFAULT or saved_entry to an entry id. Like GRUB, each
+/// '>'-separated step of the path is an index, a title or a menuentry id at
+/// its menu level ("1>2", "gnulinux-advanced-<uuid>>gnulinux-linux-lts-advanced-<uuid>").
+fn resolve_grub_default(setting: &str, entries: &[BootEntry], top_level: &[GrubMenuItem]) -> Option<String> {
+    if let Some(entry) = entries.iter().find(|e| e.id == setting || e.grub_id.as_deref() == Some(setting)) {
+        return Some(entry.id.clone());
+    }
+    let (first, rest) = match setting.split_once('>') {
+        Some((first, rest)) => (first, Some(rest)),
+        None => (setting, None),
+    };
+    let item = match first.parse::<usize>() {
+        Ok(index) => top_level.get(index)?,
+        Err(_) => top_level.iter().find(|i| i.id == first || i.grub_id.as_deref() == Some(first))?,
+    };
+    let Some(step) = rest else { return Some(item.id.clone()) };
+    let mut children = entries.iter().filter(|e| e.submenu.as_deref() == Some(item.id.as_str()));
+    match step.parse::<usize>() {
+        Ok(index) => children.nth(index),
+        Err(_) => children.find(|e| e.title == step || e.grub_id.as_deref() == Some(step)),
+    }
+    .map(|e| e.id.clone())
+}
+
+fn grub_info() -> BootloaderInfo {
+    let mut warnings = Vec::new();
+    let cfg = read(Path::new(GRUB_CFG), &mut warnings).unwrap_or_default();
+    let (mut entries, top_level) = parse_grub_cfg(&cfg);
+    let defaults = read(Path::new(GRUB_DEFAULTS), &mut warnings).unwrap_or_default();
+    let setting = read_shell_var(&defaults, "GRUB_DEFAULT").unwrap_or_else(|| "0".to_string());
+
+    let effective = if setting == "saved" {
+        std::fs::read_to_string(GRUB_ENV)
+            .ok()
+            .and_then(|env| env.lines().find_map(|l| l.strip_prefix("saved_entry=").map(str::to_string)))
+            .unwrap_or_else(|| "0".to_string())
+    } else {
+        setting.clone()
+    };
+    let default_entry = resolve_grub_default(&effective, &entries, &top_level);
+    for entry in &mut entries {
+        entry.is_default = Some(&entry.id) == default_entry.as_ref();
+    }
+
+    BootloaderInfo {
+        kind: BootloaderKind::Grub,
+        config_path: GRUB_CFG.to_string(),
+        default_setting: Some(setting),
+        default_entry,
+        entries,
+        warnings,
+    }
+}
+
+async fn set_grub_default(entry_id: &str) -> Result<(), String> {
+    let defaults = std::fs::read_to_string(GRUB_DEFAULTS).map_err(|e| format!("Failed to read {}: {}", GRUB_DEFAULTS, e))?;
+    if read_shell_var(&defaults, "GRUB_DEFAULT").as_deref() == Some("saved") {
+        // The choice lives in grubenv; grub.cfg stays as it is.
+        privileged::run_as_root("grub-set-default", &[entry_id]).await?;
+    } else {
+        let updated = set_shell_var(&defaults, "GRUB_DEFAULT", entry_id);
+        privileged::apply_as_root(
+            &[RootWrite::config(GRUB_DEFAULTS, updated)],
+            &[vec!["grub-mkconfig".into(), "-o".into(), GRUB_CFG.into()]],
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// systemd-boot
+// -----------------------------------------------------------------------------
+pub fn loader_conf_path() -> Option<PathBuf> {
+    find_in_esp("loader/loader.conf")
+}
+
+/// Entry files from every ESP/XBOOTLDR candidate, keyed by file name.
+pub fn systemd_boot_entry_files() -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = ESP_CANDIDATES
+        .iter()
+        .filter_map(|esp| std::fs::read_dir(Path::new(esp).join("loader/entries")).ok())
+        .flat_map(|dir| dir.filter_map(|e| e.ok()).map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "conf"))
+        .collect();
+    files.sort_by_key(|p| p.file_name().map(|n| n.to_os_string()));
+    files.dedup_by(|a, b| a.file_name() == b.file_name());
+    files
+}
+
+pub fn parse_loader_entry(id: &str, contents: &str) -> BootEntry {
+    BootEntry {
+        id: id.to_string(),
+        title: read_directive(contents, "title").unwrap_or_else(|| id.trim_end_matches(".conf").to_string()),
+        grub_id: None,
+        submenu: None,
+        kernel: read_directive(contents, "linux").or_else(|| read_directive(contents, "efi")),
+        version: read_directive(contents, "version"),
+        options: read_directive(contents, "options"),
+        is_default: false,
+    }
+}
+
+fn systemd_boot_info() -> BootloaderInfo {
+    let mut warnings = Vec::new();
+    let loader_conf = loader_conf_path();
+    let setting = loader_conf
+        .as_deref()
+        .and_then(|p| read(p, &mut warnings))
+        .and_then(|c| read_directive(&c, "default"));
+
+    let mut entries: Vec<BootEntry> = systemd_boot_entry_files()
+        .iter()
+        .filter_map(|path| {
+            let id = path.file_name()?.to_string_lossy().to_string();
+            Some(parse_loader_entry(&id, &read(path, &mut warnings)?))
+        })
+        .collect();
+
+    // Without a default, systemd-boot picks the first entry in its sort order.
+    let default_entry = match &setting {
+        Some(pattern) => entries.iter().find(|e| glob_match(pattern, &e.id) || glob_match(pattern, e.id.trim_end_matches(".conf"))),
+        None => entries.first(),
+    }
+    .map(|e| e.id.clone());
+    for entry in &mut entries {
+        entry.is_default = Some(&entry.id) == default_entry.as_ref();
+    }
+
+    BootloaderInfo {
+        kind: BootloaderKind::SystemdBoot,
+        config_path: loader_conf.map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
+        default_setting: setting,
+        default_entry,
+        entries,
+        warnings,
+    }
+}
+
+async fn set_systemd_boot_default(entry_id: &str) -> Result<(), String> {
+    let path = loader_conf_path().ok_or("loader/loader.conf was not found on the ESP")?;
+    let contents = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let updated = set_directive(&contents, "default", entry_id);
+    privileged::apply_as_root(&[RootWrite::config(path.to_string_lossy(), updated)], &[]).await?;
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// rEFInd
+// -----------------------------------------------------------------------------
+fn refind_conf_path() -> Option<PathBuf> {
+    REFIND_CONFIGS.iter().find_map(|c| find_in_esp(c))
+}
+
+fn refind_info() -> BootloaderInfo {
+    let mut warnings = Vec::new();
+    let conf_path = refind_conf_path();
+    let conf = conf_path.as_deref().and_then(|p| read(p, &mut warnings)).unwrap_or_default();
+    let setting = read_directive(&conf, "default_selection").map(|v| unquote(&v));
+
+    // Manual stanzas: menuentry "Title" { loader ...; options ... }
+    let mut entries = Vec::new();
+    let mut current: Option<BootEntry> = None;
+    for line in conf.lines() {
+        let trimmed = line.trim();
+        if let Some(rest) = trimmed.strip_prefix("menuentry") {
+            let title = take_quoted(rest).map(|(t, _)| t).unwrap_or_else(|| rest.trim_end_matches('{').trim().to_string());
+            current = Some(BootEntry {
+                id: title.clone(),
+                title,
+                grub_id: None,
+                submenu: None,
+                kernel: None,
+                version: None,
+                options: None,
+                is_default: false,
+            });
+        } else if trimmed == "}" {
+            if let Some(entry) = current.take() {
+                entries.push(entry);
+            }
+        } else if let Some(entry) = current.as_mut() {
+            match trimmed.split_once(char::is_whitespace) {
+                Some(("loader", v)) => entry.kernel = Some(v.trim().to_string()),
+                Some(("options", v)) => entry.options = Some(unquote(v)),
+                _ => {}
+            }
+        }
+    }
+
+    // Auto-detected kernels in /boot; options come from refind_linux.conf.
+    let linux_options = std::fs::read_to_string("/boot/refind_linux.conf")
+        .ok()
+        .and_then(|c| c.lines().find(|l| !l.trim().starts_with('#') && !l.trim().is_empty()).map(str::to_string))
+        .and_then(|l| take_quoted(&l).and_then(|(_, rest)| take_quoted(rest)).map(|(o, _)| o));
+    if let Ok(dir) = std::fs::read_dir("/boot") {
+        let mut kernels: Vec<String> = dir
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .filter(|n| n.starts_with("vmlinuz"))
+            .collect();
+        kernels.sort();
+        for name in kernels {
+            entries.push(BootEntry {
+                id: name.clone(),
+                title: format!("Boot {} from /boot", name),
+                grub_id: None,
+                submenu: None,
+                kernel: Some(format!("/boot/{}", name)),
+                version: None,
+                options: linux_options.clone(),
+                is_default: false,
+            });
+        }
+    }
+
+    // default_selection matches a substring of the title or loader file name.
+    let default_entry = setting
+        .as_deref()
+        .and_then(|s| entries.iter().find(|e| e.id == s || e.title.contains(s) || e.kernel.as_deref().is_some_and(|k| k.contains(s))))
+        .map(|e| e.id.clone());
+    for entry in &mut entries {
+        entry.is_default = Some(&entry.id) == default_entry.as_ref();
+    }
+
+    BootloaderInfo {
+        kind: BootloaderKind::Refind,
+        config_path: conf_path.map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
+        default_setting: setting,
+        default_entry,
+        entries,
+        warnings,
+    }
+}
+
+async fn set_refind_default(entry_id: &str) -> Result<(), String> {
+    let path = refind_conf_path().ok_or("refind.conf was not found on the ESP")?;
+    let contents = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let updated = set_directive(&contents, "default_selection", &format!("\"{}\"", entry_id));
+    privileged::apply_as_root(&[RootWrite::config(path.to_string_lossy(), updated)], &[]).await?;
+    Ok(())
+}
+
+pub fn bootloader_info() -> Result<BootloaderInfo, String> {
+    match detect() {
+        Some(BootloaderKind::Grub) => Ok(grub_info()),
+        Some(BootloaderKind::SystemdBoot) => Ok(systemd_boot_info()),
+        Some(BootloaderKind::Refind) => Ok(refind_info()),
+        None => Err("No supported bootloader (GRUB, systemd-boot, rEFInd) was found".to_string()),
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: list boot entries and the current default
+// -----------------------------------------------------------------------------
+#[tauri::command]
+pub async fn get_bootloader_info() -> Result<BootloaderInfo, String> {
+    bootloader_info()
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: change the default boot entry
+// -----------------------------------------------------------------------------
+#[tauri::command]
+pub async fn set_default_boot_entry(entry_id: String) -> Result<BootloaderInfo, String> {
+    let info = bootloader_info()?;
+    if !info.entries.iter().any(|e| e.id == entry_id) {
+        return Err(format!("Unknown boot entry: {}", entry_id));
+    }
+
+    match info.kind {
+        BootloaderKind::Grub => set_grub_default(&entry_id).await?,
+        BootloaderKind::SystemdBoot => set_systemd_boot_default(&entry_id).await?,
+        BootloaderKind::Refind => set_refind_default(&entry_id).await?,
+    }
+    bootloader_info()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UUID: &str = "4e1f2a7c-9b3d-4c55-8a0e-6f2d1b7c9e10";
+
+    fn fixture() -> (Vec<BootEntry>, Vec<GrubMenuItem>) {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/grub/grub.cfg");
+        parse_grub_cfg(&std::fs::read_to_string(path).unwrap())
+    }
+
+    #[test]
+    fn parses_entries_and_submenus() {
+        let (entries, top_level) = fixture();
+        let ids: Vec<&str> = entries.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            [
+                "Arch Linux",
+                "Advanced options for Arch Linux>Arch Linux, with Linux linux",
+                "Advanced options for Arch Linux>Arch Linux, with Linux linux (fallback initramfs)",
+                "Advanced options for Arch Linux>Arch Linux, with Linux linux-lts",
+                "Advanced options for Arch Linux>Arch Linux, with Linux linux-lts (fallback initramfs)",
+                "UEFI Firmware Settings",
+            ]
+        );
+        let top: Vec<(&str, Option<&str>)> = top_level.iter().map(|i| (i.id.as_str(), i.grub_id.as_deref())).collect();
+        let advanced = format!("gnulinux-advanced-{}", UUID);
+        assert_eq!(
+            top,
+            [
+                ("Arch Linux", Some(format!("gnulinux-simple-{}", UUID).as_str())),
+                ("Advanced options for Arch Linux", Some(advanced.as_str())),
+                ("UEFI Firmware Settings", Some("uefi-firmware")),
+            ]
+        );
+
+        let lts = &entries[3];
+        assert_eq!(lts.submenu.as_deref(), Some("Advanced options for Arch Linux"));
+        assert_eq!(lts.grub_id, Some(format!("gnulinux-linux-lts-advanced-{}", UUID)));
+        assert_eq!(lts.kernel.as_deref(), Some("/boot/vmlinuz-linux-lts"));
+        assert_eq!(lts.options, Some(format!("root=UUID={} rw loglevel=3 quiet", UUID)));
+        assert_eq!(entries[5].kernel, None);
+    }
+
+    #[test]
+    fn resolves_defaults_and_saved_entries() {
+        let (entries, top_level) = fixture();
+        let resolve = |setting: &str| resolve_grub_default(setting, &entries, &top_level);
+        let lts = Some("Advanced options for Arch Linux>Arch Linux, with Linux linux-lts".to_string());
+
+        assert_eq!(resolve("0").as_deref(), Some("Arch Linux"));
+        assert_eq!(resolve("1>2"), lts);
+        assert_eq!(resolve("Advanced options for Arch Linux>Arch Linux, with Linux linux-lts"), lts);
+        // grub-set-default and grub-reboot store submenu paths of menuentry ids.
+        assert_eq!(resolve(&format!("gnulinux-advanced-{0}>gnulinux-linux-lts-advanced-{0}", UUID)), lts);
+        assert_eq!(resolve(&format!("1>gnulinux-linux-lts-advanced-{}", UUID)), lts);
+        assert_eq!(resolve(&format!("gnulinux-linux-lts-advanced-{}", UUID)), lts);
+        assert_eq!(resolve("uefi-firmware").as_deref(), Some("UEFI Firmware Settings"));
+        assert_eq!(resolve("1>9"), None);
+        assert_eq!(resolve("7"), None);
+    }
+}
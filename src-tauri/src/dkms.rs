@@ -0,0 +1,297 @@
+// src/dkms.rs
+//
+// DKMS module status per installed kernel. `dkms status` only lists what was
+// built; modules that failed (or never could build because the headers are
+// missing) are found by comparing it with the tree in /var/lib/dkms.
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use tokio::process::Command;
+
+use crate::kernel::{get_installed_kernels, get_running_kernel_version};
+use crate::pacman_manager::{emit_progress, run_checked_command};
+
+// -----------------------------------------------------------------------------
+// Configuration
+// -----------------------------------------------------------------------------
+const DKMS_TREE: &str = "/var/lib/dkms";
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DkmsState {
+    Installed,
+    Built,
+    /// A build was attempted and left a make.log, but nothing was built.
+    Failed,
+    NotBuilt,
+    /// The kernel's headers are not installed, so DKMS cannot build for it.
+    MissingHeaders,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DkmsModuleStatus {
+    pub module: String,
+    pub version: String,
+    pub kernel_version: String,
+    pub state: DkmsState,
+    pub log_path: Option<String>,
+    /// Extra text from `dkms status`, e.g. "WARNING! Diff between built and installed module!".
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct KernelDkmsStatus {
+    pub kernel: String,
+    pub kernel_version: String,
+    pub headers_installed: bool,
+    pub running: bool,
+    pub modules: Vec<DkmsModuleStatus>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DkmsReport {
+    pub dkms_available: bool,
+    pub kernels: Vec<KernelDkmsStatus>,
+}
+
+/// One line of `dkms status`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DkmsStatusLine {
+    pub module: String,
+    pub version: String,
+    pub kernel_version: Option<String>,
+    pub state: String,
+    pub detail: Option<String>,
+}
+
+// -----------------------------------------------------------------------------
+// Parsing
+// -----------------------------------------------------------------------------
+/// Accepts both formats:
+/// "nvidia/550.78, 6.9.1-arch1-1, x86_64: installed" (dkms 3) and
+/// "nvidia, 550.78, 6.9.1-arch1-1, x86_64: installed" (dkms 2).
+pub fn parse_status_line(line: &str) -> Option<DkmsStatusLine> {
+    let (left, state) = line.rsplit_once(": ")?;
+    let mut fields = left.split(", ").map(str::trim);
+    let first = fields.next()?;
+    let (module, version) = match first.split_once('/') {
+        Some((m, v)) => (m.to_string(), v.to_string()),
+        None => (first.to_string(), fields.next()?.to_string()),
+    };
+    let kernel_version = fields.next().map(str::to_string);
+
+    let state = state.trim();
+    // Notes follow in parentheses, several of them when more than one applies:
+    // "installed (Original modules exist) (WARNING! Diff between built and installed module!)"
+    let (state, detail) = match state.split_once(' ') {
+        Some((s, rest)) => {
+            let notes: Vec<&str> = rest.split(") (").map(|n| n.trim_matches(|c| c == '(' || c == ')')).collect();
+            (s, Some(notes.join("; ")))
+        }
+        None => (state, None),
+    };
+    Some(DkmsStatusLine { module, version, kernel_version, state: state.trim_end_matches(',').to_string(), detail })
+}
+
+async fn dkms_status() -> Option<Vec<DkmsStatusLine>> {
+    let output = Command::new("dkms").arg("status").env("LC_ALL", "C").output().await.ok()?;
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(parse_status_line)
+            .collect(),
+    )
+}
+
+/// Module/version pairs registered in the DKMS tree.
+fn registered_modules(tree: &Path) -> Vec<(String, String)> {
+    let mut modules = Vec::new();
+    let Ok(entries) = std::fs::read_dir(tree) else { return modules };
+    for module_dir in entries.filter_map(|e| e.ok()).map(|e| e.path()).filter(|p| p.is_dir()) {
+        let Some(module) = module_dir.file_name().map(|n| n.to_string_lossy().to_string()) else { continue };
+        let Ok(versions) = std::fs::read_dir(&module_dir) else { continue };
+        for version_dir in versions.filter_map(|e| e.ok()).map(|e| e.path()) {
+            let name = version_dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            // Skip the "kernel-<kver>-<arch>" symlinks next to the version dirs.
+            if version_dir.is_dir() && !name.starts_with("kernel-") && version_dir.join("source").exists() {
+                modules.push((module.clone(), name));
+            }
+        }
+    }
+    modules.sort();
+    modules
+}
+
+fn build_log(tree: &Path, module: &str, version: &str, kernel_version: &str) -> Option<PathBuf> {
+    let base = tree.join(module).join(version);
+    [
+        base.join(kernel_version).join(std::env::consts::ARCH).join("log/make.log"),
+        base.join("build/make.log"),
+    ]
+    .into_iter()
+    .find(|p| p.exists())
+}
+
+fn module_state(
+    tree: &Path,
+    status: &[DkmsStatusLine],
+    module: &str,
+    version: &str,
+    kernel_version: &str,
+    headers_installed: bool,
+) -> DkmsModuleStatus {
+    let line = status
+        .iter()
+        .find(|l| l.module == module && l.version == version && l.kernel_version.as_deref() == Some(kernel_version));
+    let log = build_log(tree, module, version, kernel_version);
+
+    let state = match line.map(|l| l.state.as_str()) {
+        Some("installed") => DkmsState::Installed,
+        Some("built") => DkmsState::Built,
+        _ if !headers_installed => DkmsState::MissingHeaders,
+        _ if log.is_some() => DkmsState::Failed,
+        _ => DkmsState::NotBuilt,
+    };
+
+    DkmsModuleStatus {
+        module: module.to_string(),
+        version: version.to_string(),
+        kernel_version: kernel_version.to_string(),
+        log_path: log.filter(|_| state == DkmsState::Failed).map(|p| p.to_string_lossy().to_string()),
+        detail: line.and_then(|l| l.detail.clone()),
+        state,
+    }
+}
+
+/// Registered DKMS modules as "name/version".
+pub fn registered_module_names() -> Vec<String> {
+    registered_modules(Path::new(DKMS_TREE))
+        .into_iter()
+        .map(|(m, v)| format!("{}/{}", m, v))
+        .collect()
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: DKMS status for every installed kernel
+// -----------------------------------------------------------------------------
+#[tauri::command]
+pub async fn get_dkms_status() -> Result<DkmsReport, String> {
+    let running = get_running_kernel_version();
+    let (kernels, _) = get_installed_kernels(&running).await.map_err(|e| format!("Failed to get installed kernels: {}", e))?;
+    let status = dkms_status().await;
+    let dkms_available = status.is_some();
+    let status = status.unwrap_or_default();
+    let tree = Path::new(DKMS_TREE);
+    let modules = registered_modules(tree);
+
+    Ok(DkmsReport {
+        dkms_available,
+        kernels: kernels
+            .into_iter()
+            .map(|k| KernelDkmsStatus {
+                modules: modules
+                    .iter()
+                    .map(|(m, v)| module_state(tree, &status, m, v, &k.version, k.headers_installed))
+                    .collect(),
+                kernel: k.name,
+                kernel_version: k.version,
+                headers_installed: k.headers_installed,
+                running: k.running,
+            })
+            .collect(),
+    })
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: rebuild DKMS modules
+// -----------------------------------------------------------------------------
+/// Rebuilds one module ("name/version") or, without `module`, everything
+/// registered for `kernel_version` (the running kernel by default). Output is
+/// streamed as "pacman-progress" events like other package operations.
+#[tauri::command]
+pub async fn rebuild_dkms_modules(
+    app_handle: AppHandle,
+    kernel_version: Option<String>,
+    module: Option<String>,
+) -> Result<DkmsReport, String> {
+    let kernel_version = kernel_version.unwrap_or_else(get_running_kernel_version);
+    if !Path::new("/usr/lib/modules").join(&kernel_version).join("build").exists() {
+        return Err(format!("Headers for {} are not installed; DKMS cannot build modules for it", kernel_version));
+    }
+
+    let op_desc = "DKMS Rebuild";
+    emit_progress(&app_handle, op_desc, &format!("Rebuilding DKMS modules for {}...", kernel_version));
+    let result = match &module {
+        Some(m) => {
+            if !m.contains('/') {
+                return Err(format!("Expected module as name/version, got {}", m));
+            }
+            run_checked_command("pkexec", &["dkms", "install", "--force", m, "-k", &kernel_version], &app_handle, op_desc).await
+        }
+        None => run_checked_command("pkexec", &["dkms", "autoinstall", "-k", &kernel_version], &app_handle, op_desc).await,
+    };
+    if let Err(e) = result {
+        emit_progress(&app_handle, op_desc, &format!("Failed: {}", e));
+    }
+
+    // Report the outcome either way; a failure shows up as a Failed state with its log.
+    get_dkms_status().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(module: &str, version: &str, kernel: Option<&str>, state: &str, detail: Option<&str>) -> Option<DkmsStatusLine> {
+        Some(DkmsStatusLine {
+            module: module.to_string(),
+            version: version.to_string(),
+            kernel_version: kernel.map(str::to_string),
+            state: state.to_string(),
+            detail: detail.map(str::to_string),
+        })
+    }
+
+    #[test]
+    fn parses_dkms3_status_lines() {
+        assert_eq!(
+            parse_status_line("nvidia/550.78, 6.9.1-arch1-1, x86_64: installed"),
+            line("nvidia", "550.78", Some("6.9.1-arch1-1"), "installed", None)
+        );
+        assert_eq!(
+            parse_status_line("v4l2loopback/0.13.2, 6.6.32-1-lts, x86_64: built"),
+            line("v4l2loopback", "0.13.2", Some("6.6.32-1-lts"), "built", None)
+        );
+        assert_eq!(parse_status_line("zfs/2.2.4: added"), line("zfs", "2.2.4", None, "added", None));
+    }
+
+    #[test]
+    fn parses_dkms2_status_lines() {
+        assert_eq!(
+            parse_status_line("nvidia, 550.78, 6.9.1-arch1-1, x86_64: installed"),
+            line("nvidia", "550.78", Some("6.9.1-arch1-1"), "installed", None)
+        );
+        assert_eq!(
+            parse_status_line("vboxhost, 7.0.18_OSE, 6.9.1-arch1-1, x86_64: built"),
+            line("vboxhost", "7.0.18_OSE", Some("6.9.1-arch1-1"), "built", None)
+        );
+        assert_eq!(parse_status_line("zfs, 2.2.4: added"), line("zfs", "2.2.4", None, "added", None));
+    }
+
+    #[test]
+    fn keeps_warning_suffixes_as_detail() {
+        assert_eq!(
+            parse_status_line("nvidia/550.78, 6.9.1-arch1-1, x86_64: installed (WARNING! Diff between built and installed module!)"),
+            line("nvidia", "550.78", Some("6.9.1-arch1-1"), "installed", Some("WARNING! Diff between built and installed module!"))
+        );
+        assert_eq!(
+            parse_status_line("e1000e, 3.8.7, 6.9.1-arch1-1, x86_64: installed (original_module exists) (WARNING! Missing some built modules!)"),
+            line("e1000e", "3.8.7", Some("6.9.1-arch1-1"), "installed", Some("original_module exists; WARNING! Missing some built modules!"))
+        );
+        assert_eq!(parse_status_line("Error! Could not locate dkms.conf file."), None);
+        assert_eq!(parse_status_line(""), None);
+    }
+}
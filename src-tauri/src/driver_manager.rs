@@ -0,0 +1,589 @@
+// src/driver_manager.rs
+//
+// Installs, switches and removes driver profiles. Every operation is planned
+// first (packages, config snippets, initramfs rebuild, profile steps) and the
+// plan is what gets executed, in a single privileged script. Applied profiles
+// are recorded in STATE_FILE so the hardware panel can show them.
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use tauri::AppHandle;
+use tokio::process::Command;
+
+use crate::driver_profiles::{DriverProfile, PackageSource, ProfileDatabase, ProfileStep};
+use crate::hwids::IdDatabase;
+use crate::pacman_manager::emit_progress;
+use crate::partial_upgrade;
+use crate::pci;
+use crate::privileged::{self, RootStep, RootWrite};
+use crate::usb;
+
+// -----------------------------------------------------------------------------
+// Configuration
+// -----------------------------------------------------------------------------
+const STATE_FILE: &str = "/var/lib/linuxhub/driver-profiles.json";
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InstalledProfile {
+    pub id: String,
+    pub name: String,
+    /// Version of the profile database the profile was installed from.
+    pub database_version: String,
+    pub installed_at: String,
+    pub packages: Vec<String>,
+    /// Config snippets written for the profile, removed with it.
+    pub files: Vec<String>,
+    #[serde(default)]
+    pub mkinitcpio_modules: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct DriverState {
+    pub installed: Vec<InstalledProfile>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DriverAction {
+    Install,
+    Switch,
+    Remove,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DriverTransaction {
+    pub action: DriverAction,
+    pub profile: String,
+    /// Installed profiles taken out by this transaction.
+    pub replaces: Vec<String>,
+    pub install_packages: Vec<String>,
+    pub remove_packages: Vec<String>,
+    pub write_files: Vec<String>,
+    pub remove_files: Vec<String>,
+    pub regenerate_initramfs: bool,
+    pub pre_install: Vec<ProfileStep>,
+    pub post_install: Vec<ProfileStep>,
+    pub warnings: Vec<String>,
+    /// Reasons the transaction can't be applied as planned.
+    pub blockers: Vec<String>,
+    pub planned_only: bool,
+}
+
+// -----------------------------------------------------------------------------
+// State
+// -----------------------------------------------------------------------------
+pub fn read_state() -> DriverState {
+    std::fs::read_to_string(STATE_FILE)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+// -----------------------------------------------------------------------------
+// Helpers
+// -----------------------------------------------------------------------------
+fn modprobe_path(id: &str) -> String {
+    format!("/etc/modprobe.d/linuxhub-{}.conf", id)
+}
+
+fn mkinitcpio_path(id: &str) -> String {
+    format!("/etc/mkinitcpio.conf.d/linuxhub-{}.conf", id)
+}
+
+fn xorg_path(id: &str) -> String {
+    format!("/etc/X11/xorg.conf.d/20-linuxhub-{}.conf", id)
+}
+
+/// Config snippets a profile installs, as (path, contents).
+fn profile_files(profile: &DriverProfile) -> Vec<(String, String)> {
+    let mut files = Vec::new();
+    if let Some(modprobe) = &profile.modprobe {
+        files.push((modprobe_path(&profile.id), modprobe.clone()));
+    }
+    if !profile.mkinitcpio_modules.is_empty() {
+        files.push((mkinitcpio_path(&profile.id), format!("MODULES+=({})\n", profile.mkinitcpio_modules.join(" "))));
+    }
+    if let Some(xorg) = &profile.xorg {
+        files.push((xorg_path(&profile.id), xorg.clone()));
+    }
+    files
+}
+
+/// What an installed profile consists of; falls back to the database for
+/// profiles whose packages were installed by hand.
+fn installed_record(state: &DriverState, db: &ProfileDatabase, id: &str) -> Option<InstalledProfile> {
+    state.installed.iter().find(|p| p.id == id).cloned().or_else(|| {
+        db.get(id).map(|p| InstalledProfile {
+            id: p.id.clone(),
+            name: p.name.clone(),
+            database_version: db.version.clone(),
+            installed_at: String::new(),
+            packages: p.packages.clone(),
+            files: Vec::new(),
+            mkinitcpio_modules: Vec::new(),
+        })
+    })
+}
+
+async fn installed_packages(packages: &[String]) -> HashSet<String> {
+    if packages.is_empty() {
+        return HashSet::new();
+    }
+    let output = Command::new("pacman").arg("-Qq").args(packages).env("LC_ALL", "C").output().await;
+    output.map(|o| String::from_utf8_lossy(&o.stdout).lines().map(str::to_string).collect()).unwrap_or_default()
+}
+
+/// "Required By" of each package, from `pacman -Qi`.
+async fn required_by(packages: &[String]) -> HashMap<String, Vec<String>> {
+    let mut result = HashMap::new();
+    if packages.is_empty() {
+        return result;
+    }
+    let Ok(output) = Command::new("pacman").arg("-Qi").args(packages).env("LC_ALL", "C").output().await else {
+        return result;
+    };
+    let text = String::from_utf8_lossy(&output.stdout);
+    let mut name = String::new();
+    let mut current: Option<Vec<String>> = None;
+    for line in text.lines() {
+        if line.starts_with(' ') {
+            if let Some(list) = current.as_mut() {
+                list.extend(line.split_whitespace().map(str::to_string));
+            }
+            continue;
+        }
+        if let Some(list) = current.take() {
+            result.insert(name.clone(), list);
+        }
+        let Some((key, value)) = line.split_once(':') else { continue };
+        match key.trim() {
+            "Name" => name = value.trim().to_string(),
+            "Required By" => {
+                current = Some(value.split_whitespace().filter(|v| *v != "None").map(str::to_string).collect());
+            }
+            _ => {}
+        }
+    }
+    if let Some(list) = current {
+        result.insert(name, list);
+    }
+    result
+}
+
+fn conflicts_with(a: &DriverProfile, b_id: &str, db: &ProfileDatabase) -> bool {
+    a.conflicts.iter().any(|c| c == b_id) || db.get(b_id).is_some_and(|b| b.conflicts.contains(&a.id))
+}
+
+/// Profiles matching a device on this machine, each once.
+fn detected_profiles(db: &ProfileDatabase) -> Vec<&DriverProfile> {
+    let root = Path::new(pci::SYSFS_ROOT);
+    let mut profiles: Vec<&DriverProfile> = Vec::new();
+    let pci_matches = pci::enumerate(root, &IdDatabase::default()).into_iter().map(|d| db.matching(&d)).collect::<Vec<_>>();
+    let usb_matches = usb::enumerate(root, &IdDatabase::default()).into_iter().map(|d| db.matching_usb(&d)).collect::<Vec<_>>();
+    for profile in pci_matches.into_iter().chain(usb_matches).flatten() {
+        if !profiles.iter().any(|p| p.id == profile.id) {
+            profiles.push(profile);
+        }
+    }
+    profiles
+}
+
+/// Packages still wanted after the transaction: those of the other recorded
+/// profiles, of the target, and of every profile matching a detected device
+/// that is neither being replaced nor in conflict with the target. The last
+/// keeps e.g. mesa for the Intel iGPU when nouveau is switched out.
+fn kept_packages(
+    state: &DriverState,
+    replacing: &[InstalledProfile],
+    target: Option<&DriverProfile>,
+    detected: &[&DriverProfile],
+    db: &ProfileDatabase,
+) -> HashSet<String> {
+    let replaced = |id: &str| replacing.iter().any(|r| r.id == id);
+    let mut keep: HashSet<String> =
+        state.installed.iter().filter(|p| !replaced(&p.id)).flat_map(|p| p.packages.iter().cloned()).collect();
+    keep.extend(
+        detected
+            .iter()
+            .filter(|p| !replaced(&p.id) && !target.is_some_and(|t| conflicts_with(t, &p.id, db)))
+            .flat_map(|p| p.packages.iter().cloned()),
+    );
+    if let Some(t) = target {
+        keep.extend(t.packages.iter().cloned());
+    }
+    keep
+}
+
+/// The packages of `replacing` to remove: installed, not kept, and not
+/// required by anything outside the removal set. Returns them sorted, with
+/// a warning for each package that stays because of a dependant.
+fn resolve_removals(
+    replacing: &[InstalledProfile],
+    keep: &HashSet<String>,
+    installed: &HashSet<String>,
+    requirements: &HashMap<String, Vec<String>>,
+) -> (Vec<String>, Vec<String>) {
+    let mut warnings = Vec::new();
+    let mut removing: HashSet<String> = replacing
+        .iter()
+        .flat_map(|p| p.packages.iter())
+        .filter(|p| !keep.contains(*p) && installed.contains(*p))
+        .cloned()
+        .collect();
+    // A package stays when something outside the removal set needs it; that
+    // can in turn keep its own dependencies, so repeat until nothing changes.
+    loop {
+        let mut kept: Vec<(String, Vec<String>)> = removing
+            .iter()
+            .filter_map(|package| {
+                let needed_by: Vec<String> =
+                    requirements.get(package)?.iter().filter(|d| !removing.contains(*d)).cloned().collect();
+                (!needed_by.is_empty()).then(|| (package.clone(), needed_by))
+            })
+            .collect();
+        if kept.is_empty() {
+            break;
+        }
+        kept.sort();
+        for (package, needed_by) in kept {
+            removing.remove(&package);
+            warnings.push(format!("Keeping {}: required by {}", package, needed_by.join(", ")));
+        }
+    }
+    let mut remove_packages: Vec<String> = removing.into_iter().collect();
+    remove_packages.sort();
+    (remove_packages, warnings)
+}
+
+/// Builds the plan. `target` is the profile to install (None for removal);
+/// `replacing` are installed profiles to take out first.
+async fn plan(
+    action: DriverAction,
+    profile_id: &str,
+    target: Option<&DriverProfile>,
+    replacing: Vec<InstalledProfile>,
+    state: &DriverState,
+    db: &ProfileDatabase,
+) -> DriverTransaction {
+    let mut blockers = Vec::new();
+    let detected = detected_profiles(db);
+
+    let keep = kept_packages(state, &replacing, target, &detected, db);
+    let candidates: Vec<String> = replacing.iter().flat_map(|p| p.packages.iter()).filter(|p| !keep.contains(*p)).cloned().collect();
+    let installed = installed_packages(&candidates).await;
+    let requirements = required_by(&installed.iter().cloned().collect::<Vec<_>>()).await;
+    let (remove_packages, mut warnings) = resolve_removals(&replacing, &keep, &installed, &requirements);
+
+    let mut install_packages = Vec::new();
+    let mut write_files = Vec::new();
+    if let Some(t) = target {
+        let present = installed_packages(&t.packages).await;
+        let missing: Vec<String> = t.packages.iter().filter(|p| !present.contains(*p)).cloned().collect();
+        if t.source == PackageSource::Aur && !missing.is_empty() {
+            blockers.push(format!(
+                "{} comes from the AUR; install {} with an AUR helper first, then apply the profile again",
+                t.name,
+                missing.join(" ")
+            ));
+        } else {
+            // The root script installs without asking, so the guard runs here.
+            match partial_upgrade::first_blocked(&missing).await {
+                Ok(Some(report)) => blockers.push(report.explanation),
+                Ok(None) => {}
+                Err(e) => warnings.push(format!("Could not check for a partial upgrade: {}", e)),
+            }
+        }
+        install_packages = t.packages.clone();
+        write_files = profile_files(t).into_iter().map(|(path, _)| path).collect();
+        if !detected.iter().any(|p| p.id == t.id) {
+            warnings.push(format!("No detected device matches {}", t.name));
+        }
+    }
+
+    let remove_files: Vec<String> =
+        replacing.iter().flat_map(|p| p.files.iter()).filter(|f| !write_files.contains(f)).cloned().collect();
+    let regenerate_initramfs =
+        target.is_some_and(|t| !t.mkinitcpio_modules.is_empty()) || replacing.iter().any(|p| !p.mkinitcpio_modules.is_empty());
+    if !remove_packages.is_empty() || regenerate_initramfs {
+        warnings.push("Reboot to finish changing drivers".to_string());
+    }
+
+    DriverTransaction {
+        action,
+        profile: profile_id.to_string(),
+        replaces: replacing.iter().map(|p| p.id.clone()).collect(),
+        install_packages,
+        remove_packages,
+        write_files,
+        remove_files,
+        regenerate_initramfs,
+        pre_install: target.map(|t| t.pre_install.clone()).unwrap_or_default(),
+        post_install: target.map(|t| t.post_install.clone()).unwrap_or_default(),
+        warnings,
+        blockers,
+        planned_only: true,
+    }
+}
+
+/// Runs the plan as one privileged script: package changes first, then the
+/// config snippets and the initramfs, and the new state last, so a failed
+/// step never leaves a profile recorded (or a MODULES+= snippet in place)
+/// without its packages.
+async fn execute(
+    app_handle: &AppHandle,
+    mut transaction: DriverTransaction,
+    target: Option<&DriverProfile>,
+    db: &ProfileDatabase,
+    mut state: DriverState,
+) -> Result<DriverTransaction, String> {
+    if !transaction.blockers.is_empty() {
+        return Err(transaction.blockers.join("; "));
+    }
+    let op_desc = "Driver Profile";
+
+    let to_steps = |steps: &[ProfileStep]| -> Vec<RootStep> {
+        steps.iter().filter(|s| !s.run.is_empty()).map(|s| RootStep::Run(s.run.clone())).collect()
+    };
+    let mut steps = to_steps(&transaction.pre_install);
+    if !transaction.remove_packages.is_empty() {
+        // The removal set is resolved in the plan; -R removes exactly that.
+        let mut remove = vec!["pacman".to_string(), "-R".to_string(), "--noconfirm".to_string()];
+        remove.extend(transaction.remove_packages.iter().cloned());
+        steps.push(RootStep::Run(remove));
+    }
+    if target.is_some_and(|t| t.source == PackageSource::Repo) && !transaction.install_packages.is_empty() {
+        let mut install = vec!["pacman".to_string(), "-S".to_string(), "--needed".to_string(), "--noconfirm".to_string()];
+        install.extend(transaction.install_packages.iter().cloned());
+        steps.push(RootStep::Run(install));
+    }
+    if !transaction.remove_files.is_empty() {
+        let mut rm = vec!["rm".to_string(), "-f".to_string()];
+        rm.extend(transaction.remove_files.iter().cloned());
+        steps.push(RootStep::Run(rm));
+    }
+
+    state.installed.retain(|p| !transaction.replaces.contains(&p.id) && Some(p.id.as_str()) != target.map(|t| t.id.as_str()));
+    if let Some(t) = target {
+        for (path, contents) in profile_files(t) {
+            steps.push(RootStep::Write(RootWrite::config(path, contents)));
+        }
+        state.installed.push(InstalledProfile {
+            id: t.id.clone(),
+            name: t.name.clone(),
+            database_version: db.version.clone(),
+            installed_at: Utc::now().to_rfc3339(),
+            packages: t.packages.clone(),
+            files: transaction.write_files.clone(),
+            mkinitcpio_modules: t.mkinitcpio_modules.clone(),
+        });
+    }
+    if transaction.regenerate_initramfs {
+        steps.push(RootStep::Run(vec!["mkinitcpio".to_string(), "-P".to_string()]));
+    }
+    steps.extend(to_steps(&transaction.post_install));
+    let state_json = serde_json::to_string_pretty(&state).map_err(|e| e.to_string())?;
+    steps.push(RootStep::Write(RootWrite::config(STATE_FILE, state_json + "\n")));
+
+    emit_progress(app_handle, op_desc, &format!("Applying driver profile {}...", transaction.profile));
+    match privileged::run_steps_as_root(&steps).await {
+        Ok(output) => {
+            for line in output.lines() {
+                emit_progress(app_handle, op_desc, line);
+            }
+            emit_progress(app_handle, op_desc, "Driver profile applied.");
+        }
+        Err(e) => {
+            emit_progress(app_handle, op_desc, &format!("Failed: {}", e));
+            return Err(e);
+        }
+    }
+    transaction.planned_only = false;
+    Ok(transaction)
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: installed profiles
+// -----------------------------------------------------------------------------
+#[tauri::command]
+pub async fn get_installed_driver_profiles() -> Result<Vec<InstalledProfile>, String> {
+    Ok(read_state().installed)
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: install a profile
+// -----------------------------------------------------------------------------
+/// Installs `profile_id` next to what is installed. Refuses when it conflicts
+/// with an installed profile; use `switch_driver_profile` for that. With
+/// `dry_run` only the plan is returned.
+#[tauri::command]
+pub async fn install_driver_profile(
+    app_handle: AppHandle,
+    profile_id: String,
+    dry_run: Option<bool>,
+) -> Result<DriverTransaction, String> {
+    let db = ProfileDatabase::load();
+    let state = read_state();
+    let target = db.get(&profile_id).ok_or_else(|| format!("Unknown driver profile: {}", profile_id))?;
+
+    let mut transaction = plan(DriverAction::Install, &profile_id, Some(target), Vec::new(), &state, &db).await;
+    for installed in state.installed.iter().filter(|p| conflicts_with(target, &p.id, &db)) {
+        transaction.blockers.push(format!("{} conflicts with installed profile {}; switch instead", target.name, installed.name));
+    }
+    if state.installed.iter().any(|p| p.id == profile_id) {
+        transaction.warnings.push(format!("{} is already installed and will be reapplied", target.name));
+    }
+
+    if dry_run.unwrap_or(false) {
+        return Ok(transaction);
+    }
+    execute(&app_handle, transaction, Some(target), &db, state).await
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: switch profiles
+// -----------------------------------------------------------------------------
+/// Installs `profile_id` and removes every installed profile it conflicts
+/// with (e.g. nouveau -> nvidia), in one transaction.
+#[tauri::command]
+pub async fn switch_driver_profile(
+    app_handle: AppHandle,
+    profile_id: String,
+    dry_run: Option<bool>,
+) -> Result<DriverTransaction, String> {
+    let db = ProfileDatabase::load();
+    let state = read_state();
+    let target = db.get(&profile_id).ok_or_else(|| format!("Unknown driver profile: {}", profile_id))?;
+
+    // Conflicting profiles, whether recorded or installed by hand.
+    let mut replacing: Vec<InstalledProfile> =
+        state.installed.iter().filter(|p| conflicts_with(target, &p.id, &db)).cloned().collect();
+    for id in &target.conflicts {
+        if replacing.iter().any(|p| &p.id == id) {
+            continue;
+        }
+        if let Some(record) = installed_record(&state, &db, id) {
+            if !installed_packages(&record.packages).await.is_empty() {
+                replacing.push(record);
+            }
+        }
+    }
+
+    let mut transaction = plan(DriverAction::Switch, &profile_id, Some(target), replacing, &state, &db).await;
+    if transaction.replaces.is_empty() {
+        transaction.warnings.push("No conflicting profile is installed; this is a plain install".to_string());
+    }
+
+    if dry_run.unwrap_or(false) {
+        return Ok(transaction);
+    }
+    execute(&app_handle, transaction, Some(target), &db, state).await
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: remove a profile
+// -----------------------------------------------------------------------------
+/// Removes the profile's packages (unless something else needs them) and
+/// its config snippets.
+#[tauri::command]
+pub async fn remove_driver_profile(
+    app_handle: AppHandle,
+    profile_id: String,
+    dry_run: Option<bool>,
+) -> Result<DriverTransaction, String> {
+    let db = ProfileDatabase::load();
+    let state = read_state();
+    let record = installed_record(&state, &db, &profile_id).ok_or_else(|| format!("Unknown driver profile: {}", profile_id))?;
+
+    let recorded = state.installed.iter().any(|p| p.id == profile_id);
+    if !recorded && installed_packages(&record.packages).await.is_empty() {
+        return Err(format!("{} is not installed", record.name));
+    }
+
+    let mut transaction = plan(DriverAction::Remove, &profile_id, None, vec![record], &state, &db).await;
+    if !recorded {
+        transaction.warnings.push("This profile was not installed through linuxhub; only its packages are removed".to_string());
+    }
+
+    if dry_run.unwrap_or(false) {
+        return Ok(transaction);
+    }
+    execute(&app_handle, transaction, None, &db, state).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_snippets_are_named_after_the_profile() {
+        let db = ProfileDatabase::bundled();
+        let files = profile_files(db.get("video-nvidia-open").unwrap());
+        let paths: Vec<&str> = files.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(paths, ["/etc/modprobe.d/linuxhub-video-nvidia-open.conf", "/etc/mkinitcpio.conf.d/linuxhub-video-nvidia-open.conf"]);
+        assert_eq!(files[1].1, "MODULES+=(nvidia nvidia_modeset nvidia_uvm nvidia_drm)\n");
+
+        assert!(profile_files(db.get("video-intel").unwrap()).is_empty());
+        assert_eq!(profile_files(db.get("video-amdgpu").unwrap())[0].0, "/etc/X11/xorg.conf.d/20-linuxhub-video-amdgpu.conf");
+    }
+
+    fn record(db: &ProfileDatabase, id: &str) -> InstalledProfile {
+        installed_record(&DriverState::default(), db, id).unwrap()
+    }
+
+    fn set(items: &[&str]) -> HashSet<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn switching_away_from_nouveau_keeps_mesa_for_the_igpu() {
+        let db = ProfileDatabase::bundled();
+        let target = db.get("video-nvidia-open").unwrap();
+        let replacing = vec![record(&db, "video-nouveau")];
+        // Intel + NVIDIA laptop: every profile that matches either GPU.
+        let detected: Vec<&DriverProfile> = ["video-intel", "video-modesetting", "video-nvidia-open", "video-nvidia-580xx", "video-nouveau"]
+            .iter()
+            .map(|id| db.get(id).unwrap())
+            .collect();
+
+        let keep = kept_packages(&DriverState::default(), &replacing, Some(target), &detected, &db);
+        assert!(keep.contains("mesa"));
+        assert!(keep.contains("nvidia-utils"));
+        assert!(!keep.contains("vulkan-nouveau"));
+        assert!(!keep.contains("nvidia-580xx-utils"), "profiles in conflict with the target are not kept");
+
+        let (remove, warnings) = resolve_removals(&replacing, &keep, &set(&["mesa", "vulkan-nouveau"]), &HashMap::new());
+        assert_eq!(remove, ["vulkan-nouveau"]);
+        assert!(warnings.is_empty());
+
+        // Without the iGPU, mesa is only kept by what depends on it.
+        let keep = kept_packages(&DriverState::default(), &replacing, Some(target), &[], &db);
+        let requirements = HashMap::from([("mesa".to_string(), vec!["vulkan-nouveau".to_string()])]);
+        let (remove, _) = resolve_removals(&replacing, &keep, &set(&["mesa", "vulkan-nouveau"]), &requirements);
+        assert_eq!(remove, ["mesa", "vulkan-nouveau"]);
+    }
+
+    #[test]
+    fn packages_needed_outside_the_removal_set_stay() {
+        let db = ProfileDatabase::bundled();
+        let replacing = vec![record(&db, "video-nvidia-580xx")];
+        let installed = set(&["nvidia-580xx-dkms", "nvidia-580xx-utils", "nvidia-580xx-settings"]);
+        // Something unrelated needs the utils; the utils keep the DKMS module.
+        let requirements = HashMap::from([
+            ("nvidia-580xx-utils".to_string(), vec!["cuda".to_string(), "nvidia-580xx-settings".to_string()]),
+            ("nvidia-580xx-dkms".to_string(), vec!["nvidia-580xx-utils".to_string()]),
+        ]);
+        let (remove, warnings) = resolve_removals(&replacing, &HashSet::new(), &installed, &requirements);
+        assert_eq!(remove, ["nvidia-580xx-settings"]);
+        assert_eq!(warnings, ["Keeping nvidia-580xx-utils: required by cuda", "Keeping nvidia-580xx-dkms: required by nvidia-580xx-utils"]);
+
+        // Packages not installed are never removed; recorded profiles keep theirs.
+        let state = DriverState { installed: vec![record(&db, "video-intel")] };
+        let keep = kept_packages(&state, &[record(&db, "video-nouveau")], None, &[], &db);
+        let (remove, _) = resolve_removals(&[record(&db, "video-nouveau")], &keep, &set(&["mesa"]), &HashMap::new());
+        assert!(remove.is_empty());
+    }
+}
@@ -0,0 +1,294 @@
+// src/driver_profiles.rs
+//
+// mhwd-style driver profiles: which packages drive which hardware, matched on
+// PCI class, vendor and device IDs, or on USB interface class, vendor and
+// product IDs. The bundled database lives in
+// `data/driver-profiles.json`; JSON files in LOCAL_PROFILE_DIR with the same
+// schema add profiles or replace bundled ones with the same id.
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::pci::PciDevice;
+use crate::usb::UsbDevice;
+
+// -----------------------------------------------------------------------------
+// Configuration
+// -----------------------------------------------------------------------------
+const BUNDLED_PROFILES: &str = include_str!("../data/driver-profiles.json");
+const LOCAL_PROFILE_DIR: &str = "/etc/linuxhub/driver-profiles.d";
+const SUPPORTED_SCHEMA: u32 = 1;
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PackageSource {
+    #[default]
+    Repo,
+    /// Not in the official repositories; needs an AUR helper.
+    Aur,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Bus {
+    #[default]
+    Pci,
+    Usb,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProfileStep {
+    pub description: String,
+    /// Command run as root; empty for steps that are advice only.
+    #[serde(default)]
+    pub run: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DriverProfile {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub bus: Bus,
+    /// Hex class ("03") or class + subclass ("0302"); for USB the class of
+    /// an interface.
+    pub class_ids: Vec<String>,
+    /// Empty matches any vendor.
+    #[serde(default)]
+    pub vendor_ids: Vec<String>,
+    /// Device or product IDs ("1c8d") or inclusive ranges ("1e00-1fff");
+    /// empty matches any device.
+    #[serde(default)]
+    pub device_ids: Vec<String>,
+    /// Among matching profiles the highest priority is recommended.
+    pub priority: i32,
+    pub free: bool,
+    #[serde(default)]
+    pub source: PackageSource,
+    pub packages: Vec<String>,
+    /// Ids of profiles that can't be installed alongside this one.
+    #[serde(default)]
+    pub conflicts: Vec<String>,
+    #[serde(default)]
+    pub pre_install: Vec<ProfileStep>,
+    #[serde(default)]
+    pub post_install: Vec<ProfileStep>,
+    /// Contents of /etc/modprobe.d/linuxhub-<id>.conf.
+    pub modprobe: Option<String>,
+    /// Added to MODULES through /etc/mkinitcpio.conf.d/linuxhub-<id>.conf.
+    #[serde(default)]
+    pub mkinitcpio_modules: Vec<String>,
+    /// Contents of /etc/X11/xorg.conf.d/20-linuxhub-<id>.conf.
+    pub xorg: Option<String>,
+    pub wiki_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProfileFile {
+    schema: u32,
+    version: String,
+    profiles: Vec<DriverProfile>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProfileDatabase {
+    pub version: String,
+    pub profiles: Vec<DriverProfile>,
+}
+
+/// The profiles matching one device, best first.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DeviceProfiles {
+    pub bus: Bus,
+    /// PCI address, or the sysfs name of a USB device ("1-4").
+    pub address: String,
+    pub device: String,
+    pub recommended: Option<String>,
+    /// Best match among free profiles, when it differs from `recommended`.
+    pub recommended_free: Option<String>,
+    /// A matching profile recorded as installed by the driver manager.
+    pub installed: Option<String>,
+    pub profiles: Vec<DriverProfile>,
+}
+
+// -----------------------------------------------------------------------------
+// Matching
+// -----------------------------------------------------------------------------
+/// `pattern` is a hex ID or an inclusive "start-end" range.
+fn id_matches(pattern: &str, id: &str) -> bool {
+    let parse = |s: &str| u32::from_str_radix(s.trim(), 16).ok();
+    let Some(id) = parse(id) else { return false };
+    match pattern.split_once('-') {
+        Some((start, end)) => matches!((parse(start), parse(end)), (Some(s), Some(e)) if (s..=e).contains(&id)),
+        None => parse(pattern) == Some(id),
+    }
+}
+
+impl DriverProfile {
+    fn matches_ids(&self, class: &str, vendor_id: &str, device_id: &str) -> bool {
+        self.class_ids.iter().any(|c| class.starts_with(&c.to_lowercase()))
+            && (self.vendor_ids.is_empty() || self.vendor_ids.iter().any(|v| v.eq_ignore_ascii_case(vendor_id)))
+            && (self.device_ids.is_empty() || self.device_ids.iter().any(|d| id_matches(d, device_id)))
+    }
+
+    pub fn matches(&self, device: &PciDevice) -> bool {
+        self.bus == Bus::Pci && self.matches_ids(&device.class, &device.vendor_id, &device.device_id)
+    }
+
+    /// Matches USB devices on their interfaces that no driver has claimed;
+    /// a device the kernel already drives needs no profile.
+    pub fn matches_usb(&self, device: &UsbDevice) -> bool {
+        self.bus == Bus::Usb
+            && device.unbound_interfaces().any(|i| {
+                self.matches_ids(&format!("{}{}{}", i.class, i.subclass, i.protocol), &device.vendor_id, &device.product_id)
+            })
+    }
+}
+
+impl ProfileDatabase {
+    fn parse(text: &str) -> Result<ProfileFile, String> {
+        let file: ProfileFile = serde_json::from_str(text).map_err(|e| format!("Invalid driver profile file: {}", e))?;
+        if file.schema != SUPPORTED_SCHEMA {
+            return Err(format!("Unsupported driver profile schema {} (expected {})", file.schema, SUPPORTED_SCHEMA));
+        }
+        Ok(file)
+    }
+
+    pub fn bundled() -> Self {
+        let file = Self::parse(BUNDLED_PROFILES).expect("bundled driver profiles are valid");
+        ProfileDatabase { version: file.version, profiles: file.profiles }
+    }
+
+    /// The bundled profiles merged with local files. Unreadable or
+    /// incompatible local files are skipped.
+    pub fn load() -> Self {
+        let mut db = Self::bundled();
+
+        let Ok(entries) = std::fs::read_dir(LOCAL_PROFILE_DIR) else { return db };
+        let mut files: Vec<_> = entries.filter_map(|e| e.ok()).map(|e| e.path()).filter(|p| p.extension().is_some_and(|x| x == "json")).collect();
+        files.sort();
+        for path in files {
+            let Some(file) = std::fs::read_to_string(&path).ok().and_then(|t| Self::parse(&t).ok()) else { continue };
+            db.version = format!("{}+{}", db.version, Path::new(&path).file_stem().unwrap_or_default().to_string_lossy());
+            for profile in file.profiles {
+                db.profiles.retain(|p| p.id != profile.id);
+                db.profiles.push(profile);
+            }
+        }
+        db
+    }
+
+    pub fn get(&self, id: &str) -> Option<&DriverProfile> {
+        self.profiles.iter().find(|p| p.id == id)
+    }
+
+    /// Profiles matching `device`, highest priority first.
+    pub fn matching(&self, device: &PciDevice) -> Vec<&DriverProfile> {
+        let mut matches: Vec<&DriverProfile> = self.profiles.iter().filter(|p| p.matches(device)).collect();
+        matches.sort_by_key(|p| std::cmp::Reverse(p.priority));
+        matches
+    }
+
+    pub fn matching_usb(&self, device: &UsbDevice) -> Vec<&DriverProfile> {
+        let mut matches: Vec<&DriverProfile> = self.profiles.iter().filter(|p| p.matches_usb(device)).collect();
+        matches.sort_by_key(|p| std::cmp::Reverse(p.priority));
+        matches
+    }
+
+    fn recommend(bus: Bus, address: &str, device: String, profiles: Vec<&DriverProfile>) -> Option<DeviceProfiles> {
+        let recommended = profiles.first()?;
+        let recommended_free = profiles.iter().find(|p| p.free).filter(|p| p.id != recommended.id);
+        Some(DeviceProfiles {
+            bus,
+            address: address.to_string(),
+            device,
+            recommended: Some(recommended.id.clone()),
+            recommended_free: recommended_free.map(|p| p.id.clone()),
+            installed: None,
+            profiles: profiles.into_iter().cloned().collect(),
+        })
+    }
+
+    /// A recommendation for every device that at least one profile matches.
+    pub fn match_devices(&self, devices: &[PciDevice]) -> Vec<DeviceProfiles> {
+        devices.iter().filter_map(|d| Self::recommend(Bus::Pci, &d.address, d.describe(), self.matching(d))).collect()
+    }
+
+    pub fn match_usb_devices(&self, devices: &[UsbDevice]) -> Vec<DeviceProfiles> {
+        devices.iter().filter_map(|d| Self::recommend(Bus::Usb, &d.name, d.describe(), self.matching_usb(d))).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci::fixture_root;
+
+    fn gpu(vendor: &str, device: &str, class: &str) -> PciDevice {
+        PciDevice {
+            address: "0000:01:00.0".into(),
+            class: class.into(),
+            vendor_id: vendor.into(),
+            device_id: device.into(),
+            ..Default::default()
+        }
+    }
+
+    fn recommended(device: &PciDevice) -> Option<String> {
+        ProfileDatabase::bundled().match_devices(std::slice::from_ref(device)).pop().and_then(|m| m.recommended)
+    }
+
+    #[test]
+    fn id_ranges_are_inclusive() {
+        assert!(id_matches("1e00-1fff", "1e00"));
+        assert!(id_matches("1e00-1fff", "1fff"));
+        assert!(!id_matches("1e00-1fff", "2000"));
+        assert!(id_matches("4331", "4331"));
+        assert!(!id_matches("4331", "zz"));
+    }
+
+    #[test]
+    fn nvidia_generations_get_their_driver_branch() {
+        // GTX 1050 Mobile (Pascal), RTX 3060 (Ampere), GTX 770 (Kepler), GTX 480 (Fermi)
+        assert_eq!(recommended(&gpu("10de", "1c8d", "030200")).as_deref(), Some("video-nvidia-580xx"));
+        assert_eq!(recommended(&gpu("10de", "2503", "030000")).as_deref(), Some("video-nvidia-open"));
+        assert_eq!(recommended(&gpu("10de", "1184", "030000")).as_deref(), Some("video-nvidia-470xx"));
+        assert_eq!(recommended(&gpu("10de", "06c0", "030000")).as_deref(), Some("video-nouveau"));
+    }
+
+    #[test]
+    fn free_alternative_is_offered_for_nonfree_recommendations() {
+        let db = ProfileDatabase::bundled();
+        let matched = db.match_devices(&[gpu("10de", "2503", "030000")]).pop().unwrap();
+        assert_eq!(matched.recommended_free.as_deref(), Some("video-nouveau"));
+        assert!(matched.profiles.iter().all(|p| p.id != "video-nvidia-470xx"));
+    }
+
+    #[test]
+    fn amd_and_intel_and_unknown_vendors() {
+        assert_eq!(recommended(&gpu("1002", "73df", "030000")).as_deref(), Some("video-amdgpu"));
+        assert_eq!(recommended(&gpu("1002", "6779", "030000")).as_deref(), Some("video-radeon-legacy"));
+        assert_eq!(recommended(&gpu("8086", "3e9b", "030000")).as_deref(), Some("video-intel"));
+        assert_eq!(recommended(&gpu("1a03", "2000", "030000")).as_deref(), Some("video-modesetting"));
+        // Not a display controller: no video profile applies.
+        assert_eq!(recommended(&gpu("8086", "a348", "040380")), None);
+    }
+
+    #[test]
+    fn unbound_usb_interfaces_match_usb_profiles() {
+        let root = fixture_root();
+        let devices = crate::usb::enumerate(&root, &crate::hwids::IdDatabase::default());
+        let matched = ProfileDatabase::bundled().match_usb_devices(&devices);
+
+        let found: Vec<(&str, Option<&str>)> = matched.iter().map(|m| (m.address.as_str(), m.recommended.as_deref())).collect();
+        // The webcam is bound to uvcvideo and the hub to hub, so only the
+        // fingerprint reader and the Wi-Fi adapter need a profile.
+        assert_eq!(found, [("1-4", Some("usb-fingerprint-fprintd")), ("2-1", Some("usb-wifi-rtl8812au"))]);
+        assert!(matched.iter().all(|m| m.bus == Bus::Usb));
+        // USB profiles never match PCI devices.
+        assert_eq!(recommended(&gpu("0bda", "8812", "ff0000")), None);
+    }
+}
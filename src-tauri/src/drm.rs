@@ -0,0 +1,213 @@
+// src/drm.rs
+//
+// Which GPU drives which display, from the DRM class in sysfs. A card is
+// active when one of its connectors has a connected, enabled monitor; on a
+// headless machine the firmware's boot_vga device is used instead. Each
+// connector's EDID identifies the monitor behind it (see edid.rs).
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+
+use crate::edid::{self, Edid};
+use crate::pci::{link_name, read_attr};
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DrmConnector {
+    /// Connector name without the card prefix, e.g. "eDP-1" or "HDMI-A-1".
+    pub name: String,
+    /// "connected", "disconnected" or "unknown".
+    pub status: String,
+    pub enabled: bool,
+    /// e.g. "Built-in", "HDMI" or "DisplayPort".
+    pub connector_type: String,
+    /// The attached monitor's EDID; None when nothing is attached or the
+    /// blob could not be parsed.
+    pub edid: Option<Edid>,
+}
+
+impl DrmConnector {
+    pub fn has_monitor(&self) -> bool {
+        self.status == "connected" && self.enabled
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DrmCard {
+    /// e.g. "card0"
+    pub name: String,
+    pub pci_address: Option<String>,
+    pub driver: Option<String>,
+    pub boot_vga: bool,
+    pub connectors: Vec<DrmConnector>,
+    pub active: bool,
+}
+
+impl DrmCard {
+    /// Connectors with a monitor attached.
+    pub fn monitors(&self) -> Vec<DrmConnector> {
+        self.connectors.iter().filter(|c| c.has_monitor()).cloned().collect()
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Helpers
+// -----------------------------------------------------------------------------
+/// Connector kind from the kernel's connector name, e.g. "HDMI-A-1".
+fn connector_type(name: &str) -> String {
+    let kind = name.rsplit_once('-').map_or(name, |(kind, _)| kind);
+    match kind {
+        "eDP" | "LVDS" | "DSI" => "Built-in",
+        "HDMI-A" | "HDMI-B" => "HDMI",
+        "DP" => "DisplayPort",
+        "DVI-D" | "DVI-I" | "DVI-A" => "DVI",
+        "VGA" => "VGA",
+        "Virtual" => "Virtual",
+        "Writeback" => "Writeback",
+        "USB" => "USB",
+        other => other,
+    }
+    .to_string()
+}
+
+/// The EDID sysfs attribute is empty while nothing is attached.
+fn read_edid(dir: &Path) -> Option<Edid> {
+    let data = std::fs::read(dir.join("edid")).ok().filter(|d| !d.is_empty())?;
+    edid::parse(&data).ok()
+}
+
+fn is_card(name: &str) -> bool {
+    name.strip_prefix("card").is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Marks the cards that drive a monitor, or the boot_vga card when no
+/// monitor is connected anywhere.
+pub fn mark_active(cards: &mut [DrmCard]) {
+    let any_monitor = cards.iter().any(|c| c.connectors.iter().any(DrmConnector::has_monitor));
+    for card in cards.iter_mut() {
+        card.active = if any_monitor { card.connectors.iter().any(DrmConnector::has_monitor) } else { card.boot_vga };
+    }
+}
+
+/// DRM cards under `<sysfs_root>/class/drm` with their connectors and the
+/// EDID of each attached monitor.
+pub fn enumerate(sysfs_root: &Path) -> Vec<DrmCard> {
+    let class_dir = sysfs_root.join("class/drm");
+    let Ok(entries) = std::fs::read_dir(&class_dir) else { return Vec::new() };
+    let mut names: Vec<String> = entries.filter_map(|e| e.ok()).map(|e| e.file_name().to_string_lossy().to_string()).collect();
+    names.sort();
+
+    let mut cards: Vec<DrmCard> = names
+        .iter()
+        .filter(|n| is_card(n))
+        .map(|card| {
+            let device = class_dir.join(card).join("device");
+            let prefix = format!("{}-", card);
+            DrmCard {
+                name: card.clone(),
+                pci_address: link_name(&device),
+                driver: link_name(&device.join("driver")),
+                boot_vga: read_attr(&device, "boot_vga").as_deref() == Some("1"),
+                connectors: names
+                    .iter()
+                    .filter_map(|n| n.strip_prefix(&prefix))
+                    .map(|connector| {
+                        let dir = class_dir.join(format!("{}{}", prefix, connector));
+                        DrmConnector {
+                            name: connector.to_string(),
+                            status: read_attr(&dir, "status").unwrap_or_else(|| "unknown".to_string()),
+                            enabled: read_attr(&dir, "enabled").as_deref() == Some("enabled"),
+                            connector_type: connector_type(connector),
+                            edid: read_edid(&dir),
+                        }
+                    })
+                    .collect(),
+                active: false,
+            }
+        })
+        .collect();
+    mark_active(&mut cards);
+    cards
+}
+
+/// The OpenGL renderer string of the current session, if there is a display
+/// to ask and glxinfo or eglinfo (mesa-utils) is installed.
+pub fn gl_renderer() -> Option<String> {
+    if std::env::var_os("DISPLAY").is_none() && std::env::var_os("WAYLAND_DISPLAY").is_none() {
+        return None;
+    }
+    let probes: [(&str, &[&str]); 2] = [("glxinfo", &["-B"]), ("eglinfo", &["-B"])];
+    probes.iter().find_map(|(cmd, args)| {
+        let output = Command::new(cmd).args(*args).env("LC_ALL", "C").output().ok()?;
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .find_map(|l| l.trim().strip_prefix("OpenGL renderer string:").or_else(|| l.trim().strip_prefix("OpenGL core profile renderer:")))
+            .map(|r| r.trim().to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci::fixture_root;
+
+    fn connector(name: &str, status: &str, enabled: bool) -> DrmConnector {
+        DrmConnector { name: name.to_string(), status: status.to_string(), enabled, ..Default::default() }
+    }
+
+    #[test]
+    fn reads_cards_and_connectors_from_fixture_tree() {
+        let root = fixture_root();
+        let cards = enumerate(&root);
+
+        assert_eq!(cards.len(), 1);
+        let card = &cards[0];
+        assert_eq!(card.name, "card0");
+        assert_eq!(card.pci_address.as_deref(), Some("0000:00:02.0"));
+        assert_eq!(card.driver.as_deref(), Some("i915"));
+        assert!(card.boot_vga);
+        assert_eq!(card.connectors.len(), 3);
+        assert!(card.active);
+        let monitors: Vec<Option<String>> = card.monitors().iter().map(|m| m.edid.as_ref().and_then(|e| e.model.clone())).collect();
+        assert_eq!(monitors, [Some("DELL U2720Q".to_string()), Some("LQ156D1".to_string())]);
+        assert_eq!(card.monitors()[1].connector_type, "Built-in");
+        assert_eq!(card.monitors()[1].edid.as_ref().and_then(|e| e.diagonal_inches), Some(15.6));
+
+        let dp = card.connectors.iter().find(|c| c.name == "DP-1").unwrap();
+        assert_eq!(dp.connector_type, "DisplayPort");
+        assert!(dp.edid.is_none(), "an empty edid attribute means nothing is attached");
+    }
+
+    #[test]
+    fn monitor_on_discrete_gpu_makes_it_active() {
+        let mut cards = vec![
+            DrmCard {
+                name: "card0".into(),
+                boot_vga: true,
+                connectors: vec![connector("eDP-1", "connected", false)],
+                ..Default::default()
+            },
+            DrmCard {
+                name: "card1".into(),
+                connectors: vec![connector("DP-1", "connected", true)],
+                ..Default::default()
+            },
+        ];
+        mark_active(&mut cards);
+        assert!(!cards[0].active);
+        assert!(cards[1].active);
+    }
+
+    #[test]
+    fn headless_falls_back_to_boot_vga() {
+        let mut cards = vec![
+            DrmCard { name: "card0".into(), connectors: vec![connector("VGA-1", "disconnected", false)], ..Default::default() },
+            DrmCard { name: "card1".into(), boot_vga: true, ..Default::default() },
+        ];
+        mark_active(&mut cards);
+        assert!(!cards[0].active);
+        assert!(cards[1].active);
+    }
+}
@@ -0,0 +1,533 @@
+// src/edid.rs
+//
+// EDID parser for the blobs the kernel exposes in /sys/class/drm/*/edid:
+// the 128-byte base block (EDID 1.3/1.4) and CTA-861 extension blocks, which
+// carry the HDMI modes and the HDR and colorimetry capabilities. DisplayID
+// extensions are counted but not decoded.
+use serde::{Deserialize, Serialize};
+
+// -----------------------------------------------------------------------------
+// Configuration
+// -----------------------------------------------------------------------------
+const BLOCK: usize = 128;
+const HEADER: [u8; 8] = [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+const CTA_EXTENSION: u8 = 0x02;
+
+/// PNP IDs of common monitor and panel makers.
+const MANUFACTURERS: [(&str, &str); 30] = [
+    ("ACR", "Acer"),
+    ("AOC", "AOC"),
+    ("APP", "Apple"),
+    ("AUO", "AU Optronics"),
+    ("AUS", "ASUS"),
+    ("BNQ", "BenQ"),
+    ("BOE", "BOE"),
+    ("CMN", "Chimei Innolux"),
+    ("DEL", "Dell"),
+    ("EIZ", "EIZO"),
+    ("ENC", "EIZO"),
+    ("GBT", "Gigabyte"),
+    ("GSM", "LG Electronics"),
+    ("HSD", "HannStar"),
+    ("HWP", "HP"),
+    ("IVM", "Iiyama"),
+    ("LEN", "Lenovo"),
+    ("LGD", "LG Display"),
+    ("MEI", "Panasonic"),
+    ("MSI", "MSI"),
+    ("NEC", "NEC"),
+    ("PHL", "Philips"),
+    ("SAM", "Samsung"),
+    ("SDC", "Samsung Display"),
+    ("SHP", "Sharp"),
+    ("SNY", "Sony"),
+    ("TSB", "Toshiba"),
+    ("VSC", "ViewSonic"),
+    ("XMI", "Xiaomi"),
+    ("RHT", "Red Hat (QEMU)"),
+];
+
+/// Established timings, bit by bit from byte 35 to byte 37.
+const ESTABLISHED: [(u32, u32, f64); 17] = [
+    (720, 400, 70.0),
+    (720, 400, 88.0),
+    (640, 480, 60.0),
+    (640, 480, 67.0),
+    (640, 480, 72.0),
+    (640, 480, 75.0),
+    (800, 600, 56.0),
+    (800, 600, 60.0),
+    (800, 600, 72.0),
+    (800, 600, 75.0),
+    (832, 624, 75.0),
+    (1024, 768, 87.0),
+    (1024, 768, 60.0),
+    (1024, 768, 70.0),
+    (1024, 768, 75.0),
+    (1280, 1024, 75.0),
+    (1152, 870, 75.0),
+];
+
+/// CTA-861 video identification codes for the common TV and monitor modes:
+/// (VIC, width, height, refresh, interlaced).
+const VICS: [(u8, u32, u32, f64, bool); 30] = [
+    (1, 640, 480, 60.0, false),
+    (2, 720, 480, 60.0, false),
+    (3, 720, 480, 60.0, false),
+    (4, 1280, 720, 60.0, false),
+    (5, 1920, 1080, 60.0, true),
+    (16, 1920, 1080, 60.0, false),
+    (17, 720, 576, 50.0, false),
+    (18, 720, 576, 50.0, false),
+    (19, 1280, 720, 50.0, false),
+    (20, 1920, 1080, 50.0, true),
+    (31, 1920, 1080, 50.0, false),
+    (32, 1920, 1080, 24.0, false),
+    (33, 1920, 1080, 25.0, false),
+    (34, 1920, 1080, 30.0, false),
+    (47, 1280, 720, 120.0, false),
+    (63, 1920, 1080, 120.0, false),
+    (64, 1920, 1080, 100.0, false),
+    (93, 3840, 2160, 24.0, false),
+    (94, 3840, 2160, 25.0, false),
+    (95, 3840, 2160, 30.0, false),
+    (96, 3840, 2160, 50.0, false),
+    (97, 3840, 2160, 60.0, false),
+    (98, 4096, 2160, 24.0, false),
+    (99, 4096, 2160, 25.0, false),
+    (100, 4096, 2160, 30.0, false),
+    (101, 4096, 2160, 50.0, false),
+    (102, 4096, 2160, 60.0, false),
+    (117, 3840, 2160, 100.0, false),
+    (118, 3840, 2160, 120.0, false),
+    (196, 7680, 4320, 30.0, false),
+];
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DisplayMode {
+    pub width: u32,
+    pub height: u32,
+    /// Hz, rounded to two decimals.
+    pub refresh: f64,
+    pub interlaced: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct RangeLimits {
+    pub min_vertical_hz: u32,
+    pub max_vertical_hz: u32,
+    pub min_horizontal_khz: u32,
+    pub max_horizontal_khz: u32,
+    pub max_pixel_clock_mhz: Option<u32>,
+}
+
+/// CIE 1931 xy coordinates.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Chromaticity {
+    pub red: (f64, f64),
+    pub green: (f64, f64),
+    pub blue: (f64, f64),
+    pub white: (f64, f64),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct HdrInfo {
+    /// "SDR", "HDR (traditional gamma)", "PQ (SMPTE ST 2084)", "HLG".
+    pub eotfs: Vec<String>,
+    /// cd/m², from the desired content luminance fields.
+    pub max_luminance: Option<f64>,
+    pub max_frame_average: Option<f64>,
+    pub min_luminance: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ColourInfo {
+    /// Bits per primary colour (EDID 1.4 digital inputs).
+    pub bit_depth: Option<u8>,
+    /// sRGB is the default colour space.
+    pub srgb: bool,
+    pub chromaticity: Chromaticity,
+    pub bt2020: bool,
+    pub dci_p3: bool,
+    pub hdr: Option<HdrInfo>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Edid {
+    pub version: String,
+    /// Three-letter PNP ID, e.g. "DEL".
+    pub manufacturer_id: String,
+    pub manufacturer: Option<String>,
+    pub product_code: u16,
+    /// Display name descriptor, or the panel's model text on laptops.
+    pub model: Option<String>,
+    /// Serial descriptor, or the numeric serial when there is none.
+    pub serial: Option<String>,
+    pub manufacture_year: Option<u16>,
+    pub manufacture_week: Option<u8>,
+    pub digital: bool,
+    /// Digital interface named by EDID 1.4, e.g. "DisplayPort".
+    pub interface: Option<String>,
+    pub width_mm: Option<u32>,
+    pub height_mm: Option<u32>,
+    pub diagonal_inches: Option<f64>,
+    /// The preferred detailed timing.
+    pub native_mode: Option<DisplayMode>,
+    /// Every advertised mode, largest and fastest first.
+    pub modes: Vec<DisplayMode>,
+    pub range: Option<RangeLimits>,
+    pub colour: ColourInfo,
+    pub extensions: u8,
+    /// Whether every block's checksum matched.
+    pub checksum_valid: bool,
+}
+
+// -----------------------------------------------------------------------------
+// Helpers
+// -----------------------------------------------------------------------------
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+fn mode(width: u32, height: u32, refresh: f64, interlaced: bool) -> DisplayMode {
+    DisplayMode { width, height, refresh: round2(refresh), interlaced }
+}
+
+fn block_checksum_ok(block: &[u8]) -> bool {
+    block.iter().fold(0u8, |sum, b| sum.wrapping_add(*b)) == 0
+}
+
+fn pnp_id(bytes: [u8; 2]) -> String {
+    let value = u16::from_be_bytes(bytes);
+    [(value >> 10) & 0x1f, (value >> 5) & 0x1f, value & 0x1f].iter().map(|c| (b'@' + *c as u8) as char).collect()
+}
+
+/// Text of a display descriptor: up to 13 bytes ending at a newline.
+fn descriptor_text(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|b| *b == b'\n').unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).trim().to_string()
+}
+
+/// An 18-byte detailed timing descriptor: (mode, width_mm, height_mm).
+fn detailed_timing(d: &[u8]) -> Option<(DisplayMode, u32, u32)> {
+    let clock = u16::from_le_bytes([d[0], d[1]]) as f64 * 10_000.0;
+    if clock == 0.0 {
+        return None;
+    }
+    let h_active = d[2] as u32 | ((d[4] as u32 >> 4) << 8);
+    let h_blank = d[3] as u32 | ((d[4] as u32 & 0x0f) << 8);
+    let v_active = d[5] as u32 | ((d[7] as u32 >> 4) << 8);
+    let v_blank = d[6] as u32 | ((d[7] as u32 & 0x0f) << 8);
+    let width_mm = d[12] as u32 | ((d[14] as u32 >> 4) << 8);
+    let height_mm = d[13] as u32 | ((d[14] as u32 & 0x0f) << 8);
+    let interlaced = d[17] & 0x80 != 0;
+    let total = ((h_active + h_blank) * (v_active + v_blank)) as f64;
+    if total == 0.0 {
+        return None;
+    }
+    // An interlaced timing describes one field.
+    let (height, refresh) = if interlaced { (v_active * 2, clock / total * 2.0) } else { (v_active, clock / total) };
+    Some((mode(h_active, height, refresh, interlaced), width_mm, height_mm))
+}
+
+/// A two-byte standard timing; `version_minor` decides what aspect code 0 means.
+fn standard_timing(b: [u8; 2], version_minor: u8) -> Option<DisplayMode> {
+    if b == [0x01, 0x01] || b[0] == 0 {
+        return None;
+    }
+    let width = (b[0] as u32 + 31) * 8;
+    let height = match b[1] >> 6 {
+        0 if version_minor < 3 => width,
+        0 => width * 10 / 16,
+        1 => width * 3 / 4,
+        2 => width * 4 / 5,
+        _ => width * 9 / 16,
+    };
+    Some(mode(width, height, (b[1] & 0x3f) as f64 + 60.0, false))
+}
+
+/// 10-bit chromaticity coordinates from bytes 25-34.
+fn chromaticity(b: &[u8]) -> Chromaticity {
+    let coord = |high: u8, low: u8, shift: u8| ((high as u32) << 2 | ((low >> shift) & 0x03) as u32) as f64 / 1024.0;
+    let point = |hx: usize, hy: usize, low: u8, sx: u8, sy: u8| (round3(coord(b[hx], low, sx)), round3(coord(b[hy], low, sy)));
+    Chromaticity {
+        red: point(2, 3, b[0], 6, 4),
+        green: point(4, 5, b[0], 2, 0),
+        blue: point(6, 7, b[1], 6, 4),
+        white: point(8, 9, b[1], 2, 0),
+    }
+}
+
+fn round3(value: f64) -> f64 {
+    (value * 1000.0).round() / 1000.0
+}
+
+fn hdr_static_metadata(payload: &[u8]) -> HdrInfo {
+    const EOTFS: [&str; 4] = ["SDR", "HDR (traditional gamma)", "PQ (SMPTE ST 2084)", "HLG"];
+    let eotf_bits = payload.first().copied().unwrap_or(0);
+    let eotfs = EOTFS.iter().enumerate().filter(|(i, _)| eotf_bits & (1 << i) != 0).map(|(_, n)| n.to_string()).collect();
+    // CTA-861.3: luminance = 50 * 2^(CV/32); minimum is a fraction of the maximum.
+    let luminance = |cv: Option<&u8>| cv.filter(|v| **v != 0).map(|v| round2(50.0 * 2f64.powf(*v as f64 / 32.0)));
+    let max_luminance = luminance(payload.get(2));
+    let min_luminance = payload
+        .get(4)
+        .zip(max_luminance)
+        .map(|(cv, max)| (max * (*cv as f64 / 255.0).powi(2) / 100.0 * 10_000.0).round() / 10_000.0);
+    HdrInfo { eotfs, max_luminance, max_frame_average: luminance(payload.get(3)), min_luminance }
+}
+
+/// Modes and colour capabilities from a CTA-861 extension block.
+fn parse_cta(block: &[u8], modes: &mut Vec<DisplayMode>, colour: &mut ColourInfo) {
+    let dtd_offset = (block[2] as usize).clamp(4, BLOCK - 1);
+    let mut i = 4;
+    while i < dtd_offset {
+        let tag = block[i] >> 5;
+        let len = (block[i] & 0x1f) as usize;
+        let payload = &block[(i + 1).min(dtd_offset)..(i + 1 + len).min(dtd_offset)];
+        match tag {
+            // Video data block: short video descriptors.
+            2 => {
+                for svd in payload {
+                    // Bit 7 flags a native mode for VICs 1-64 only.
+                    let vic = if (129..=192).contains(svd) { svd & 0x7f } else { *svd };
+                    if let Some((_, w, h, r, interlaced)) = VICS.iter().find(|v| v.0 == vic) {
+                        modes.push(mode(*w, *h, *r, *interlaced));
+                    }
+                }
+            }
+            // Extended tags: colorimetry (5) and HDR static metadata (6).
+            7 => match payload.first() {
+                Some(5) => {
+                    let flags = payload.get(1).copied().unwrap_or(0);
+                    colour.bt2020 |= flags & 0xe0 != 0;
+                    colour.dci_p3 |= payload.get(2).is_some_and(|b| b & 0x80 != 0);
+                }
+                Some(6) => colour.hdr = Some(hdr_static_metadata(&payload[1..])),
+                _ => {}
+            },
+            _ => {}
+        }
+        i += 1 + len;
+    }
+    let mut offset = dtd_offset;
+    while offset + 18 < BLOCK {
+        match detailed_timing(&block[offset..offset + 18]) {
+            Some((m, _, _)) => modes.push(m),
+            None => break,
+        }
+        offset += 18;
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Parsing
+// -----------------------------------------------------------------------------
+/// Parses an EDID blob. Checksum errors are reported in the result rather
+/// than rejected, as many monitors ship with a bad extension checksum.
+pub fn parse(data: &[u8]) -> Result<Edid, String> {
+    if data.len() < BLOCK {
+        return Err(format!("EDID too short: {} bytes", data.len()));
+    }
+    if data[..8] != HEADER {
+        return Err("Not an EDID: bad header".to_string());
+    }
+    let base = &data[..BLOCK];
+    let version_minor = base[19];
+    let mut edid = Edid {
+        version: format!("{}.{}", base[18], version_minor),
+        manufacturer_id: pnp_id([base[8], base[9]]),
+        product_code: u16::from_le_bytes([base[10], base[11]]),
+        extensions: base[126],
+        digital: base[20] & 0x80 != 0,
+        ..Default::default()
+    };
+    edid.manufacturer = MANUFACTURERS.iter().find(|(id, _)| *id == edid.manufacturer_id).map(|(_, n)| n.to_string());
+
+    let serial_number = u32::from_le_bytes([base[12], base[13], base[14], base[15]]);
+    // Week 0xff marks the year as a model year rather than a manufacture date.
+    edid.manufacture_week = Some(base[16]).filter(|w| (1..=54).contains(w));
+    edid.manufacture_year = Some(base[17] as u16 + 1990).filter(|_| base[17] != 0);
+
+    if edid.digital && version_minor >= 4 {
+        edid.colour.bit_depth = match (base[20] >> 4) & 0x07 {
+            0 | 7 => None,
+            n => Some(4 + n * 2),
+        };
+        edid.interface = match base[20] & 0x0f {
+            1 => Some("DVI"),
+            2 | 3 => Some("HDMI"),
+            4 => Some("MDDI"),
+            5 => Some("DisplayPort"),
+            _ => None,
+        }
+        .map(str::to_string);
+    }
+    edid.colour.srgb = base[24] & 0x04 != 0;
+    edid.colour.chromaticity = chromaticity(&base[25..35]);
+
+    let mut modes = Vec::new();
+    let established = u32::from_be_bytes([0, base[35], base[36], base[37]]);
+    for (bit, (w, h, r)) in ESTABLISHED.iter().enumerate() {
+        if established & (1 << (23 - bit)) != 0 {
+            modes.push(mode(*w, *h, *r, false));
+        }
+    }
+    modes.extend(base[38..54].chunks(2).filter_map(|b| standard_timing([b[0], b[1]], version_minor)));
+
+    let mut unspecified_text = Vec::new();
+    let mut serial_text = None;
+    for descriptor in base[54..126].chunks(18) {
+        if let Some((m, width_mm, height_mm)) = detailed_timing(descriptor) {
+            if edid.native_mode.is_none() {
+                edid.native_mode = Some(m.clone());
+                if width_mm > 0 && height_mm > 0 {
+                    edid.width_mm = Some(width_mm);
+                    edid.height_mm = Some(height_mm);
+                }
+            }
+            modes.push(m);
+            continue;
+        }
+        let text = &descriptor[5..18];
+        match descriptor[3] {
+            0xff => serial_text = Some(descriptor_text(text)),
+            0xfe => unspecified_text.push(descriptor_text(text)),
+            0xfc => edid.model = Some(descriptor_text(text)),
+            0xfd => {
+                let d = descriptor;
+                // EDID 1.4 offset flags add 255 to the rates.
+                let add = |flag: u8| if d[4] & flag != 0 { 255 } else { 0 };
+                edid.range = Some(RangeLimits {
+                    min_vertical_hz: d[5] as u32 + if d[4] & 0x03 == 0x03 { 255 } else { 0 },
+                    max_vertical_hz: d[6] as u32 + add(0x02),
+                    min_horizontal_khz: d[7] as u32 + if d[4] & 0x0c == 0x0c { 255 } else { 0 },
+                    max_horizontal_khz: d[8] as u32 + add(0x08),
+                    max_pixel_clock_mhz: Some(d[9] as u32 * 10).filter(|c| *c > 0),
+                });
+            }
+            _ => {}
+        }
+    }
+    // Laptop panels carry the maker and part number as unspecified text.
+    if edid.model.is_none() {
+        edid.model = unspecified_text.into_iter().rfind(|t| !t.is_empty());
+    }
+    edid.serial = serial_text.filter(|s| !s.is_empty()).or_else(|| (serial_number != 0).then(|| serial_number.to_string()));
+
+    if edid.width_mm.is_none() && base[21] > 0 && base[22] > 0 {
+        edid.width_mm = Some(base[21] as u32 * 10);
+        edid.height_mm = Some(base[22] as u32 * 10);
+    }
+    edid.diagonal_inches = edid
+        .width_mm
+        .zip(edid.height_mm)
+        .map(|(w, h)| ((w as f64).hypot(h as f64) / 25.4 * 10.0).round() / 10.0);
+
+    let mut checksum_valid = block_checksum_ok(base);
+    for block in data[BLOCK..].chunks_exact(BLOCK).take(edid.extensions as usize) {
+        checksum_valid &= block_checksum_ok(block);
+        if block[0] == CTA_EXTENSION {
+            parse_cta(block, &mut modes, &mut edid.colour);
+        }
+    }
+    edid.checksum_valid = checksum_valid;
+
+    modes.sort_by(|a, b| {
+        (b.width * b.height).cmp(&(a.width * a.height)).then(b.refresh.total_cmp(&a.refresh)).then(a.interlaced.cmp(&b.interlaced))
+    });
+    modes.dedup();
+    edid.modes = modes;
+    Ok(edid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    // Three monitors as they identify themselves: a 15.6" UHD laptop panel on
+    // eDP (EDID 1.4, no extensions), a 27" UHD monitor on HDMI with a CTA-861
+    // block and HDR10 metadata, and a 19" 5:4 VGA monitor (EDID 1.3).
+    fn fixture(name: &str) -> Edid {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/edid").join(name);
+        parse(&std::fs::read(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn laptop_panel() {
+        let edid = fixture("sharp-lq156d1.bin");
+        assert_eq!(edid.version, "1.4");
+        assert_eq!(edid.manufacturer_id, "SHP");
+        assert_eq!(edid.manufacturer.as_deref(), Some("Sharp"));
+        assert_eq!(edid.model.as_deref(), Some("LQ156D1"));
+        assert_eq!(edid.serial, None);
+        assert_eq!(edid.interface.as_deref(), Some("DisplayPort"));
+        assert_eq!(edid.colour.bit_depth, Some(8));
+        assert_eq!((edid.width_mm, edid.height_mm), (Some(346), Some(194)));
+        assert_eq!(edid.diagonal_inches, Some(15.6));
+        assert_eq!(edid.native_mode, Some(mode(3840, 2160, 60.0, false)));
+        assert_eq!(edid.modes, [mode(3840, 2160, 60.0, false), mode(3840, 2160, 48.0, false)]);
+        assert!(edid.colour.hdr.is_none());
+        assert!(edid.checksum_valid);
+    }
+
+    #[test]
+    fn hdmi_monitor_with_cta_extension() {
+        let edid = fixture("dell-u2720q.bin");
+        assert_eq!(edid.version, "1.3");
+        assert_eq!(edid.manufacturer.as_deref(), Some("Dell"));
+        assert_eq!(edid.model.as_deref(), Some("DELL U2720Q"));
+        assert_eq!(edid.serial.as_deref(), Some("F8KBB83"));
+        assert_eq!((edid.manufacture_year, edid.manufacture_week), (Some(2021), Some(12)));
+        assert_eq!(edid.diagonal_inches, Some(27.0));
+        assert_eq!(edid.extensions, 1);
+        assert!(edid.checksum_valid);
+
+        assert_eq!(edid.native_mode, Some(mode(3840, 2160, 60.0, false)));
+        assert_eq!(edid.modes[0], mode(3840, 2160, 60.0, false));
+        assert!(edid.modes.contains(&mode(3840, 2160, 24.0, false)));
+        assert!(edid.modes.contains(&mode(1920, 1080, 60.0, false)));
+        assert!(edid.modes.contains(&mode(1680, 1050, 60.0, false)));
+        assert!(edid.modes.contains(&mode(640, 480, 60.0, false)));
+        assert_eq!(edid.modes.iter().filter(|m| **m == mode(1920, 1080, 60.0, false)).count(), 1);
+
+        let range = edid.range.unwrap();
+        assert_eq!((range.min_vertical_hz, range.max_vertical_hz), (24, 76));
+        assert_eq!(range.max_pixel_clock_mhz, Some(600));
+
+        assert!(edid.colour.bt2020);
+        assert!(!edid.colour.dci_p3);
+        let hdr = edid.colour.hdr.unwrap();
+        assert_eq!(hdr.eotfs, ["SDR", "PQ (SMPTE ST 2084)"]);
+        assert_eq!(hdr.max_luminance, Some(400.0));
+        assert_eq!(hdr.min_luminance, Some(0.2442));
+        assert_eq!(edid.colour.chromaticity.white, (0.313, 0.329));
+    }
+
+    #[test]
+    fn analog_edid_1_3_monitor() {
+        let edid = fixture("dell-1907fp.bin");
+        assert!(!edid.digital);
+        assert_eq!(edid.interface, None);
+        assert_eq!(edid.model.as_deref(), Some("DELL 1907FP"));
+        assert_eq!(edid.serial.as_deref(), Some("UY0137A4AA4L"));
+        assert_eq!(edid.native_mode, Some(mode(1280, 1024, 60.02, false)));
+        assert!(edid.modes.contains(&mode(1280, 1024, 75.0, false)));
+        assert!(edid.modes.contains(&mode(1152, 864, 75.0, false)));
+        assert!(edid.modes.contains(&mode(720, 400, 70.0, false)));
+        assert!(edid.colour.srgb);
+        assert_eq!(edid.diagonal_inches, Some(19.0));
+    }
+
+    #[test]
+    fn damaged_blobs() {
+        let mut data = std::fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/edid/dell-u2720q.bin")).unwrap();
+        data[200] ^= 0xff;
+        assert!(!parse(&data).unwrap().checksum_valid);
+        assert!(parse(&data[..100]).is_err());
+        assert!(parse(&[0u8; 128]).is_err());
+    }
+}
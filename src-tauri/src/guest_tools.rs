@@ -0,0 +1,251 @@
+// src/guest_tools.rs
+//
+// Guest additions for the hypervisor we run under (see platform.rs): which
+// packages, services and kernel modules make up a working guest, whether
+// they are in place, and installing whatever is missing.
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+use tauri::AppHandle;
+
+use crate::pacman_manager::emit_progress;
+use crate::partial_upgrade;
+use crate::pci;
+use crate::platform;
+use crate::privileged;
+
+// -----------------------------------------------------------------------------
+// Configuration
+// -----------------------------------------------------------------------------
+struct GuestToolset {
+    /// systemd-detect-virt names this toolset applies to.
+    virtualization: &'static [&'static str],
+    name: &'static str,
+    packages: &'static [&'static str],
+    services: &'static [&'static str],
+    /// Services udev starts when the host provides their channel; they
+    /// have nothing to enable.
+    udev_started: &'static [&'static str],
+    modules: &'static [&'static str],
+    wiki_url: &'static str,
+}
+
+const TOOLSETS: [GuestToolset; 4] = [
+    GuestToolset {
+        virtualization: &["oracle"],
+        name: "VirtualBox Guest Additions",
+        packages: &["virtualbox-guest-utils"],
+        services: &["vboxservice.service"],
+        udev_started: &[],
+        modules: &["vboxguest", "vboxsf"],
+        wiki_url: "https://wiki.archlinux.org/title/VirtualBox/Install_Arch_Linux_as_a_guest",
+    },
+    GuestToolset {
+        virtualization: &["kvm", "qemu"],
+        name: "QEMU guest agent and SPICE",
+        packages: &["qemu-guest-agent", "spice-vdagent"],
+        services: &["qemu-guest-agent.service", "spice-vdagentd.service"],
+        udev_started: &["qemu-guest-agent.service", "spice-vdagentd.service"],
+        modules: &["virtio_console"],
+        wiki_url: "https://wiki.archlinux.org/title/QEMU#QEMU_guest_agent",
+    },
+    GuestToolset {
+        virtualization: &["vmware"],
+        name: "Open VM Tools",
+        packages: &["open-vm-tools"],
+        services: &["vmtoolsd.service", "vmware-vmblock-fuse.service"],
+        udev_started: &[],
+        modules: &["vmw_vmci", "vmwgfx"],
+        wiki_url: "https://wiki.archlinux.org/title/VMware/Install_Arch_Linux_as_a_guest",
+    },
+    GuestToolset {
+        virtualization: &["microsoft"],
+        name: "Hyper-V integration services",
+        packages: &["hyperv"],
+        services: &["hv_kvp_daemon.service", "hv_vss_daemon.service"],
+        udev_started: &[],
+        modules: &["hv_vmbus", "hv_utils"],
+        wiki_url: "https://wiki.archlinux.org/title/Hyper-V",
+    },
+];
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GuestPackage {
+    pub name: String,
+    pub installed: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GuestService {
+    pub name: String,
+    /// `systemctl is-enabled` output, e.g. "enabled", "disabled" or "static".
+    pub enabled: String,
+    pub active: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GuestModule {
+    pub name: String,
+    pub loaded: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GuestTools {
+    pub virtualization: String,
+    pub name: String,
+    pub packages: Vec<GuestPackage>,
+    pub services: Vec<GuestService>,
+    pub modules: Vec<GuestModule>,
+    pub wiki_url: String,
+    /// Every package installed and every service running or set to start.
+    pub complete: bool,
+}
+
+// -----------------------------------------------------------------------------
+// Helpers
+// -----------------------------------------------------------------------------
+fn toolset_for(virtualization: &str) -> Option<&'static GuestToolset> {
+    TOOLSETS.iter().find(|t| t.virtualization.contains(&virtualization))
+}
+
+fn systemctl(args: &[&str]) -> String {
+    Command::new("systemctl")
+        .args(args)
+        .env("LC_ALL", "C")
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_default()
+}
+
+fn package_installed(name: &str) -> bool {
+    Command::new("pacman").args(["-Q", name]).output().is_ok_and(|o| o.status.success())
+}
+
+/// Loaded as a module or built into the kernel.
+fn module_loaded(name: &str) -> bool {
+    Path::new("/sys/module").join(name).exists()
+}
+
+fn status(virtualization: &str, toolset: &GuestToolset) -> GuestTools {
+    let packages: Vec<GuestPackage> =
+        toolset.packages.iter().map(|p| GuestPackage { name: p.to_string(), installed: package_installed(p) }).collect();
+    let services: Vec<GuestService> = toolset
+        .services
+        .iter()
+        .map(|s| GuestService {
+            name: s.to_string(),
+            enabled: systemctl(&["is-enabled", s]),
+            active: systemctl(&["is-active", s]) == "active",
+        })
+        .collect();
+    let modules = toolset.modules.iter().map(|m| GuestModule { name: m.to_string(), loaded: module_loaded(m) }).collect();
+    GuestTools {
+        virtualization: virtualization.to_string(),
+        name: toolset.name.to_string(),
+        complete: packages.iter().all(|p| p.installed) && services.iter().all(|s| s.active || s.enabled != "disabled"),
+        packages,
+        services,
+        modules,
+        wiki_url: toolset.wiki_url.to_string(),
+    }
+}
+
+/// Guest tools status for this machine; None on bare metal or under a
+/// hypervisor without guest tools in the repositories.
+pub fn detect(virtualization: Option<&str>) -> Option<GuestTools> {
+    let virtualization = virtualization?;
+    toolset_for(virtualization).map(|t| status(virtualization, t))
+}
+
+fn current() -> Option<GuestTools> {
+    let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").unwrap_or_default();
+    let platform = platform::read(Path::new(pci::SYSFS_ROOT), &cpuinfo);
+    detect(platform.virtualization.as_deref())
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: status
+// -----------------------------------------------------------------------------
+#[tauri::command]
+pub fn get_guest_tools() -> Result<Option<GuestTools>, String> {
+    Ok(current())
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: install
+// -----------------------------------------------------------------------------
+/// Installs the missing guest packages and enables and starts their services.
+/// Refused when the partial-upgrade guard blocks the package install.
+#[tauri::command]
+pub async fn install_guest_tools(app_handle: AppHandle) -> Result<GuestTools, String> {
+    let op_desc = "Guest Tools";
+    let tools = current().ok_or("No supported hypervisor detected")?;
+    if tools.complete {
+        return Ok(tools);
+    }
+
+    let mut commands = Vec::new();
+    let missing: Vec<String> = tools.packages.iter().filter(|p| !p.installed).map(|p| p.name.clone()).collect();
+    if !missing.is_empty() {
+        match partial_upgrade::first_blocked(&missing).await {
+            Ok(Some(report)) => {
+                emit_progress(&app_handle, op_desc, &format!("Failed: {}", report.explanation));
+                return Err(report.explanation);
+            }
+            Ok(None) => {}
+            Err(e) => emit_progress(&app_handle, "PARTIAL_UPGRADE", &format!("Could not check for a partial upgrade: {}", e)),
+        }
+        let mut install = vec!["pacman".to_string(), "-S".to_string(), "--needed".to_string(), "--noconfirm".to_string()];
+        install.extend(missing);
+        commands.push(install);
+    }
+    // Services of packages not installed yet can't be queried, so which ones
+    // to enable comes from the toolset rather than `is-enabled`.
+    let toolset = toolset_for(&tools.virtualization).ok_or("No supported hypervisor detected")?;
+    let disabled: Vec<String> = tools
+        .services
+        .iter()
+        .filter(|s| !s.active && !toolset.udev_started.contains(&s.name.as_str()))
+        .map(|s| s.name.clone())
+        .collect();
+    if !disabled.is_empty() {
+        let mut enable = vec!["systemctl".to_string(), "enable".to_string(), "--now".to_string()];
+        enable.extend(disabled);
+        commands.push(enable);
+    }
+
+    emit_progress(&app_handle, op_desc, &format!("Installing {}...", tools.name));
+    match privileged::apply_as_root(&[], &commands).await {
+        Ok(output) => {
+            for line in output.lines() {
+                emit_progress(&app_handle, op_desc, line);
+            }
+            emit_progress(&app_handle, op_desc, "Guest tools installed. Log out and back in for clipboard and display resizing.");
+        }
+        Err(e) => {
+            emit_progress(&app_handle, op_desc, &format!("Failed: {}", e));
+            return Err(e);
+        }
+    }
+    current().ok_or_else(|| "No supported hypervisor detected".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hypervisors_map_to_their_guest_packages() {
+        let packages = |virt: &str| toolset_for(virt).map(|t| t.packages.to_vec());
+        assert_eq!(packages("oracle"), Some(vec!["virtualbox-guest-utils"]));
+        assert_eq!(packages("kvm"), Some(vec!["qemu-guest-agent", "spice-vdagent"]));
+        assert_eq!(packages("qemu"), packages("kvm"));
+        assert_eq!(packages("vmware"), Some(vec!["open-vm-tools"]));
+        assert_eq!(packages("microsoft"), Some(vec!["hyperv"]));
+        assert_eq!(packages("vm-other"), None);
+        assert!(detect(None).is_none());
+    }
+}
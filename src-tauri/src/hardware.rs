@@ -1,18 +1,43 @@
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
-use std::process::Command;
-
-const NVIDIA_VENDOR: &str = "10de";
-const AMD_VENDOR:    &str = "1002";
-const INTEL_VENDOR:  &str = "8086";
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::audio::{self, AudioInfo};
+use crate::driver_manager::{self, InstalledProfile};
+use crate::driver_profiles::{DeviceProfiles, DriverProfile, PackageSource, ProfileDatabase};
+use crate::drm::{self, DrmCard, DrmConnector};
+use crate::guest_tools::{self, GuestTools};
+use crate::hwids::IdDatabase;
+use crate::hybrid::{self, HybridInfo, INTEL_VENDOR, NVIDIA_VENDOR};
+use crate::input::{self, InputDevice};
+use crate::pci::{self, PciDevice};
+use crate::platform::{self, PlatformInfo};
+use crate::usb::{self, UsbDevice};
+
+const AMD_VENDOR: &str = "1002";
 
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct HardwareInfo {
     pub gpus: Vec<GpuInfo>,
     pub network_cards: Vec<NetworkCardInfo>,
-    pub other_cards: Vec<HashMap<String, String>>,
+    /// Sound cards with their codecs, and problems with the audio hardware.
+    pub audio: AudioInfo,
+    pub input_devices: Vec<InputDevice>,
     pub driver_packages: HashMap<String, DriverPackageInfo>,
     pub hybrid: Option<HybridInfo>,
+    pub pci_devices: Vec<PciDevice>,
+    pub usb_devices: Vec<UsbDevice>,
+    /// DMI identification, firmware and virtualization.
+    pub platform: PlatformInfo,
+    /// Guest additions status when running in a virtual machine.
+    pub guest_tools: Option<GuestTools>,
+    pub displays: Vec<DrmCard>,
+    /// Matching driver profiles per device, best first.
+    pub driver_profiles: Vec<DeviceProfiles>,
+    /// Profiles applied through the driver manager.
+    pub installed_profiles: Vec<InstalledProfile>,
+    /// OpenGL renderer of the current session, when it could be queried.
+    pub renderer: Option<String>,
 }
 
 /* ---------- GPU ---------- */
@@ -23,6 +48,10 @@ pub struct GpuInfo {
     pub driver_type: String,
     pub driver_module: String,
     pub in_use: bool,
+    pub pci_address: String,
+    pub device_id: String,           // e.g. "1c8d"
+    pub drm_card: Option<String>,     // e.g. "card0"
+    pub monitors: Vec<DrmConnector>,  // connected connectors with their EDID
 }
 
 /* ---------- Network ---------- */
@@ -51,267 +80,236 @@ pub struct DriverVariant {
     pub wiki_url: Option<String>,
 }
 
-/* ---------- Hybrid info ---------- */
-#[derive(Serialize, Deserialize, Debug)]
-pub struct HybridInfo {
-    pub primary: String,
-    pub secondary: String,
-    pub switch_method: String,
-    pub recommended_variant: String,
-}
-
 /* --------------------------------------------------------------- */
 pub fn get_hardware_info() -> Result<HardwareInfo, String> {
     let mut info = HardwareInfo::default();
 
     // ---------- 1. PCI devices ----------
-    let lspci = run_cmd("lspci", &["-nnk"])?;
-    let pci_lines = lspci.lines().map(|s| s.to_string()).collect::<Vec<_>>();
-
-    // ---------- 2. Loaded kernel modules ----------
-    let lsmod = run_cmd("lsmod", &[])?;
-    let loaded_modules: HashSet<String> = lsmod
-        .lines()
-        .filter_map(|l| l.split_whitespace().next().map(|s| s.to_string()))
-        .collect();
+    let ids = IdDatabase::load(&pci::PCI_IDS);
+    let devices = pci::enumerate(Path::new(pci::SYSFS_ROOT), &ids);
+    let usb_devices = usb::enumerate(Path::new(pci::SYSFS_ROOT), &IdDatabase::load(&usb::USB_IDS));
 
-    // ---------- 3. Xorg / Wayland renderer ----------
-    let renderer = detect_current_renderer();
+    // ---------- 2. DRM cards and session renderer ----------
+    let displays = drm::enumerate(Path::new(pci::SYSFS_ROOT));
+    let renderer = drm::gl_renderer();
 
-    // ---------- 4. Parse GPUs ----------
-    let gpus = parse_gpus(&pci_lines, &loaded_modules, &renderer);
+    // ---------- 3. Parse GPUs ----------
+    let gpus = parse_gpus(&devices, &displays, renderer.as_deref());
     info.gpus = gpus;
 
-    // ---------- 5. Hybrid detection ----------
-    if info.gpus.len() > 1 {
-        info.hybrid = detect_hybrid(&info.gpus, &loaded_modules);
+    // ---------- 4. Hybrid detection ----------
+    let in_use: Vec<String> = info.gpus.iter().filter(|g| g.in_use).map(|g| g.pci_address.clone()).collect();
+    info.hybrid = hybrid::detect(Path::new(pci::SYSFS_ROOT), &devices, &displays, &in_use);
+
+    // ---------- 5. Network, audio & input ----------
+    parse_network_cards(&devices, &mut info);
+    info.audio = audio::read(&devices);
+    info.input_devices = input::read();
+
+    // ---------- 6. Driver profiles for the devices found ----------
+    let profiles = ProfileDatabase::load();
+    info.driver_profiles = profiles.match_devices(&devices);
+    info.driver_profiles.extend(profiles.match_usb_devices(&usb_devices));
+    info.installed_profiles = driver_manager::read_state().installed;
+    for matched in info.driver_profiles.iter_mut() {
+        matched.installed = matched
+            .profiles
+            .iter()
+            .find(|p| info.installed_profiles.iter().any(|i| i.id == p.id))
+            .map(|p| p.id.clone());
     }
-
-    // ---------- 6. Network & other cards ----------
-    parse_other_devices(&pci_lines, &mut info);
-
-    // ---------- 7. Driver package catalogue ----------
-    populate_driver_catalog(&mut info);
-
+    populate_driver_catalog(&mut info, &devices);
+
+    info.pci_devices = devices;
+    info.usb_devices = usb_devices;
+    info.platform = platform::read(Path::new(pci::SYSFS_ROOT), &std::fs::read_to_string("/proc/cpuinfo").unwrap_or_default());
+    info.guest_tools = guest_tools::detect(info.platform.virtualization.as_deref());
+    info.displays = displays;
+    info.renderer = renderer;
     Ok(info)
 }
 
 /* ----------------------------------------------------------------- */
-fn parse_gpus(
-    lines: &[String],
-    _loaded: &HashSet<String>,
-    renderer: &str,
-) -> Vec<GpuInfo> {
-    let mut gpus = Vec::new();
-    let mut cur: Option<GpuInfo> = None;
-
-    for line in lines {
-        if line.contains("VGA compatible controller") || line.contains("3D controller") {
-            let (vendor, model) = extract_pci_info(line);
-            cur = Some(GpuInfo {
-                vendor: vendor.clone(),
-                model: model.clone(),
-                driver_type: "unknown".to_string(),
-                driver_module: "unknown".to_string(),
-                in_use: false,
-            });
-        }
-        else if let Some(ref mut gpu) = cur {
-            if line.contains("Kernel driver in use:") {
-                let module = line.split(':').nth(1).unwrap_or("").trim().to_string();
-                gpu.driver_module = module.clone();
-                gpu.driver_type = if module == "nvidia" {
-                    "proprietary".to_string()
-                } else {
-                    "open_source".to_string()
-                };
-
-                if renderer.contains(&module) || renderer.contains(&gpu.vendor) {
-                    gpu.in_use = true;
-                }
+/// The GPU rendering the session. The renderer string only names a vendor,
+/// so with two GPUs of that vendor the one driving a monitor, then the
+/// firmware's boot GPU, is taken.
+fn renderer_gpu<'a>(gpus: &[&'a PciDevice], cards: &[DrmCard], renderer: &str) -> Option<&'a PciDevice> {
+    let vendor = renderer_vendor(renderer)?;
+    let candidates: Vec<&PciDevice> = gpus.iter().copied().filter(|d| d.vendor_id == vendor).collect();
+    let active = |d: &PciDevice| cards.iter().any(|c| c.pci_address.as_deref() == Some(d.address.as_str()) && c.active);
+    match candidates.as_slice() {
+        [only] => Some(*only),
+        _ => candidates.iter().find(|d| active(d)).or_else(|| candidates.iter().find(|d| d.boot_vga)).copied(),
+    }
+}
 
-                gpus.push(gpu.clone());
-                cur = None;
+fn parse_gpus(devices: &[PciDevice], cards: &[DrmCard], renderer: Option<&str>) -> Vec<GpuInfo> {
+    let gpus: Vec<&PciDevice> = devices.iter().filter(|d| d.is_gpu()).collect();
+    let rendering = renderer.and_then(|r| renderer_gpu(&gpus, cards, r)).map(|d| d.address.as_str());
+    // Every display controller is listed, including ones no driver has
+    // claimed yet; those are the ones that need a driver installed.
+    gpus.iter()
+        .map(|d| {
+            let vendor = d.vendor_id.to_uppercase();
+            let (driver_type, driver_module) = match d.driver.as_deref() {
+                Some("nvidia") => ("proprietary", "nvidia"),
+                Some(module) => ("open_source", module),
+                None => ("none", "none"),
+            };
+            let card = cards.iter().find(|c| c.pci_address.as_deref() == Some(d.address.as_str()));
+            GpuInfo {
+                // Scanning out to a monitor, or rendering the session (e.g. PRIME).
+                in_use: card.is_some_and(|c| c.active) || rendering == Some(d.address.as_str()),
+                drm_card: card.map(|c| c.name.clone()),
+                monitors: card.map(DrmCard::monitors).unwrap_or_default(),
+                vendor,
+                model: d.model(),
+                driver_type: driver_type.to_string(),
+                driver_module: driver_module.to_string(),
+                pci_address: d.address.clone(),
+                device_id: d.device_id.clone(),
             }
-        }
-    }
-    gpus
+        })
+        .collect()
 }
 
 /* ----------------------------------------------------------------- */
-fn detect_hybrid(gpus: &[GpuInfo], loaded: &HashSet<String>) -> Option<HybridInfo> {
-    let mut intel = None;
-    let mut discrete = None;
-
-    for g in gpus {
-        if g.vendor.eq_ignore_ascii_case(INTEL_VENDOR) {
-            intel = Some(g);
-        } else if g.vendor.eq_ignore_ascii_case(NVIDIA_VENDOR) || g.vendor.eq_ignore_ascii_case(AMD_VENDOR) {
-            discrete = Some(g);
-        }
-    }
-
-    let (primary, secondary) = if let (Some(i), Some(d)) = (intel, discrete) {
-        if i.in_use { (i, d) } else { (d, i) }
-    } else {
-        return None;
-    };
-
-    let mut method = "none".to_string();
-    if loaded.contains("nvidia") {
-        if Command::new("prime-run").arg("--version").output().is_ok() {
-            method = "prime-run".to_string();
-        } else if std::path::Path::new("/usr/bin/optimus-manager").exists() {
-            method = "optimus-manager".to_string();
-        }
+fn parse_network_cards(devices: &[PciDevice], info: &mut HardwareInfo) {
+    for device in devices.iter().filter(|d| d.is_network()) {
+        info.network_cards.push(NetworkCardInfo {
+            vendor: device.vendor_id.to_uppercase(),
+            model: device.model(),
+            device: device.id(),
+        });
     }
-
-    let rec = if primary.vendor.eq_ignore_ascii_case(NVIDIA_VENDOR) {
-        "PRIME (nvidia-prime)".to_string()
-    } else {
-        "Intel iGPU (default)".to_string()
-    };
-
-    Some(HybridInfo {
-        primary: primary.vendor.clone(),
-        secondary: secondary.vendor.clone(),
-        switch_method: method,
-        recommended_variant: rec,
-    })
 }
 
 /* ----------------------------------------------------------------- */
-fn parse_other_devices(lines: &[String], info: &mut HardwareInfo) {
-    for line in lines {
-        if line.contains("Ethernet controller") || line.contains("Network controller") {
-            let (vendor, model) = extract_pci_info(line);
-            info.network_cards.push(NetworkCardInfo {
-                vendor,
-                model: model.clone(),
-                device: extract_device_id(line),
-            });
-        } else if !line.contains("VGA") && line.contains('[') {
-            let mut map = HashMap::new();
-            map.insert("raw".to_string(), line.clone());
-            info.other_cards.push(map);
-        }
+fn vendor_label(device: &PciDevice) -> String {
+    match device.vendor_id.as_str() {
+        NVIDIA_VENDOR => "NVIDIA".to_string(),
+        AMD_VENDOR => "AMD".to_string(),
+        INTEL_VENDOR => "Intel".to_string(),
+        _ => device.vendor_name.clone().unwrap_or_else(|| device.vendor_id.to_uppercase()),
     }
 }
 
-/* ----------------------------------------------------------------- */
-fn populate_driver_catalog(info: &mut HardwareInfo) {
-    // ---------- NVIDIA ----------
-    let mut nvidia = DriverPackageInfo {
-        r#type: "proprietary".to_string(),
-        packages: vec!["nvidia".to_string(), "nvidia-utils".to_string()],
-        instructions: "sudo pacman -S nvidia nvidia-utils".to_string(),
-        wiki_url: "https://wiki.archlinux.org/title/NVIDIA".to_string(),
-        variants: vec![],
-    };
-    nvidia.variants.push(DriverVariant {
-        name: "Open-source (nouveau)".to_string(),
-        packages: vec!["xf86-video-nouveau".to_string()],
-        instructions: "Blacklist nvidia module → edit /etc/modprobe.d/nouveau.conf".to_string(),
-        wiki_url: Some("https://wiki.archlinux.org/title/Nouveau".to_string()),
-    });
-    nvidia.variants.push(DriverVariant {
-        name: "PRIME (nvidia-prime)".to_string(),
-        packages: vec!["nvidia-prime".to_string()],
-        instructions: "sudo pacman -S nvidia-prime; prime-run <app>".to_string(),
-        wiki_url: Some("https://wiki.archlinux.org/title/PRIME".to_string()),
-    });
-    nvidia.variants.push(DriverVariant {
-        name: "optimus-manager".to_string(),
-        packages: vec!["optimus-manager".to_string(), "optimus-manager-qt".to_string()],
-        instructions: "sudo pacman -S optimus-manager; systemctl enable optimus-manager".to_string(),
-        wiki_url: Some("https://github.com/Askannz/optimus-manager/wiki".to_string()),
-    });
-    info.driver_packages.insert("NVIDIA".to_string(), nvidia);
-
-    // ---------- AMD ----------
-    let amd = DriverPackageInfo {
-        r#type: "open_source".to_string(),
-        packages: vec!["mesa".to_string(), "xf86-video-amdgpu".to_string(), "vulkan-radeon".to_string()],
-        instructions: "sudo pacman -S mesa xf86-video-amdgpu vulkan-radeon".to_string(),
-        wiki_url: "https://wiki.archlinux.org/title/AMDGPU".to_string(),
-        variants: vec![DriverVariant {
-            name: "Legacy Radeon".to_string(),
-            packages: vec!["xf86-video-ati".to_string()],
-            instructions: "For pre-GCN cards".to_string(),
-            wiki_url: Some("https://wiki.archlinux.org/title/ATI".to_string()),
-        }],
-    };
-    info.driver_packages.insert("AMD".to_string(), amd);
-
-    // ---------- Intel ----------
-    let intel = DriverPackageInfo {
-        r#type: "open_source".to_string(),
-        packages: vec!["mesa".to_string(), "vulkan-intel".to_string()],
-        instructions: "sudo pacman -S mesa vulkan-intel".to_string(),
-        wiki_url: "https://wiki.archlinux.org/title/Intel_graphics".to_string(),
-        variants: vec![],
-    };
-    info.driver_packages.insert("Intel".to_string(), intel); // <-- FIXED
+fn install_instructions(profile: &DriverProfile) -> String {
+    match profile.source {
+        PackageSource::Repo => format!("sudo pacman -S {}", profile.packages.join(" ")),
+        PackageSource::Aur => format!("Install from the AUR: {}", profile.packages.join(" ")),
+    }
 }
 
-/* ----------------------------------------------------------------- */
-fn detect_current_renderer() -> String {
-    if let Ok(out) = run_cmd("glxinfo", &["|", "grep", "OpenGL renderer"]) {
-        return out.trim().to_string();
+/// Fills `driver_packages` from the recommended profile of each device, keyed
+/// by vendor; the other matching profiles become variants.
+fn populate_driver_catalog(info: &mut HardwareInfo, devices: &[PciDevice]) {
+    for matched in &info.driver_profiles {
+        let Some(device) = devices.iter().find(|d| d.address == matched.address) else { continue };
+        let Some(best) = matched.profiles.first() else { continue };
+
+        let entry = info.driver_packages.entry(vendor_label(device)).or_insert_with(|| DriverPackageInfo {
+            r#type: if best.free { "open_source" } else { "proprietary" }.to_string(),
+            packages: best.packages.clone(),
+            instructions: install_instructions(best),
+            wiki_url: best.wiki_url.clone().unwrap_or_default(),
+            variants: vec![],
+        });
+        for profile in matched.profiles.iter().skip(1) {
+            if !entry.variants.iter().any(|v| v.name == profile.name) && entry.packages != profile.packages {
+                entry.variants.push(DriverVariant {
+                    name: profile.name.clone(),
+                    packages: profile.packages.clone(),
+                    instructions: install_instructions(profile),
+                    wiki_url: profile.wiki_url.clone(),
+                });
+            }
+        }
     }
-    if let Ok(out) = run_cmd("weston-info", &["|", "grep", "renderer"]) {
-        return out.trim().to_string();
+
+    // Switching tools for hybrid laptops aren't tied to a single device.
+    if let (Some(_), Some(nvidia)) = (&info.hybrid, info.driver_packages.get_mut("NVIDIA")) {
+        nvidia.variants.push(DriverVariant {
+            name: "PRIME (nvidia-prime)".to_string(),
+            packages: vec!["nvidia-prime".to_string()],
+            instructions: "sudo pacman -S nvidia-prime; prime-run <app>".to_string(),
+            wiki_url: Some("https://wiki.archlinux.org/title/PRIME".to_string()),
+        });
+        nvidia.variants.push(DriverVariant {
+            name: "optimus-manager".to_string(),
+            packages: vec!["optimus-manager".to_string(), "optimus-manager-qt".to_string()],
+            instructions: "sudo pacman -S optimus-manager; systemctl enable optimus-manager".to_string(),
+            wiki_url: Some("https://github.com/Askannz/optimus-manager/wiki".to_string()),
+        });
     }
-    "unknown".to_string()
 }
 
 /* ----------------------------------------------------------------- */
-fn run_cmd(cmd: &str, args: &[&str]) -> Result<String, String> {
-    Command::new(cmd)
-        .args(args)
-        .output()
-        .map_err(|e| format!("{}: {}", cmd, e))
-        .and_then(|o| {
-            if o.status.success() {
-                Ok(String::from_utf8_lossy(&o.stdout).to_string())
-            } else {
-                Err(format!("{} exited {}", cmd, o.status))
-            }
-        })
+/// Vendor ID named by an OpenGL renderer string such as
+/// "Mesa Intel(R) UHD Graphics 630 (CFL GT2)" or "NVIDIA GeForce GTX 1050/PCIe/SSE2".
+fn renderer_vendor(renderer: &str) -> Option<&'static str> {
+    if renderer.contains("NVIDIA") || renderer.starts_with("NV") {
+        Some(NVIDIA_VENDOR)
+    } else if renderer.contains("AMD") || renderer.contains("Radeon") {
+        Some(AMD_VENDOR)
+    } else if renderer.contains("Intel") {
+        Some(INTEL_VENDOR)
+    } else {
+        None // llvmpipe and other software renderers
+    }
 }
 
-/* ----------------------------------------------------------------- */
-fn extract_pci_info(line: &str) -> (String, String) {
-    // Example line:
-    // 00:02.0 VGA compatible controller [0300]: Intel Corporation HD Graphics 530 [8086:191b] (rev 06)
-    let after_class = line.split(']').nth(1).unwrap_or(line);
-
-    // ---- VENDOR ----
-    let vendor = after_class
-        .split('[')
-        .nth(1)
-        .and_then(|s| s.split(':').next())
-        .map(|s| s.trim().to_uppercase())
-        .unwrap_or("????".to_string());
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PCI_IDS_FIXTURE: &str = include_str!("../tests/fixtures/pci.ids");
+
+    #[test]
+    fn gpus_without_a_driver_are_listed() {
+        let root = pci::fixture_root();
+        let devices = pci::enumerate(&root, &IdDatabase::parse(PCI_IDS_FIXTURE));
+        let gpus = parse_gpus(&devices, &drm::enumerate(&root), None);
+
+        assert_eq!(gpus.len(), 2);
+        assert_eq!(gpus[0].driver_module, "i915");
+        assert!(gpus[0].in_use);
+        let monitors: Vec<&str> = gpus[0].monitors.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(monitors, ["HDMI-A-1", "eDP-1"]);
+        assert_eq!(gpus[1].vendor, "10DE");
+        assert_eq!(gpus[1].driver_type, "none");
+        assert!(!gpus[1].in_use);
+        assert_eq!(gpus[1].drm_card, None);
+    }
 
-    // ---- MODEL (strip [vendor:device] at the end) ----
-    let model = after_class
-        .split(": ")
-        .nth(1)
-        .unwrap_or("")
-        .split('[')               // <-- NEW: cut off at first '['
-        .next()
-        .unwrap_or("")
-        .trim()
-        .to_string();
+    #[test]
+    fn session_renderer_marks_its_gpu() {
+        assert_eq!(renderer_vendor("NVIDIA GeForce GTX 1050/PCIe/SSE2"), Some("10de"));
+        assert_eq!(renderer_vendor("NV137"), Some("10de"));
+        assert_eq!(renderer_vendor("AMD Radeon RX 6700 XT (radeonsi, navi22, LLVM 17.0.6, DRM 3.57)"), Some("1002"));
+        assert_eq!(renderer_vendor("Mesa Intel(R) UHD Graphics 630 (CFL GT2)"), Some("8086"));
+        assert_eq!(renderer_vendor("llvmpipe (LLVM 17.0.6, 256 bits)"), None);
+    }
 
-    (vendor, model)
+    #[test]
+    fn renderer_of_one_vendor_marks_a_single_gpu() {
+        let gpu = |address: &str, boot_vga: bool| PciDevice {
+            address: address.into(),
+            class: "030000".into(),
+            vendor_id: AMD_VENDOR.into(),
+            driver: Some("amdgpu".into()),
+            boot_vga,
+            ..Default::default()
+        };
+        let devices = [gpu("0000:03:00.0", false), gpu("0000:05:00.0", true)];
+        let renderer = "AMD Radeon 780M (radeonsi, gfx1103_r1, LLVM 18.1.8, DRM 3.57)";
+        let gpus = parse_gpus(&devices, &[], Some(renderer));
+        let in_use: Vec<&str> = gpus.iter().filter(|g| g.in_use).map(|g| g.pci_address.as_str()).collect();
+        assert_eq!(in_use, ["0000:05:00.0"]);
+
+        let cards = [DrmCard { name: "card0".into(), pci_address: Some("0000:03:00.0".into()), active: true, ..Default::default() }];
+        let gpus = parse_gpus(&devices, &cards, Some(renderer));
+        let in_use: Vec<&str> = gpus.iter().filter(|g| g.in_use).map(|g| g.pci_address.as_str()).collect();
+        assert_eq!(in_use, ["0000:03:00.0"]);
+    }
 }
-
-fn extract_device_id(line: &str) -> String {
-    line.split('[')
-        .nth(1)
-        .and_then(|s| s.split(']').next())
-        .unwrap_or("")
-        .to_string()
-}
\ No newline at end of file
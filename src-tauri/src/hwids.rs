@@ -0,0 +1,130 @@
+// src/hwids.rs
+//
+// Parser for the hwdata ID databases (pci.ids, usb.ids). Both share the
+// vendor / device / subsystem-or-interface layout and a "C" section for
+// device classes; the other usb.ids sections (HID usages, languages, ...)
+// are skipped.
+use std::collections::HashMap;
+use std::path::Path;
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+#[derive(Debug, Default)]
+pub struct IdDatabase {
+    vendors: HashMap<u16, String>,
+    devices: HashMap<(u16, u16), String>,
+    /// Third-level entries: subsystems in pci.ids, interfaces in usb.ids.
+    subdevices: HashMap<(u16, u16, String), String>,
+    classes: HashMap<u8, String>,
+    subclasses: HashMap<(u8, u8), String>,
+}
+
+enum Section {
+    Vendor(u16),
+    Class(u8),
+    Other,
+}
+
+// -----------------------------------------------------------------------------
+// Parsing
+// -----------------------------------------------------------------------------
+fn split_id(line: &str) -> Option<(&str, &str)> {
+    let (id, name) = line.split_once("  ")?;
+    Some((id.trim(), name.trim()))
+}
+
+impl IdDatabase {
+    pub fn parse(text: &str) -> Self {
+        let mut db = IdDatabase::default();
+        let mut section = Section::Other;
+        let mut device: Option<u16> = None;
+
+        for line in text.lines() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let depth = line.chars().take_while(|c| *c == '\t').count();
+            let body = &line[depth..];
+
+            match (depth, &section) {
+                (0, _) => {
+                    device = None;
+                    section = if let Some(rest) = body.strip_prefix("C ") {
+                        split_id(rest)
+                            .and_then(|(id, name)| Some((u8::from_str_radix(id, 16).ok()?, name)))
+                            .map_or(Section::Other, |(id, name)| {
+                                db.classes.insert(id, name.to_string());
+                                Section::Class(id)
+                            })
+                    } else {
+                        split_id(body)
+                            .filter(|(id, _)| id.len() == 4)
+                            .and_then(|(id, name)| Some((u16::from_str_radix(id, 16).ok()?, name)))
+                            .map_or(Section::Other, |(id, name)| {
+                                db.vendors.insert(id, name.to_string());
+                                Section::Vendor(id)
+                            })
+                    };
+                }
+                (1, Section::Vendor(vendor)) => {
+                    device = split_id(body).and_then(|(id, name)| {
+                        let id = u16::from_str_radix(id, 16).ok()?;
+                        db.devices.insert((*vendor, id), name.to_string());
+                        Some(id)
+                    });
+                }
+                (2, Section::Vendor(vendor)) => {
+                    if let (Some(device), Some((id, name))) = (device, split_id(body)) {
+                        db.subdevices.insert((*vendor, device, id.to_lowercase()), name.to_string());
+                    }
+                }
+                (1, Section::Class(class)) => {
+                    if let Some((id, name)) = split_id(body) {
+                        if let Ok(id) = u8::from_str_radix(id, 16) {
+                            db.subclasses.insert((*class, id), name.to_string());
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        db
+    }
+
+    /// Loads the first database found; an empty one if none is installed.
+    pub fn load(candidates: &[&str]) -> Self {
+        candidates
+            .iter()
+            .map(Path::new)
+            .find_map(|p| std::fs::read_to_string(p).ok())
+            .map(|text| IdDatabase::parse(&text))
+            .unwrap_or_default()
+    }
+
+    // -------------------------------------------------------------------------
+    // Lookups
+    // -------------------------------------------------------------------------
+    pub fn vendor(&self, vendor: u16) -> Option<&str> {
+        self.vendors.get(&vendor).map(String::as_str)
+    }
+
+    pub fn device(&self, vendor: u16, device: u16) -> Option<&str> {
+        self.devices.get(&(vendor, device)).map(String::as_str)
+    }
+
+    /// pci.ids keys subsystems as "ssvv ssdd"; usb.ids keys interfaces by number.
+    pub fn subdevice(&self, vendor: u16, device: u16, key: &str) -> Option<&str> {
+        self.subdevices.get(&(vendor, device, key.to_lowercase())).map(String::as_str)
+    }
+
+    /// The class name alone, as lsusb shows it for devices.
+    pub fn base_class(&self, class: u8) -> Option<&str> {
+        self.classes.get(&class).map(String::as_str)
+    }
+
+    /// The subclass name (what lspci shows), falling back to the class name.
+    pub fn class(&self, class: u8, subclass: u8) -> Option<&str> {
+        self.subclasses.get(&(class, subclass)).or_else(|| self.classes.get(&class)).map(String::as_str)
+    }
+}
@@ -0,0 +1,510 @@
+// src/hybrid.rs
+//
+// Hybrid graphics: which GPU is integrated and which is discrete, how the
+// system switches between them and whether the discrete GPU is powered down.
+// Nothing here launches a program on the discrete GPU; switching tools are
+// detected from their files and read-only queries.
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::drm::{self, DrmCard};
+use crate::hwids::IdDatabase;
+use crate::pci::{self, PciDevice};
+
+// -----------------------------------------------------------------------------
+// Configuration
+// -----------------------------------------------------------------------------
+pub const INTEL_VENDOR: &str = "8086";
+pub const NVIDIA_VENDOR: &str = "10de";
+
+const PRIME_RUN: &str = "/usr/bin/prime-run";
+const SWITCHEROOCTL: &str = "/usr/bin/switcherooctl";
+const OPTIMUS_MANAGER: &str = "/usr/bin/optimus-manager";
+const ENVYCONTROL: &str = "/usr/bin/envycontrol";
+
+/// Variables this module puts in front of Exec lines; used to strip them again.
+const OFFLOAD_VARS: [&str; 4] = ["__NV_PRIME_RENDER_OFFLOAD", "__VK_LAYER_NV_optimus", "__GLX_VENDOR_LIBRARY_NAME", "DRI_PRIME"];
+/// Marks desktop entries written by `set_app_gpu_offload`: "copy" for a
+/// user override of a system entry, "edit" for a user entry changed in place.
+/// A PrefersNonDefaultGPU value the entry had before is kept as ";prev=".
+const OFFLOAD_MARKER: &str = "X-LinuxHub-GPU-Offload";
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+/// Runtime power management state of a PCI device.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct RuntimePower {
+    /// "active", "suspended", "suspending", "resuming" or "unsupported".
+    pub status: Option<String>,
+    /// "auto" when runtime PM may suspend the device, "on" when it may not.
+    pub control: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SwitchingTool {
+    /// "prime-offload", "switcheroo-control", "optimus-manager" or "envycontrol".
+    pub name: String,
+    pub installed: bool,
+    pub active: bool,
+    /// Mode reported by optimus-manager or envycontrol, e.g. "hybrid".
+    pub mode: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct HybridInfo {
+    /// Vendor ID of the GPU rendering the session, upper-case.
+    pub primary: String,
+    pub secondary: String,
+    /// The tool in charge of switching, or "none".
+    pub switch_method: String,
+    pub recommended_variant: String,
+    /// PCI addresses.
+    pub integrated: String,
+    pub discrete: String,
+    pub discrete_driver: Option<String>,
+    pub discrete_power: RuntimePower,
+    pub tools: Vec<SwitchingTool>,
+    /// Environment that runs a program on the discrete GPU, as NAME=value.
+    pub offload_env: Vec<String>,
+    /// Launch option for game launchers such as Steam.
+    pub offload_launch_option: String,
+}
+
+/// Result of `set_app_gpu_offload`.
+#[derive(Serialize, Debug)]
+pub struct AppOffload {
+    /// The user desktop entry written or removed.
+    pub desktop_file: String,
+    pub enabled: bool,
+    /// Exec lines of the entry as written; empty when it was removed.
+    pub exec: Vec<String>,
+}
+
+// -----------------------------------------------------------------------------
+// Detection
+// -----------------------------------------------------------------------------
+pub fn runtime_power(sysfs_root: &Path, address: &str) -> RuntimePower {
+    let dir = sysfs_root.join("bus/pci/devices").join(address).join("power");
+    RuntimePower { status: pci::read_attr(&dir, "runtime_status"), control: pci::read_attr(&dir, "control") }
+}
+
+/// Whether a card has a built-in panel connector, as the GPU of a laptop
+/// screen does even when the lid is closed.
+fn drives_panel(card: Option<&DrmCard>) -> bool {
+    card.is_some_and(|c| c.connectors.iter().any(|k| k.name.starts_with("eDP") || k.name.starts_with("LVDS")))
+}
+
+/// Intel's integrated graphics sit on the root bus ("0000:00:02.0"); Arc
+/// cards share the vendor and class but are behind a PCIe port.
+fn is_intel_integrated(device: &PciDevice) -> bool {
+    device.vendor_id == INTEL_VENDOR && device.address.split(':').nth(1) == Some("00")
+}
+
+/// Picks (integrated, discrete) among the GPUs. Intel graphics on the root
+/// bus are taken as integrated, then the firmware's boot GPU, then the one
+/// wired to the panel; among the rest an NVIDIA GPU is preferred as discrete.
+pub fn pick_pair<'a>(gpus: &[&'a PciDevice], cards: &[DrmCard]) -> Option<(&'a PciDevice, &'a PciDevice)> {
+    if gpus.len() < 2 {
+        return None;
+    }
+    let card = |d: &PciDevice| cards.iter().find(|c| c.pci_address.as_deref() == Some(d.address.as_str()));
+    let integrated = gpus
+        .iter()
+        .find(|d| is_intel_integrated(d))
+        .or_else(|| gpus.iter().find(|d| d.boot_vga))
+        .or_else(|| gpus.iter().find(|d| drives_panel(card(d))))
+        .copied()?;
+    let others: Vec<&PciDevice> = gpus.iter().copied().filter(|d| d.address != integrated.address).collect();
+    let discrete = others.iter().find(|d| d.vendor_id == NVIDIA_VENDOR).or_else(|| others.first()).copied()?;
+    Some((integrated, discrete))
+}
+
+/// Environment that renders on `discrete`: NVIDIA's offload variables for
+/// the proprietary driver, DRI_PRIME for Mesa drivers.
+pub fn offload_env(discrete: &PciDevice) -> Vec<String> {
+    if discrete.driver.as_deref() == Some("nvidia") {
+        vec![
+            "__NV_PRIME_RENDER_OFFLOAD=1".to_string(),
+            "__VK_LAYER_NV_optimus=NVIDIA_only".to_string(),
+            "__GLX_VENDOR_LIBRARY_NAME=nvidia".to_string(),
+        ]
+    } else {
+        // Mesa takes the device as a "pci-dddd_bb_dd_f" tag.
+        vec![format!("DRI_PRIME=pci-{}", discrete.address.replace([':', '.'], "_"))]
+    }
+}
+
+/// Output of a read-only query, when the tool is installed.
+fn query(program: &str, args: &[&str]) -> Option<String> {
+    if !Path::new(program).exists() {
+        return None;
+    }
+    let output = Command::new(program).args(args).env("LC_ALL", "C").output().ok()?;
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// "Current GPU mode : hybrid" from optimus-manager, "hybrid" from envycontrol.
+fn parse_mode(output: &str) -> Option<String> {
+    let line = output.lines().rev().find(|l| !l.trim().is_empty())?;
+    let mode = line.rsplit([':', ' ']).next()?.trim().to_lowercase();
+    (!mode.is_empty()).then_some(mode)
+}
+
+fn switching_tools(discrete: &PciDevice) -> Vec<SwitchingTool> {
+    let nvidia_driver = discrete.driver.as_deref() == Some("nvidia");
+    // Mesa drivers offload without extra packages; the NVIDIA driver needs
+    // prime-run (nvidia-prime) or the variables set by hand.
+    let prime_installed = !nvidia_driver || Path::new(PRIME_RUN).exists();
+    let mut tools = vec![SwitchingTool {
+        name: "prime-offload".to_string(),
+        installed: prime_installed,
+        active: discrete.driver.is_some(),
+        mode: None,
+    }];
+
+    if Path::new(SWITCHEROOCTL).exists() {
+        let active = Command::new("systemctl")
+            .args(["is-active", "switcheroo-control.service"])
+            .output()
+            .is_ok_and(|o| String::from_utf8_lossy(&o.stdout).trim() == "active");
+        tools.push(SwitchingTool { name: "switcheroo-control".to_string(), installed: true, active, mode: None });
+    }
+    if let Some(output) = query(OPTIMUS_MANAGER, &["--print-mode"]) {
+        let mode = parse_mode(&output);
+        tools.push(SwitchingTool { name: "optimus-manager".to_string(), installed: true, active: mode.is_some(), mode });
+    }
+    if let Some(output) = query(ENVYCONTROL, &["--query"]) {
+        let mode = parse_mode(&output);
+        tools.push(SwitchingTool { name: "envycontrol".to_string(), installed: true, active: mode.is_some(), mode });
+    }
+    tools
+}
+
+/// Hybrid graphics details, or None with fewer than two GPUs. `active` are
+/// the PCI addresses of GPUs that drive a monitor or render the session.
+pub fn detect(sysfs_root: &Path, devices: &[PciDevice], cards: &[DrmCard], active: &[String]) -> Option<HybridInfo> {
+    let gpus: Vec<&PciDevice> = devices.iter().filter(|d| d.is_gpu()).collect();
+    let (integrated, discrete) = pick_pair(&gpus, cards)?;
+
+    let discrete_in_use = active.contains(&discrete.address) && !active.contains(&integrated.address);
+    let (primary, secondary) = if discrete_in_use { (discrete, integrated) } else { (integrated, discrete) };
+
+    let tools = switching_tools(discrete);
+    // A mode switcher overrides offloading; otherwise the desktop's
+    // switcheroo integration, then plain PRIME offload.
+    let switch_method = ["optimus-manager", "envycontrol", "switcheroo-control", "prime-offload"]
+        .iter()
+        .find(|name| tools.iter().any(|t| t.name == **name && t.installed && t.active))
+        .map_or("none", |name| name)
+        .to_string();
+
+    let nvidia_driver = discrete.driver.as_deref() == Some("nvidia");
+    let env = offload_env(discrete);
+    let offload_launch_option = if nvidia_driver && Path::new(PRIME_RUN).exists() {
+        "prime-run %command%".to_string()
+    } else {
+        format!("{} %command%", env.join(" "))
+    };
+    let recommended_variant = if discrete.vendor_id == NVIDIA_VENDOR && nvidia_driver {
+        "PRIME (nvidia-prime)".to_string()
+    } else {
+        "PRIME render offload (DRI_PRIME)".to_string()
+    };
+
+    Some(HybridInfo {
+        primary: primary.vendor_id.to_uppercase(),
+        secondary: secondary.vendor_id.to_uppercase(),
+        switch_method,
+        recommended_variant,
+        integrated: integrated.address.clone(),
+        discrete: discrete.address.clone(),
+        discrete_driver: discrete.driver.clone(),
+        discrete_power: runtime_power(sysfs_root, &discrete.address),
+        tools,
+        offload_env: env,
+        offload_launch_option,
+    })
+}
+
+/// Detection from sysfs alone, for commands that don't need a full
+/// hardware scan.
+fn current() -> Option<HybridInfo> {
+    let root = Path::new(pci::SYSFS_ROOT);
+    let devices = pci::enumerate(root, &IdDatabase::default());
+    let cards = drm::enumerate(root);
+    let active: Vec<String> = cards.iter().filter(|c| c.active).filter_map(|c| c.pci_address.clone()).collect();
+    detect(root, &devices, &cards, &active)
+}
+
+// -----------------------------------------------------------------------------
+// Desktop entries
+// -----------------------------------------------------------------------------
+fn user_applications_dir() -> Result<PathBuf, String> {
+    let home = home::home_dir().ok_or("Could not find home directory")?;
+    Ok(std::env::var_os("XDG_DATA_HOME").map(PathBuf::from).unwrap_or_else(|| home.join(".local/share")).join("applications"))
+}
+
+/// Finds a desktop entry by id ("firefox.desktop") or absolute path, user
+/// entries first, as the desktop does.
+fn find_desktop_file(desktop_file: &str) -> Result<PathBuf, String> {
+    let path = Path::new(desktop_file);
+    if path.is_absolute() {
+        return if path.exists() { Ok(path.to_path_buf()) } else { Err(format!("{} does not exist", desktop_file)) };
+    }
+    let data_dirs = std::env::var("XDG_DATA_DIRS").ok().filter(|d| !d.is_empty()).unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+    std::iter::once(user_applications_dir()?)
+        .chain(data_dirs.split(':').map(|d| Path::new(d).join("applications")))
+        .map(|dir| dir.join(desktop_file))
+        .find(|p| p.exists())
+        .ok_or_else(|| format!("Desktop entry {} not found", desktop_file))
+}
+
+fn is_section(line: &str) -> bool {
+    line.starts_with('[') && line.trim_end().ends_with(']')
+}
+
+/// Removes offload variables put in front of an Exec command.
+fn strip_exec(exec: &str) -> String {
+    let Some(rest) = exec.strip_prefix("env ") else { return exec.to_string() };
+    let tokens: Vec<&str> = rest.split(' ').collect();
+    let ours = tokens.iter().take_while(|t| OFFLOAD_VARS.iter().any(|v| t.starts_with(&format!("{}=", v)))).count();
+    if ours == 0 {
+        return exec.to_string();
+    }
+    let remaining = tokens[ours..].join(" ");
+    let keeps_env = tokens[ours..].first().is_some_and(|t| t.contains('=') && !t.starts_with('/'));
+    if keeps_env { format!("env {}", remaining) } else { remaining }
+}
+
+/// Undoes `apply_offload`, putting back the PrefersNonDefaultGPU line it
+/// replaced. Returns the entry and the marker kind it carried.
+fn strip_offload(contents: &str) -> (String, Option<String>) {
+    let prefix = format!("{}=", OFFLOAD_MARKER);
+    let value = contents.lines().find_map(|l| l.strip_prefix(&prefix)).map(str::trim);
+    let (marker, previous) = match value.map(|v| v.split_once(";prev=").map_or((v, None), |(m, p)| (m, Some(p)))) {
+        Some((marker, previous)) => (Some(marker.to_string()), previous),
+        None => (None, None),
+    };
+    let mut in_entry = false;
+    let mut lines = Vec::new();
+    for line in contents.lines() {
+        if is_section(line) {
+            in_entry = line.trim() == "[Desktop Entry]";
+        } else if line.starts_with(&prefix) {
+            continue;
+        } else if in_entry && marker.is_some() && line.starts_with("PrefersNonDefaultGPU=") {
+            if let Some(previous) = previous {
+                lines.push(format!("PrefersNonDefaultGPU={}", previous));
+            }
+            continue;
+        }
+        match line.strip_prefix("Exec=") {
+            Some(exec) => lines.push(format!("Exec={}", strip_exec(exec))),
+            None => lines.push(line.to_string()),
+        }
+    }
+    (lines.join("\n") + "\n", marker)
+}
+
+/// Runs every Exec line of the entry with `env` on the discrete GPU and sets
+/// PrefersNonDefaultGPU for desktops that launch through switcheroo-control.
+fn apply_offload(contents: &str, env: &[String], marker: &str) -> String {
+    let (clean, _) = strip_offload(contents);
+    let mut in_entry = false;
+    let previous = clean.lines().find_map(|line| {
+        if is_section(line) {
+            in_entry = line.trim() == "[Desktop Entry]";
+        }
+        line.strip_prefix("PrefersNonDefaultGPU=").filter(|_| in_entry).map(str::trim)
+    });
+    let marker = match previous {
+        Some(previous) => format!("{};prev={}", marker, previous),
+        None => marker.to_string(),
+    };
+    let mut in_entry = false;
+    let mut lines = Vec::new();
+    for line in clean.lines() {
+        if is_section(line) {
+            in_entry = line.trim() == "[Desktop Entry]";
+        } else if line.starts_with("PrefersNonDefaultGPU=") {
+            // The entry's own key is overridden where it stands, so disabling
+            // puts the original back in the same place.
+            if in_entry {
+                lines.push("PrefersNonDefaultGPU=true".to_string());
+            }
+            continue;
+        }
+        match line.strip_prefix("Exec=") {
+            Some(exec) => lines.push(format!("Exec=env {} {}", env.join(" "), exec)),
+            None => lines.push(line.to_string()),
+        }
+        if line.trim() == "[Desktop Entry]" {
+            if previous.is_none() {
+                lines.push("PrefersNonDefaultGPU=true".to_string());
+            }
+            lines.push(format!("{}={}", OFFLOAD_MARKER, marker));
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+fn exec_lines(contents: &str) -> Vec<String> {
+    contents.lines().filter_map(|l| l.strip_prefix("Exec=")).map(str::to_string).collect()
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: hybrid graphics
+// -----------------------------------------------------------------------------
+#[tauri::command]
+pub fn get_hybrid_graphics() -> Result<Option<HybridInfo>, String> {
+    Ok(current())
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: per-application offload
+// -----------------------------------------------------------------------------
+/// Makes an application start on the discrete GPU, or stops it doing so.
+/// System entries get a user override in ~/.local/share/applications,
+/// which is deleted again on disable; user entries are edited in place.
+#[tauri::command]
+pub fn set_app_gpu_offload(desktop_file: String, enable: bool) -> Result<AppOffload, String> {
+    let source = find_desktop_file(&desktop_file)?;
+    let user_dir = user_applications_dir()?;
+    let file_name = source.file_name().ok_or("Invalid desktop entry path")?;
+    let target = user_dir.join(file_name);
+    let contents = std::fs::read_to_string(&source).map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+
+    if enable {
+        let info = current().ok_or("No hybrid graphics detected")?;
+        let marker = if source.starts_with(&user_dir) { "edit" } else { "copy" };
+        // Re-enabling keeps the original marker so disabling still restores correctly.
+        let marker = strip_offload(&contents).1.unwrap_or_else(|| marker.to_string());
+        let updated = apply_offload(&contents, &info.offload_env, &marker);
+        std::fs::create_dir_all(&user_dir).map_err(|e| format!("Failed to create {}: {}", user_dir.display(), e))?;
+        std::fs::write(&target, &updated).map_err(|e| format!("Failed to write {}: {}", target.display(), e))?;
+        return Ok(AppOffload { desktop_file: target.display().to_string(), enabled: true, exec: exec_lines(&updated) });
+    }
+
+    if !source.starts_with(&user_dir) {
+        // Only the system entry exists, so nothing was overridden.
+        return Ok(AppOffload { desktop_file: source.display().to_string(), enabled: false, exec: exec_lines(&contents) });
+    }
+    let (restored, marker) = strip_offload(&contents);
+    if marker.as_deref() == Some("copy") {
+        std::fs::remove_file(&target).map_err(|e| format!("Failed to remove {}: {}", target.display(), e))?;
+        return Ok(AppOffload { desktop_file: target.display().to_string(), enabled: false, exec: Vec::new() });
+    }
+    std::fs::write(&target, &restored).map_err(|e| format!("Failed to write {}: {}", target.display(), e))?;
+    Ok(AppOffload { desktop_file: target.display().to_string(), enabled: false, exec: exec_lines(&restored) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gpu(address: &str, vendor: &str, driver: Option<&str>, boot_vga: bool) -> PciDevice {
+        PciDevice {
+            address: address.into(),
+            class: "030000".into(),
+            vendor_id: vendor.into(),
+            device_id: "0000".into(),
+            driver: driver.map(str::to_string),
+            boot_vga,
+            ..Default::default()
+        }
+    }
+
+    const ENTRY: &str = "[Desktop Entry]\nName=Game\nExec=/usr/bin/game %U\nPrefersNonDefaultGPU=false\n\n[Desktop Action safe]\nExec=env FOO=1 /usr/bin/game --safe\n";
+
+    #[test]
+    fn intel_and_nvidia_from_fixture_tree() {
+        let root = pci::fixture_root();
+        let devices = pci::enumerate(&root, &IdDatabase::default());
+        let cards = drm::enumerate(&root);
+        let info = detect(&root, &devices, &cards, &["0000:00:02.0".to_string()]).expect("hybrid");
+
+        assert_eq!(info.integrated, "0000:00:02.0");
+        assert_eq!(info.discrete, "0000:01:00.0");
+        assert_eq!((info.primary.as_str(), info.secondary.as_str()), ("8086", "10DE"));
+        assert_eq!(info.discrete_driver, None);
+        assert_eq!(info.discrete_power.status.as_deref(), Some("suspended"));
+        assert_eq!(info.discrete_power.control.as_deref(), Some("auto"));
+        assert_eq!(info.offload_env, ["DRI_PRIME=pci-0000_01_00_0"]);
+    }
+
+    #[test]
+    fn amd_apu_with_nvidia_or_amd_discrete() {
+        let apu = gpu("0000:05:00.0", "1002", Some("amdgpu"), true);
+        let nvidia = gpu("0000:01:00.0", "10de", Some("nvidia"), false);
+        let (integrated, discrete) = pick_pair(&[&nvidia, &apu], &[]).unwrap();
+        assert_eq!((integrated.address.as_str(), discrete.address.as_str()), ("0000:05:00.0", "0000:01:00.0"));
+        assert_eq!(offload_env(discrete)[0], "__NV_PRIME_RENDER_OFFLOAD=1");
+
+        // Without boot_vga the GPU wired to the panel is the integrated one.
+        let apu = gpu("0000:05:00.0", "1002", Some("amdgpu"), false);
+        let radeon = gpu("0000:03:00.0", "1002", Some("amdgpu"), false);
+        let cards = vec![DrmCard {
+            name: "card1".into(),
+            pci_address: Some(apu.address.clone()),
+            connectors: vec![drm::DrmConnector {
+                name: "eDP-1".into(),
+                status: "connected".into(),
+                enabled: true,
+                ..Default::default()
+            }],
+            ..Default::default()
+        }];
+        let (integrated, discrete) = pick_pair(&[&radeon, &apu], &cards).unwrap();
+        assert_eq!((integrated.address.as_str(), discrete.address.as_str()), ("0000:05:00.0", "0000:03:00.0"));
+        assert!(pick_pair(&[&radeon], &cards).is_none());
+    }
+
+    #[test]
+    fn intel_arc_is_discrete() {
+        let arc = gpu("0000:03:00.0", "8086", Some("xe"), false);
+        let apu = gpu("0000:05:00.0", "1002", Some("amdgpu"), true);
+        let (integrated, discrete) = pick_pair(&[&arc, &apu], &[]).unwrap();
+        assert_eq!((integrated.address.as_str(), discrete.address.as_str()), ("0000:05:00.0", "0000:03:00.0"));
+
+        let igpu = gpu("0000:00:02.0", "8086", Some("i915"), false);
+        let (integrated, discrete) = pick_pair(&[&arc, &igpu], &[]).unwrap();
+        assert_eq!((integrated.address.as_str(), discrete.address.as_str()), ("0000:00:02.0", "0000:03:00.0"));
+        assert_eq!(offload_env(discrete), ["DRI_PRIME=pci-0000_03_00_0"]);
+    }
+
+    #[test]
+    fn tool_modes_are_parsed() {
+        assert_eq!(parse_mode("Current GPU mode : hybrid\n").as_deref(), Some("hybrid"));
+        assert_eq!(parse_mode("integrated").as_deref(), Some("integrated"));
+        assert_eq!(parse_mode(""), None);
+    }
+
+    #[test]
+    fn offload_round_trips_through_desktop_entry() {
+        let env = vec!["DRI_PRIME=pci-0000_03_00_0".to_string()];
+        let applied = apply_offload(ENTRY, &env, "copy");
+        assert_eq!(exec_lines(&applied), ["env DRI_PRIME=pci-0000_03_00_0 /usr/bin/game %U", "env DRI_PRIME=pci-0000_03_00_0 env FOO=1 /usr/bin/game --safe"]);
+        assert!(applied.contains("[Desktop Entry]\nX-LinuxHub-GPU-Offload=copy;prev=false\n"));
+        assert!(applied.contains("\nPrefersNonDefaultGPU=true\n"));
+        assert!(!applied.contains("PrefersNonDefaultGPU=false"));
+
+        // Applying again replaces the variables instead of stacking them.
+        let nvidia = vec!["__NV_PRIME_RENDER_OFFLOAD=1".to_string(), "__GLX_VENDOR_LIBRARY_NAME=nvidia".to_string()];
+        let reapplied = apply_offload(&applied, &nvidia, "copy");
+        assert_eq!(exec_lines(&reapplied)[0], "env __NV_PRIME_RENDER_OFFLOAD=1 __GLX_VENDOR_LIBRARY_NAME=nvidia /usr/bin/game %U");
+
+        let (restored, marker) = strip_offload(&reapplied);
+        assert_eq!(marker.as_deref(), Some("copy"));
+        assert_eq!(restored, ENTRY);
+
+        // An entry that never set the key doesn't gain one.
+        let plain = ENTRY.replace("PrefersNonDefaultGPU=false\n", "");
+        let applied = apply_offload(&plain, &env, "edit");
+        assert!(applied.contains("[Desktop Entry]\nPrefersNonDefaultGPU=true\nX-LinuxHub-GPU-Offload=edit\n"));
+        let (restored, marker) = strip_offload(&applied);
+        assert_eq!(marker.as_deref(), Some("edit"));
+        assert_eq!(restored, plain);
+    }
+}
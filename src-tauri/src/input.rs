@@ -0,0 +1,283 @@
+// src/input.rs
+//
+// Input devices from /proc/bus/input/devices, classified from their
+// capability bitmaps the way udev's input_id does, with the driver behind
+// each one from sysfs. A few well-known setups that leave a device working
+// below its abilities are called out as hints.
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::pci::{self, link_name};
+
+// -----------------------------------------------------------------------------
+// Configuration
+// -----------------------------------------------------------------------------
+// Event types and codes from linux/input-event-codes.h.
+const EV_KEY: usize = 0x01;
+const EV_REL: usize = 0x02;
+const EV_ABS: usize = 0x03;
+const EV_SW: usize = 0x05;
+const REL_X: usize = 0x00;
+const REL_Y: usize = 0x01;
+const ABS_X: usize = 0x00;
+const ABS_Y: usize = 0x01;
+const BTN_MOUSE: usize = 0x110;
+const BTN_JOYSTICK: usize = 0x120;
+const BTN_GAMEPAD: usize = 0x130;
+const BTN_TOOL_PEN: usize = 0x140;
+const BTN_TOOL_FINGER: usize = 0x145;
+const BTN_TOUCH: usize = 0x14a;
+const BTN_STYLUS: usize = 0x14b;
+const INPUT_PROP_DIRECT: usize = 0x01;
+
+const WACOM_VENDOR: &str = "056a";
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum InputKind {
+    Keyboard,
+    Mouse,
+    Touchpad,
+    Touchscreen,
+    Tablet,
+    Joystick,
+    /// Lid and tablet-mode switches.
+    Switch,
+    #[default]
+    Other,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct InputDevice {
+    pub name: String,
+    pub kind: InputKind,
+    /// e.g. "USB", "PS/2" or "I2C".
+    pub bus: String,
+    /// Lower-case hex IDs, e.g. "056a".
+    pub vendor_id: String,
+    pub product_id: String,
+    /// Physical path, e.g. "isa0060/serio1/input0".
+    pub phys: Option<String>,
+    /// e.g. "/devices/platform/i8042/serio1/input/input5"
+    pub sysfs_path: Option<String>,
+    /// Event interfaces and legacy handlers, e.g. ["mouse0", "event5"].
+    pub handlers: Vec<String>,
+    /// Kernel driver of the underlying device, e.g. "psmouse" or "hid-multitouch".
+    pub driver: Option<String>,
+    pub hints: Vec<String>,
+}
+
+/// One block of /proc/bus/input/devices before classification.
+#[derive(Default)]
+struct RawDevice {
+    device: InputDevice,
+    props: Vec<u64>,
+    ev: Vec<u64>,
+    key: Vec<u64>,
+    rel: Vec<u64>,
+    abs: Vec<u64>,
+}
+
+// -----------------------------------------------------------------------------
+// Helpers
+// -----------------------------------------------------------------------------
+/// A capability bitmap as the kernel prints it: space-separated hex words,
+/// most significant first. Words are 64 bits wide on 64-bit kernels.
+fn parse_bitmap(value: &str) -> Vec<u64> {
+    value.split_whitespace().rev().map(|w| u64::from_str_radix(w, 16).unwrap_or(0)).collect()
+}
+
+fn test_bit(bitmap: &[u64], bit: usize) -> bool {
+    bitmap.get(bit / 64).is_some_and(|w| w >> (bit % 64) & 1 == 1)
+}
+
+fn bus_name(code: &str) -> String {
+    match code {
+        "0003" => "USB",
+        "0005" => "Bluetooth",
+        "0006" => "Virtual",
+        "0011" => "PS/2",
+        "0018" => "I2C",
+        "0019" => "Platform",
+        "001c" => "SPI",
+        _ => return format!("0x{}", code),
+    }
+    .to_string()
+}
+
+fn classify(raw: &RawDevice) -> InputKind {
+    let key = |bit| test_bit(&raw.key, bit);
+    let has_abs_xy = test_bit(&raw.ev, EV_ABS) && test_bit(&raw.abs, ABS_X) && test_bit(&raw.abs, ABS_Y);
+    let has_rel_xy = test_bit(&raw.ev, EV_REL) && test_bit(&raw.rel, REL_X) && test_bit(&raw.rel, REL_Y);
+    let joystick = key(BTN_JOYSTICK) || key(BTN_GAMEPAD);
+
+    if has_abs_xy {
+        if key(BTN_TOOL_PEN) || key(BTN_STYLUS) {
+            return InputKind::Tablet;
+        }
+        if key(BTN_TOOL_FINGER) && !test_bit(&raw.props, INPUT_PROP_DIRECT) {
+            return InputKind::Touchpad;
+        }
+        if key(BTN_TOUCH) {
+            return InputKind::Touchscreen;
+        }
+        if joystick {
+            return InputKind::Joystick;
+        }
+    }
+    if has_rel_xy && key(BTN_MOUSE) {
+        return InputKind::Mouse;
+    }
+    // udev's test: every key from Esc to D.
+    if test_bit(&raw.ev, EV_KEY) && (1..32).all(key) {
+        return InputKind::Keyboard;
+    }
+    if joystick {
+        return InputKind::Joystick;
+    }
+    if test_bit(&raw.ev, EV_SW) {
+        return InputKind::Switch;
+    }
+    InputKind::Other
+}
+
+fn parse_block(block: &str) -> Option<RawDevice> {
+    let mut raw = RawDevice::default();
+    for line in block.lines() {
+        let Some((tag, value)) = line.split_once(": ") else { continue };
+        match tag {
+            "I" => {
+                for field in value.split_whitespace() {
+                    match field.split_once('=') {
+                        Some(("Bus", v)) => raw.device.bus = bus_name(&v.to_lowercase()),
+                        Some(("Vendor", v)) => raw.device.vendor_id = v.to_lowercase(),
+                        Some(("Product", v)) => raw.device.product_id = v.to_lowercase(),
+                        _ => {}
+                    }
+                }
+            }
+            "N" => raw.device.name = value.strip_prefix("Name=")?.trim_matches('"').to_string(),
+            "P" => raw.device.phys = value.strip_prefix("Phys=").filter(|v| !v.is_empty()).map(str::to_string),
+            "S" => raw.device.sysfs_path = value.strip_prefix("Sysfs=").map(str::to_string),
+            "H" => raw.device.handlers = value.strip_prefix("Handlers=").unwrap_or("").split_whitespace().map(str::to_string).collect(),
+            "B" => match value.split_once('=') {
+                Some(("PROP", v)) => raw.props = parse_bitmap(v),
+                Some(("EV", v)) => raw.ev = parse_bitmap(v),
+                Some(("KEY", v)) => raw.key = parse_bitmap(v),
+                Some(("REL", v)) => raw.rel = parse_bitmap(v),
+                Some(("ABS", v)) => raw.abs = parse_bitmap(v),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+    Some(raw)
+}
+
+/// Hints for one device; `others` is the whole inventory.
+fn device_hints(device: &InputDevice, others: &[InputDevice]) -> Vec<String> {
+    let mut hints = Vec::new();
+    let driver = device.driver.as_deref();
+    if device.kind == InputKind::Touchpad && driver == Some("psmouse") {
+        let parameter = if device.name.contains("Synaptics") {
+            " Try the psmouse.synaptics_intertouch=1 kernel parameter."
+        } else if device.name.contains("Elan") {
+            " Try the psmouse.elantech_smbus=1 kernel parameter."
+        } else {
+            ""
+        };
+        hints.push(format!("The touchpad runs in PS/2 compatibility mode, which limits gestures and precision.{}", parameter));
+    }
+    if device.kind == InputKind::Tablet && device.vendor_id == WACOM_VENDOR && driver == Some("hid-generic") {
+        hints.push("The Wacom tablet is handled by hid-generic; pressure and buttons need the wacom kernel module.".to_string());
+    }
+    // A PS/2 mouse on a machine without a touchpad usually is the touchpad,
+    // in the fallback mode the firmware offers when its I2C driver is missing.
+    if device.name == "PS/2 Generic Mouse" && !others.iter().any(|d| d.kind == InputKind::Touchpad) {
+        hints.push("The touchpad was only recognised as a generic PS/2 mouse; its I2C HID driver (i2c_hid_acpi) may be missing or failing.".to_string());
+    }
+    hints
+}
+
+/// Input devices from `<proc_root>/bus/input/devices`, with drivers from
+/// `sysfs_root`.
+pub fn enumerate(proc_root: &Path, sysfs_root: &Path) -> Vec<InputDevice> {
+    let contents = std::fs::read_to_string(proc_root.join("bus/input/devices")).unwrap_or_default();
+    let mut devices: Vec<InputDevice> = contents
+        .split("\n\n")
+        .filter(|b| !b.trim().is_empty())
+        .filter_map(parse_block)
+        .map(|raw| {
+            let mut device = InputDevice { kind: classify(&raw), ..raw.device };
+            device.driver = device
+                .sysfs_path
+                .as_deref()
+                .and_then(|p| link_name(&sysfs_root.join(p.trim_start_matches('/')).join("device/driver")));
+            device
+        })
+        .collect();
+    let hints: Vec<Vec<String>> = devices.iter().map(|d| device_hints(d, &devices)).collect();
+    for (device, hints) in devices.iter_mut().zip(hints) {
+        device.hints = hints;
+    }
+    devices
+}
+
+/// Input devices of this machine.
+pub fn read() -> Vec<InputDevice> {
+    enumerate(Path::new(pci::PROC_ROOT), Path::new(pci::SYSFS_ROOT))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn devices() -> Vec<InputDevice> {
+        enumerate(&pci::proc_fixture_root(), &pci::fixture_root())
+    }
+
+    #[test]
+    fn classifies_fixture_devices() {
+        let devices = devices();
+        let kinds: Vec<(&str, InputKind)> = devices.iter().map(|d| (d.name.as_str(), d.kind)).collect();
+        assert_eq!(
+            kinds,
+            [
+                ("Lid Switch", InputKind::Switch),
+                ("Power Button", InputKind::Other),
+                ("AT Translated Set 2 keyboard", InputKind::Keyboard),
+                ("SynPS/2 Synaptics TouchPad", InputKind::Touchpad),
+                ("Wacom Intuos S Pen", InputKind::Tablet),
+                ("Logitech M705", InputKind::Mouse),
+            ]
+        );
+
+        let keyboard = &devices[2];
+        assert_eq!(keyboard.bus, "PS/2");
+        assert_eq!(keyboard.handlers, ["sysrq", "kbd", "leds", "event3"]);
+        assert_eq!(keyboard.driver.as_deref(), Some("atkbd"));
+
+        let tablet = &devices[4];
+        assert_eq!((tablet.bus.as_str(), tablet.vendor_id.as_str(), tablet.product_id.as_str()), ("USB", "056a", "0374"));
+        assert_eq!(tablet.driver.as_deref(), Some("wacom"));
+        assert!(tablet.hints.is_empty());
+        assert_eq!(devices[5].driver.as_deref(), Some("logitech-hidpp-device"));
+        assert_eq!(devices[0].driver, None);
+    }
+
+    #[test]
+    fn ps2_touchpad_gets_a_hint() {
+        let devices = devices();
+        let touchpad = &devices[3];
+        assert_eq!(touchpad.driver.as_deref(), Some("psmouse"));
+        assert_eq!(touchpad.hints.len(), 1);
+        assert!(touchpad.hints[0].contains("psmouse.synaptics_intertouch=1"));
+
+        let fallback = InputDevice { name: "PS/2 Generic Mouse".into(), kind: InputKind::Mouse, ..Default::default() };
+        assert_eq!(device_hints(&fallback, std::slice::from_ref(&fallback)).len(), 1);
+        assert!(device_hints(&fallback, &devices).is_empty());
+    }
+}
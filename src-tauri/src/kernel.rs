@@ -0,0 +1,521 @@
+// src/kernel.rs
+//
+// Kernel discovery. Installed kernels are resolved from their modules
+// directory to the owning package (via the `pkgbase` file and `pacman -Qo`
+// on `vmlinuz`) instead of being guessed from the directory name.
+use serde::Serialize;
+use std::path::Path;
+use sysinfo::System;
+use tauri::{AppHandle, Emitter};
+
+use crate::dkms;
+use crate::kernel_lifecycle::{self, KERNEL_LIFECYCLE_EVENT};
+use crate::model::*;
+use crate::pacman_manager::{emit_progress, run_checked_command};
+use crate::partial_upgrade;
+use crate::sync_db::{self, SyncPackage};
+
+// -----------------------------------------------------------------------------
+// Configuration
+// -----------------------------------------------------------------------------
+const MODULES_DIR: &str = "/usr/lib/modules";
+/// What kernel packages depend on for their initramfs ("initramfs" is the
+/// virtual package mkinitcpio, dracut and booster provide).
+const INITRAMFS_PROVIDERS: [&str; 4] = ["initramfs", "mkinitcpio", "dracut", "booster"];
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+/// What `install_kernel` / `remove_kernel` handed to pacman.
+#[derive(Debug, Serialize)]
+pub struct KernelTransaction {
+    pub kernel: String,
+    pub packages: Vec<String>,
+    /// Module packages of the running kernel with no counterpart in the repos.
+    pub missing_counterparts: Vec<String>,
+    /// Registered DKMS modules ("name/version"); they are built from the headers.
+    pub dkms_modules: Vec<String>,
+    pub warnings: Vec<String>,
+    /// True when nothing was run (`dry_run`).
+    pub planned_only: bool,
+}
+
+// --- The Core Tauri Command Function ---
+
+#[tauri::command]
+pub async fn get_system_kernels(app_handle: AppHandle) -> Result<String, String> {
+    let running_kernel = get_running_kernel_version();
+    let (mut installed_kernels, orphaned_module_dirs) = get_installed_kernels(&running_kernel)
+        .await
+        .map_err(|e| format!("Failed to get installed kernels: {}", e))?;
+    let mut installable_kernels = get_installable_kernels_from_repos().await.map_err(|e| format!("Failed to get installable kernels: {}", e))?;
+
+    let lifecycle = kernel_lifecycle::load(&app_handle).await;
+    for kernel in &mut installed_kernels {
+        kernel.lifecycle = lifecycle.lookup(&kernel.version);
+        if let Some(warning) = kernel.lifecycle.as_ref().and_then(|l| kernel_lifecycle::warning_for(&kernel.name, &kernel.version, l)) {
+            let _ = app_handle.emit(KERNEL_LIFECYCLE_EVENT, warning);
+        }
+    }
+    for kernel in &mut installable_kernels {
+        kernel.lifecycle = lifecycle.lookup(&kernel.version);
+    }
+
+    let result = KernelInfo {
+        running_kernel,
+        installed_kernels,
+        installable_kernels,
+        orphaned_module_dirs,
+    };
+
+    // Serialize the final struct to a JSON string
+    serde_json::to_string_pretty(&result)
+        .map_err(|e| format!("Failed to serialize kernel info to JSON: {}", e))
+}
+
+pub fn get_running_kernel_version() -> String {
+    let mut sys = System::new();
+    // This call is now redundant if kernel_version() is an associated function
+    sys.refresh_all();
+
+
+    sysinfo::System::kernel_version()
+        .unwrap_or_else(|| "<unknown>".to_owned())
+}
+pub async fn run_command(cmd: &str, args: &[&str]) -> Result<String, String> {
+    let output = tokio::process::Command::new(cmd)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute command '{}': {}", cmd, e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(format!(
+            "Command failed: {} {}",
+            cmd,
+            String::from_utf8_lossy(&output.stderr)
+        ))
+    }
+}
+
+/// Flavor from the kernel pkgbase: "linux" -> "default", "linux-zen" -> "zen",
+/// "linux-rt-lts" -> "rt-lts", Manjaro's "linux612-rt" -> "rt".
+pub fn flavor_from_pkgbase(pkgbase: &str) -> String {
+    let rest = pkgbase.strip_prefix("linux").unwrap_or(pkgbase);
+    let rest = rest.trim_start_matches(|c: char| c.is_ascii_digit());
+    let rest = rest.trim_start_matches('-');
+    let rest = rest.strip_suffix("-meta").or_else(|| (rest == "meta").then_some("")).unwrap_or(rest);
+    if rest.is_empty() {
+        "default".to_string()
+    } else {
+        rest.to_string()
+    }
+}
+
+/// Packages owning `path` (`pacman -Qoq`); empty when nothing owns it.
+async fn owners_of(path: &Path) -> Vec<String> {
+    let path = path.to_string_lossy();
+    run_command("pacman", &["-Qoq", &path])
+        .await
+        .map(|out| out.lines().map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+/// Installed version of a package, `None` when it isn't installed.
+pub async fn installed_package_version(name: &str) -> Option<String> {
+    run_command("pacman", &["-Q", name])
+        .await
+        .ok()
+        .and_then(|out| out.split_whitespace().nth(1).map(|v| v.to_string()))
+}
+
+pub async fn get_installed_kernels(
+    running_kernel: &str,
+) -> Result<(Vec<InstalledKernel>, Vec<OrphanedModuleDir>), std::io::Error> {
+    let mut installed_kernels = Vec::new();
+    let mut orphaned = Vec::new();
+
+    for entry in std::fs::read_dir(MODULES_DIR)? {
+        let path = entry?.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let Some(dir_name) = path.file_name().and_then(|s| s.to_str()).map(|s| s.to_string()) else {
+            continue;
+        };
+
+        // The kernel package ships both the image and a `pkgbase` file naming itself.
+        let vmlinuz = path.join("vmlinuz");
+        let vmlinuz_owner = if vmlinuz.exists() { owners_of(&vmlinuz).await.into_iter().next() } else { None };
+        let pkgbase = std::fs::read_to_string(path.join("pkgbase"))
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+
+        let Some(name) = vmlinuz_owner else {
+            // No kernel image owned by a package: extramodules or a leftover tree.
+            let dir_owners = owners_of(&path).await;
+            if dir_owners.is_empty() {
+                orphaned.push(OrphanedModuleDir {
+                    path: path.to_string_lossy().to_string(),
+                    pkgbase,
+                    reason: if vmlinuz.exists() {
+                        "vmlinuz is not owned by any package".to_string()
+                    } else {
+                        "no package owns this directory".to_string()
+                    },
+                });
+            }
+            continue;
+        };
+
+        // pkgbase differs from the image package for split packages; headers follow pkgbase.
+        let base = pkgbase.clone().unwrap_or_else(|| name.clone());
+        let headers_package = format!("{}-headers", base);
+        let headers_installed = installed_package_version(&headers_package).await.is_some();
+
+        installed_kernels.push(InstalledKernel {
+            flavor: flavor_from_pkgbase(&base),
+            package_version: installed_package_version(&name).await,
+            name,
+            version: dir_name.clone(),
+            pkgbase,
+            headers_package,
+            headers_installed,
+            modules_dir: path.to_string_lossy().to_string(),
+            running: dir_name == running_kernel,
+            lifecycle: None,
+        });
+    }
+
+    installed_kernels.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok((installed_kernels, orphaned))
+}
+/// Every Arch and Manjaro kernel depends on kmod and an initramfs generator;
+/// firmware, headers, docs and tools packages that share the `linux` prefix don't.
+fn is_kernel_image(package: &SyncPackage) -> bool {
+    let depends = |name: &str| package.depends.iter().any(|d| d == name);
+    depends("kmod") && INITRAMFS_PROVIDERS.iter().any(|p| depends(p))
+}
+
+/// Kernel image packages in the sync databases, first repository wins.
+async fn get_installable_kernels_from_repos() -> Result<Vec<InstallableKernel>, String> {
+    let packages = tokio::task::spawn_blocking(|| sync_db::packages(|name| name.starts_with("linux")))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut installable_list: Vec<InstallableKernel> = Vec::new();
+    for package in packages.into_iter().filter(is_kernel_image) {
+        if installable_list.iter().any(|k| k.package_name == package.name) {
+            continue;
+        }
+        installable_list.push(installable_kernel(package));
+    }
+    Ok(installable_list)
+}
+
+/// The flavor comes from `%BASE%`: a split package's own name doesn't carry it.
+fn installable_kernel(package: SyncPackage) -> InstallableKernel {
+    InstallableKernel {
+        version: package.version,
+        description: package.description,
+        flavor: flavor_from_pkgbase(&package.base),
+        package_name: package.name,
+        lifecycle: None,
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Kernel install / removal (mhwd-kernel style)
+// -----------------------------------------------------------------------------
+async fn in_repos(name: &str) -> bool {
+    run_command("pacman", &["-Si", name]).await.is_ok()
+}
+
+/// Packages shipping modules for a kernel: everything owning its modules dir or
+/// the `extramodules` tree it links to, minus the kernel and its headers.
+async fn module_packages(kernel: &InstalledKernel) -> Vec<String> {
+    let dir = Path::new(&kernel.modules_dir);
+    let mut owners = owners_of(dir).await;
+    if let Ok(extramodules) = std::fs::canonicalize(dir.join("extramodules")) {
+        owners.extend(owners_of(&extramodules).await);
+    }
+    owners.sort();
+    owners.dedup();
+    owners.retain(|p| *p != kernel.name && *p != kernel.headers_package);
+    owners
+}
+
+/// Maps a module package from one kernel to another. Manjaro prefixes the
+/// pkgbase (`linux66-nvidia` -> `linux612-nvidia`), Arch suffixes the flavor
+/// (`nvidia` -> `nvidia-lts`).
+fn counterpart_package(module_pkg: &str, from: &str, to: &str) -> Option<String> {
+    if let Some(rest) = module_pkg.strip_prefix(&format!("{}-", from)) {
+        return Some(format!("{}-{}", to, rest));
+    }
+    let base = match from.strip_prefix("linux-") {
+        Some(suffix) => module_pkg.strip_suffix(&format!("-{}", suffix))?,
+        None => module_pkg,
+    };
+    Some(match to.strip_prefix("linux-") {
+        Some(suffix) => format!("{}-{}", base, suffix),
+        None => base.to_string(),
+    })
+}
+
+/// Module package without its kernel: the Manjaro prefix or the Arch flavor
+/// suffix removed (`linux66-nvidia`, `nvidia-lts` -> `nvidia`).
+fn unflavored_package<'a>(module_pkg: &'a str, from: &str) -> &'a str {
+    if let Some(rest) = module_pkg.strip_prefix(&format!("{}-", from)) {
+        return rest;
+    }
+    from.strip_prefix("linux-")
+        .and_then(|suffix| module_pkg.strip_suffix(&format!("-{}", suffix)))
+        .unwrap_or(module_pkg)
+}
+
+/// DKMS package to fall back on when a module package has no prebuilt
+/// counterpart: `nvidia-lts` -> `nvidia-dkms`, `virtualbox-host-modules-arch`
+/// and Manjaro's `linux66-virtualbox-host-modules` -> `virtualbox-host-dkms`.
+fn dkms_package(module_pkg: &str, from: &str) -> String {
+    let base = unflavored_package(module_pkg, from);
+    let base = base.strip_suffix("-modules-arch").or_else(|| base.strip_suffix("-modules")).unwrap_or(base);
+    format!("{}-dkms", base)
+}
+
+async fn run_pacman_transaction(app_handle: &AppHandle, args: &[&str], packages: &[String], op_desc: &str) -> Result<(), String> {
+    let mut full_args = vec!["pacman"];
+    full_args.extend_from_slice(args);
+    full_args.extend(packages.iter().map(String::as_str));
+
+    emit_progress(app_handle, op_desc, &format!("Starting {}...", op_desc));
+    match run_checked_command("pkexec", &full_args, app_handle, op_desc).await {
+        Ok(_) => {
+            emit_progress(app_handle, op_desc, &format!("{} completed successfully.", op_desc));
+            Ok(())
+        }
+        Err(e) => {
+            emit_progress(app_handle, op_desc, &format!("Failed: {}", e));
+            Err(e)
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: install a kernel with headers and module packages
+// -----------------------------------------------------------------------------
+/// Installs `package_name` with its headers and the counterparts of every
+/// module package installed for the running kernel, falling back to the
+/// DKMS variant where no prebuilt one exists. Modules left without either
+/// refuse the install unless `allow_missing_modules` is set, and so does a
+/// transaction the partial-upgrade guard blocks. Progress is
+/// streamed as "pacman-progress" events like the package manager operations.
+/// With `dry_run` only the plan (and its warnings) is returned.
+#[tauri::command]
+pub async fn install_kernel(
+    app_handle: AppHandle,
+    package_name: String,
+    dry_run: Option<bool>,
+    allow_missing_modules: Option<bool>,
+) -> Result<KernelTransaction, String> {
+    let installable = get_installable_kernels_from_repos().await?;
+    if !installable.iter().any(|k| k.package_name == package_name) {
+        return Err(format!("{} is not a kernel package in the repositories", package_name));
+    }
+
+    let running = get_running_kernel_version();
+    let (installed, _) = get_installed_kernels(&running).await.map_err(|e| e.to_string())?;
+
+    let mut transaction = KernelTransaction {
+        kernel: package_name.clone(),
+        packages: vec![package_name.clone()],
+        missing_counterparts: Vec::new(),
+        dkms_modules: dkms::registered_module_names(),
+        warnings: Vec::new(),
+        planned_only: dry_run.unwrap_or(false),
+    };
+
+    let headers = format!("{}-headers", package_name);
+    let headers_available = in_repos(&headers).await;
+    if headers_available {
+        transaction.packages.push(headers.clone());
+    }
+
+    if let Some(current) = installed.iter().find(|k| k.running) {
+        let from = current.pkgbase.as_deref().unwrap_or(&current.name);
+        for module_pkg in module_packages(current).await {
+            if let Some(target) = counterpart_package(&module_pkg, from, &package_name) {
+                if in_repos(&target).await {
+                    transaction.packages.push(target);
+                    continue;
+                }
+            }
+            // DKMS builds the module against the new kernel's headers.
+            let dkms = dkms_package(&module_pkg, from);
+            if headers_available && in_repos(&dkms).await {
+                transaction.warnings.push(format!(
+                    "{} has no prebuilt counterpart for {}; {} will build it instead",
+                    module_pkg, package_name, dkms
+                ));
+                if !transaction.packages.contains(&dkms) {
+                    transaction.packages.push(dkms);
+                }
+            } else {
+                transaction.missing_counterparts.push(module_pkg);
+            }
+        }
+    }
+    for missing in &transaction.missing_counterparts {
+        transaction
+            .warnings
+            .push(format!("{} has no counterpart for {}; that module will be missing", missing, package_name));
+    }
+
+    // DKMS builds for the new kernel on install, but only with its headers present.
+    if !transaction.dkms_modules.is_empty() && !headers_available {
+        transaction.warnings.push(format!(
+            "DKMS modules ({}) are installed but {} is not available, so they cannot be built for {}",
+            transaction.dkms_modules.join(", "),
+            headers,
+            package_name
+        ));
+    }
+
+    for warning in &transaction.warnings {
+        emit_progress(&app_handle, "KERNEL_WARNING", warning);
+    }
+    if transaction.planned_only {
+        return Ok(transaction);
+    }
+    if !transaction.missing_counterparts.is_empty() && !allow_missing_modules.unwrap_or(false) {
+        return Err(format!(
+            "{} would boot without {}; confirm to install it anyway",
+            package_name,
+            transaction.missing_counterparts.join(", ")
+        ));
+    }
+    // A new kernel pulled from newer sync DBs is the classic partial upgrade.
+    match partial_upgrade::first_blocked(&transaction.packages).await {
+        Ok(Some(report)) => return Err(report.explanation),
+        Ok(None) => {}
+        Err(e) => emit_progress(&app_handle, "PARTIAL_UPGRADE", &format!("Could not check for a partial upgrade: {}", e)),
+    }
+    run_pacman_transaction(&app_handle, &["-S", "--needed", "--noconfirm"], &transaction.packages, "Kernel Installation").await?;
+    Ok(transaction)
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: remove a kernel with headers and module packages
+// -----------------------------------------------------------------------------
+/// Removes a kernel with its headers and module packages. The running kernel
+/// and the last installed kernel are refused. With `dry_run` only the plan is
+/// returned.
+#[tauri::command]
+pub async fn remove_kernel(
+    app_handle: AppHandle,
+    package_name: String,
+    dry_run: Option<bool>,
+) -> Result<KernelTransaction, String> {
+    let running = get_running_kernel_version();
+    let (installed, _) = get_installed_kernels(&running).await.map_err(|e| e.to_string())?;
+
+    let kernel = installed
+        .iter()
+        .find(|k| k.name == package_name || k.pkgbase.as_deref() == Some(package_name.as_str()))
+        .ok_or_else(|| format!("{} is not an installed kernel", package_name))?;
+
+    if kernel.running {
+        return Err(format!("{} is the running kernel and cannot be removed", kernel.name));
+    }
+    if installed.len() <= 1 {
+        return Err(format!("{} is the only installed kernel and cannot be removed", kernel.name));
+    }
+
+    let mut packages = vec![kernel.name.clone()];
+    if kernel.headers_installed {
+        packages.push(kernel.headers_package.clone());
+    }
+    packages.extend(module_packages(kernel).await);
+
+    let mut transaction = KernelTransaction {
+        kernel: kernel.name.clone(),
+        dkms_modules: dkms::registered_module_names(),
+        packages,
+        missing_counterparts: Vec::new(),
+        warnings: Vec::new(),
+        planned_only: dry_run.unwrap_or(false),
+    };
+
+    // The kernels left behind need headers for DKMS modules to keep working.
+    if !transaction.dkms_modules.is_empty() {
+        for other in installed.iter().filter(|k| k.name != kernel.name && !k.headers_installed) {
+            transaction.warnings.push(format!(
+                "{} has no headers ({}) installed, so DKMS modules ({}) are not built for it",
+                other.name,
+                other.headers_package,
+                transaction.dkms_modules.join(", ")
+            ));
+        }
+    }
+
+    for warning in &transaction.warnings {
+        emit_progress(&app_handle, "KERNEL_WARNING", warning);
+    }
+    if transaction.planned_only {
+        return Ok(transaction);
+    }
+    run_pacman_transaction(&app_handle, &["-Rns", "--noconfirm"], &transaction.packages, "Kernel Removal").await?;
+    Ok(transaction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arch_module_packages_follow_the_flavor_suffix() {
+        assert_eq!(counterpart_package("nvidia", "linux", "linux-lts").as_deref(), Some("nvidia-lts"));
+        assert_eq!(counterpart_package("nvidia-lts", "linux-lts", "linux").as_deref(), Some("nvidia"));
+        assert_eq!(counterpart_package("nvidia-lts", "linux-lts", "linux-zen").as_deref(), Some("nvidia-zen"));
+        // A package without the running kernel's suffix isn't one of its modules.
+        assert_eq!(counterpart_package("nvidia", "linux-lts", "linux"), None);
+    }
+
+    #[test]
+    fn manjaro_module_packages_follow_the_pkgbase_prefix() {
+        assert_eq!(counterpart_package("linux66-nvidia", "linux66", "linux612").as_deref(), Some("linux612-nvidia"));
+        assert_eq!(
+            counterpart_package("linux66-virtualbox-host-modules", "linux66", "linux612-rt").as_deref(),
+            Some("linux612-rt-virtualbox-host-modules")
+        );
+    }
+
+    #[test]
+    fn missing_counterparts_fall_back_to_dkms() {
+        assert_eq!(dkms_package("nvidia", "linux"), "nvidia-dkms");
+        assert_eq!(dkms_package("nvidia-lts", "linux-lts"), "nvidia-dkms");
+        assert_eq!(dkms_package("virtualbox-host-modules-arch", "linux"), "virtualbox-host-dkms");
+        assert_eq!(dkms_package("linux66-virtualbox-host-modules", "linux66"), "virtualbox-host-dkms");
+        assert_eq!(dkms_package("linux66-nvidia", "linux66"), "nvidia-dkms");
+    }
+
+    #[test]
+    fn only_kernel_images_are_installable() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sync");
+        let kernels: Vec<String> = sync_db::packages_in(&dir, |n| n.starts_with("linux"))
+            .into_iter()
+            .filter(is_kernel_image)
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(kernels, ["linux", "linux-lts", "linux-zen"]);
+    }
+
+    #[test]
+    fn installable_flavor_follows_the_pkgbase() {
+        let desc = "%NAME%\nlinux-rt-image\n\n%BASE%\nlinux-rt-lts\n\n%VERSION%\n6.6.65.rt47-1\n";
+        let kernel = installable_kernel(sync_db::parse_desc("extra", desc).unwrap());
+        assert_eq!((kernel.package_name.as_str(), kernel.flavor.as_str()), ("linux-rt-image", "rt-lts"));
+    }
+}
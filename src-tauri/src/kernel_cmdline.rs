@@ -0,0 +1,537 @@
+// src/kernel_cmdline.rs
+//
+// Kernel command line editor. Reads the running command line and the
+// persistent sources the bootloaders build it from, validates edits against
+// the parameter catalog in `data/kernel-params.json` and previews the diff
+// before anything is written.
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::bootloader::{self, read_shell_var, set_shell_var, GRUB_CFG, GRUB_DEFAULTS};
+use crate::privileged::{self, RootWrite};
+
+// -----------------------------------------------------------------------------
+// Configuration
+// -----------------------------------------------------------------------------
+const PARAM_CATALOG: &str = include_str!("../data/kernel-params.json");
+const PROC_CMDLINE: &str = "/proc/cmdline";
+const KERNEL_CMDLINE: &str = "/etc/kernel/cmdline";
+const GRUB_CMDLINE_KEY: &str = "GRUB_CMDLINE_LINUX_DEFAULT";
+const SYSTEMD_BOOT_TARGET_PREFIX: &str = "systemd-boot:";
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum ParamKind {
+    Flag,
+    Bool,
+    Int,
+    Enum,
+    String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CatalogEntry {
+    name: String,
+    kind: ParamKind,
+    #[serde(default)]
+    values: Vec<String>,
+    min: Option<i64>,
+    max: Option<i64>,
+    category: String,
+    description: String,
+    /// Removing it can leave the system unbootable.
+    #[serde(default)]
+    protected: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct Catalog {
+    params: Vec<CatalogEntry>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CmdlineParam {
+    pub raw: String,
+    pub key: String,
+    pub value: Option<String>,
+    pub category: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CmdlineTargetKind {
+    Grub,
+    KernelCmdline,
+    SystemdBootEntry,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CmdlineTarget {
+    /// Pass back as `target`: "grub", "kernel_cmdline" or "systemd-boot:<entry>.conf".
+    pub id: String,
+    pub kind: CmdlineTargetKind,
+    pub path: String,
+    pub params: Vec<CmdlineParam>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct KernelCmdline {
+    pub active: Vec<CmdlineParam>,
+    pub targets: Vec<CmdlineTarget>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum CmdlineChange {
+    /// `param` is "key" or "key=value"; an existing key is replaced.
+    Add { param: String },
+    Remove { key: String },
+    Set { key: String, value: Option<String> },
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueLevel {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValidationIssue {
+    pub key: String,
+    pub level: IssueLevel,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct CmdlineDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// (old, new) for parameters whose value changed.
+    pub changed: Vec<(String, String)>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CmdlinePreview {
+    pub target: String,
+    pub before: String,
+    pub after: String,
+    /// Persistent configuration, before -> after.
+    pub diff: CmdlineDiff,
+    /// Running command line -> command line at the next boot.
+    pub effective: CmdlineDiff,
+    pub issues: Vec<ValidationIssue>,
+    pub can_apply: bool,
+}
+
+// -----------------------------------------------------------------------------
+// Parsing
+// -----------------------------------------------------------------------------
+fn catalog() -> Catalog {
+    serde_json::from_str(PARAM_CATALOG).expect("bundled kernel parameter catalog is valid")
+}
+
+/// The kernel treats '-' and '_' in parameter names as the same character.
+fn normalize_key(key: &str) -> String {
+    key.replace('-', "_")
+}
+
+/// Splits on whitespace, keeping double-quoted values (acpi_osi="Windows 2015") intact.
+pub fn split_cmdline(cmdline: &str) -> Vec<String> {
+    let mut params = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for c in cmdline.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    params.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        params.push(current);
+    }
+    params
+}
+
+fn to_param(raw: &str, catalog: &Catalog) -> CmdlineParam {
+    let (key, value) = match raw.split_once('=') {
+        Some((k, v)) => (k.to_string(), Some(v.to_string())),
+        None => (raw.to_string(), None),
+    };
+    let entry = catalog.params.iter().find(|e| normalize_key(&e.name) == normalize_key(&key));
+    CmdlineParam {
+        raw: raw.to_string(),
+        category: entry.map(|e| e.category.clone()),
+        description: entry.map(|e| e.description.clone()),
+        key,
+        value,
+    }
+}
+
+fn parse(cmdline: &str, catalog: &Catalog) -> Vec<CmdlineParam> {
+    split_cmdline(cmdline).iter().map(|p| to_param(p, catalog)).collect()
+}
+
+/// systemd-boot joins every `options` line of an entry.
+fn entry_options(contents: &str) -> String {
+    contents
+        .lines()
+        .filter_map(|l| l.trim().strip_prefix("options"))
+        .filter(|rest| rest.starts_with(char::is_whitespace))
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn set_entry_options(contents: &str, options: &str) -> String {
+    let mut written = false;
+    let mut lines = Vec::new();
+    for line in contents.lines() {
+        let is_options = line.trim().strip_prefix("options").is_some_and(|r| r.starts_with(char::is_whitespace));
+        if !is_options {
+            lines.push(line.to_string());
+        } else if !written {
+            lines.push(format!("options {}", options));
+            written = true;
+        }
+    }
+    if !written {
+        lines.push(format!("options {}", options));
+    }
+    lines.join("\n") + "\n"
+}
+
+// -----------------------------------------------------------------------------
+// Persistent sources
+// -----------------------------------------------------------------------------
+/// Every source present on this system as (id, kind, path, cmdline).
+fn persistent_sources() -> Vec<(String, CmdlineTargetKind, String, String)> {
+    let mut sources = Vec::new();
+
+    if let Ok(contents) = std::fs::read_to_string(GRUB_DEFAULTS) {
+        let cmdline = read_shell_var(&contents, GRUB_CMDLINE_KEY).unwrap_or_default();
+        sources.push(("grub".to_string(), CmdlineTargetKind::Grub, GRUB_DEFAULTS.to_string(), cmdline));
+    }
+    if let Ok(contents) = std::fs::read_to_string(KERNEL_CMDLINE) {
+        let cmdline = contents.lines().filter(|l| !l.trim_start().starts_with('#')).collect::<Vec<_>>().join(" ");
+        sources.push(("kernel_cmdline".to_string(), CmdlineTargetKind::KernelCmdline, KERNEL_CMDLINE.to_string(), cmdline));
+    }
+    for path in bootloader::systemd_boot_entry_files() {
+        let (Some(name), Ok(contents)) = (path.file_name(), std::fs::read_to_string(&path)) else { continue };
+        sources.push((
+            format!("{}{}", SYSTEMD_BOOT_TARGET_PREFIX, name.to_string_lossy()),
+            CmdlineTargetKind::SystemdBootEntry,
+            path.to_string_lossy().to_string(),
+            entry_options(&contents),
+        ));
+    }
+    sources
+}
+
+fn find_source(target: &str) -> Result<(CmdlineTargetKind, String, String), String> {
+    persistent_sources()
+        .into_iter()
+        .find(|(id, ..)| id == target)
+        .map(|(_, kind, path, cmdline)| (kind, path, cmdline))
+        .ok_or_else(|| format!("Unknown command line source: {}", target))
+}
+
+// -----------------------------------------------------------------------------
+// Editing and validation
+// -----------------------------------------------------------------------------
+fn apply_changes(params: &[String], changes: &[CmdlineChange]) -> Vec<String> {
+    let mut params = params.to_vec();
+    let key_of = |p: &str| normalize_key(p.split_once('=').map_or(p, |(k, _)| k));
+
+    for change in changes {
+        let (key, replacement) = match change {
+            CmdlineChange::Add { param } => (key_of(param), Some(param.trim().to_string())),
+            CmdlineChange::Remove { key } => (normalize_key(key), None),
+            CmdlineChange::Set { key, value } => {
+                let raw = match value {
+                    Some(v) => format!("{}={}", key.trim(), v.trim()),
+                    None => key.trim().to_string(),
+                };
+                (normalize_key(key.trim()), Some(raw))
+            }
+        };
+        match (params.iter().position(|p| key_of(p) == key), replacement) {
+            (Some(i), Some(raw)) => {
+                params[i] = raw;
+                // Keep a single occurrence of the edited key.
+                let mut seen = false;
+                params.retain(|p| key_of(p) != key || !std::mem::replace(&mut seen, true));
+            }
+            (None, Some(raw)) => params.push(raw),
+            (_, None) => params.retain(|p| key_of(p) != key),
+        }
+    }
+    params
+}
+
+fn validate(before: &[CmdlineParam], after: &[CmdlineParam], catalog: &Catalog) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    let entry_for = |key: &str| catalog.params.iter().find(|e| normalize_key(&e.name) == normalize_key(key));
+
+    for removed in before.iter().filter(|b| !after.iter().any(|a| normalize_key(&a.key) == normalize_key(&b.key))) {
+        if entry_for(&removed.key).is_some_and(|e| e.protected) {
+            issues.push(ValidationIssue {
+                key: removed.key.clone(),
+                level: IssueLevel::Error,
+                message: format!("Removing {} can leave the system unable to boot", removed.key),
+            });
+        }
+    }
+
+    for param in after.iter().filter(|a| !before.iter().any(|b| b.raw == a.raw)) {
+        // A newline would start a new directive in the loader entry or GRUB default.
+        if param.raw.chars().any(char::is_control) {
+            issues.push(ValidationIssue {
+                key: param.key.clone(),
+                level: IssueLevel::Error,
+                message: format!("{} contains a line break or control character", param.key.escape_debug()),
+            });
+            continue;
+        }
+        let Some(entry) = entry_for(&param.key) else {
+            issues.push(ValidationIssue {
+                key: param.key.clone(),
+                level: IssueLevel::Warning,
+                message: format!("{} is not a known parameter; check the spelling", param.key),
+            });
+            continue;
+        };
+        let value = param.value.as_deref().map(|v| v.trim_matches('"'));
+        let problem = match (entry.kind, value) {
+            (ParamKind::Flag, Some(_)) => Some((IssueLevel::Warning, format!("{} does not take a value", entry.name))),
+            (ParamKind::Flag, None) => None,
+            (_, None) => Some((IssueLevel::Error, format!("{} needs a value", entry.name))),
+            (ParamKind::Bool, Some(v)) => {
+                (!["0", "1", "y", "n", "Y", "N", "on", "off"].contains(&v)).then(|| (IssueLevel::Error, format!("{} must be 0 or 1", entry.name)))
+            }
+            (ParamKind::Int, Some(v)) => match v.parse::<i64>() {
+                Err(_) => Some((IssueLevel::Error, format!("{} must be a number", entry.name))),
+                Ok(n) if entry.min.is_some_and(|m| n < m) || entry.max.is_some_and(|m| n > m) => Some((
+                    IssueLevel::Error,
+                    format!(
+                        "{} must be between {} and {}",
+                        entry.name,
+                        entry.min.map_or("-".into(), |m| m.to_string()),
+                        entry.max.map_or("-".into(), |m| m.to_string())
+                    ),
+                )),
+                Ok(_) => None,
+            },
+            (ParamKind::Enum, Some(v)) => (!entry.values.iter().any(|allowed| allowed == v))
+                .then(|| (IssueLevel::Error, format!("{} must be one of: {}", entry.name, entry.values.join(", ")))),
+            (ParamKind::String, Some(v)) => v.is_empty().then(|| (IssueLevel::Error, format!("{} needs a value", entry.name))),
+        };
+        if let Some((level, message)) = problem {
+            issues.push(ValidationIssue { key: param.key.clone(), level, message });
+        }
+    }
+    issues
+}
+
+fn diff(before: &[CmdlineParam], after: &[CmdlineParam]) -> CmdlineDiff {
+    let mut result = CmdlineDiff::default();
+    let find = |list: &[CmdlineParam], key: &str| list.iter().find(|p| normalize_key(&p.key) == normalize_key(key)).map(|p| p.raw.clone());
+
+    for b in before {
+        match find(after, &b.key) {
+            None => result.removed.push(b.raw.clone()),
+            Some(a) if a != b.raw => result.changed.push((b.raw.clone(), a)),
+            Some(_) => {}
+        }
+    }
+    for a in after {
+        if find(before, &a.key).is_none() {
+            result.added.push(a.raw.clone());
+        }
+    }
+    result
+}
+
+fn preview(target: &str, changes: &[CmdlineChange]) -> Result<(CmdlinePreview, CmdlineTargetKind, String), String> {
+    let catalog = catalog();
+    let (kind, path, before_line) = find_source(target)?;
+    let before = parse(&before_line, &catalog);
+    let after_raw = apply_changes(&split_cmdline(&before_line), changes);
+    let after_line = after_raw.join(" ");
+    let after = parse(&after_line, &catalog);
+
+    // Next boot: what the bootloader adds itself (BOOT_IMAGE, root, ...) plus this source.
+    let active = parse(&std::fs::read_to_string(PROC_CMDLINE).unwrap_or_default(), &catalog);
+    let mut next_boot: Vec<CmdlineParam> = active
+        .iter()
+        .filter(|p| !before.iter().any(|b| normalize_key(&b.key) == normalize_key(&p.key)))
+        .cloned()
+        .collect();
+    next_boot.extend(after.iter().cloned());
+
+    let issues = validate(&before, &after, &catalog);
+    let preview = CmdlinePreview {
+        target: target.to_string(),
+        before: before_line,
+        after: after_line,
+        diff: diff(&before, &after),
+        effective: diff(&active, &next_boot),
+        can_apply: !issues.iter().any(|i| i.level == IssueLevel::Error),
+        issues,
+    };
+    Ok((preview, kind, path))
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: read the active and persistent command lines
+// -----------------------------------------------------------------------------
+#[tauri::command]
+pub async fn get_kernel_cmdline() -> Result<KernelCmdline, String> {
+    let catalog = catalog();
+    let active = std::fs::read_to_string(PROC_CMDLINE).map_err(|e| format!("Failed to read {}: {}", PROC_CMDLINE, e))?;
+
+    Ok(KernelCmdline {
+        active: parse(&active, &catalog),
+        targets: persistent_sources()
+            .into_iter()
+            .map(|(id, kind, path, cmdline)| CmdlineTarget { id, kind, path, params: parse(&cmdline, &catalog) })
+            .collect(),
+    })
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: preview an edit
+// -----------------------------------------------------------------------------
+#[tauri::command]
+pub async fn preview_kernel_cmdline_change(target: String, changes: Vec<CmdlineChange>) -> Result<CmdlinePreview, String> {
+    preview(&target, &changes).map(|(p, _, _)| p)
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: apply an edit
+// -----------------------------------------------------------------------------
+/// Writes the edited command line; GRUB's config is regenerated so the change
+/// reaches grub.cfg. Takes effect on the next boot.
+#[tauri::command]
+pub async fn apply_kernel_cmdline_change(target: String, changes: Vec<CmdlineChange>) -> Result<CmdlinePreview, String> {
+    let (preview, kind, path) = preview(&target, &changes)?;
+    if !preview.can_apply {
+        let errors: Vec<&str> = preview
+            .issues
+            .iter()
+            .filter(|i| i.level == IssueLevel::Error)
+            .map(|i| i.message.as_str())
+            .collect();
+        return Err(format!("Refusing to apply: {}", errors.join("; ")));
+    }
+
+    let contents = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    match kind {
+        CmdlineTargetKind::Grub => {
+            let updated = set_shell_var(&contents, GRUB_CMDLINE_KEY, &preview.after);
+            let regenerate = Path::new(GRUB_CFG).exists().then(|| vec!["grub-mkconfig".into(), "-o".into(), GRUB_CFG.into()]);
+            privileged::apply_as_root(&[RootWrite::config(path, updated)], regenerate.as_slice()).await?;
+        }
+        CmdlineTargetKind::KernelCmdline => {
+            privileged::apply_as_root(&[RootWrite::config(path, format!("{}\n", preview.after))], &[]).await?;
+        }
+        CmdlineTargetKind::SystemdBootEntry => {
+            let updated = set_entry_options(&contents, &preview.after);
+            privileged::apply_as_root(&[RootWrite::config(path, updated)], &[]).await?;
+        }
+    }
+    Ok(preview)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(cmdline: &str) -> Vec<CmdlineParam> {
+        parse(cmdline, &catalog())
+    }
+
+    fn edit(cmdline: &str, changes: &[CmdlineChange]) -> String {
+        apply_changes(&split_cmdline(cmdline), changes).join(" ")
+    }
+
+    #[test]
+    fn splits_on_whitespace_outside_quotes() {
+        let cases: [(&str, &[&str]); 5] = [
+            ("quiet splash", &["quiet", "splash"]),
+            ("  root=UUID=1234   rw\n", &["root=UUID=1234", "rw"]),
+            (r#"acpi_osi="Windows 2015" quiet"#, &[r#"acpi_osi="Windows 2015""#, "quiet"]),
+            (r#"a="x y" b="" c"#, &[r#"a="x y""#, r#"b="""#, "c"]),
+            ("", &[]),
+        ];
+        for (cmdline, expected) in cases {
+            assert_eq!(split_cmdline(cmdline), expected, "{:?}", cmdline);
+        }
+        let param = &params(r#"acpi_osi="Windows 2015""#)[0];
+        assert_eq!((param.key.as_str(), param.value.as_deref()), ("acpi_osi", Some(r#""Windows 2015""#)));
+    }
+
+    #[test]
+    fn applies_adds_sets_and_removals() {
+        let add = |param: &str| CmdlineChange::Add { param: param.to_string() };
+        let remove = |key: &str| CmdlineChange::Remove { key: key.to_string() };
+        let set = |key: &str, value: Option<&str>| CmdlineChange::Set { key: key.to_string(), value: value.map(str::to_string) };
+        let cases = [
+            ("quiet", vec![add("splash")], "quiet splash"),
+            ("loglevel=3 quiet", vec![add("loglevel=7")], "loglevel=7 quiet"),
+            // Repeated keys collapse to the edited one, in place of the first.
+            ("loglevel=3 quiet loglevel=4", vec![set("loglevel", Some("5"))], "loglevel=5 quiet"),
+            ("quiet loglevel=3 splash loglevel=4", vec![remove("loglevel")], "quiet splash"),
+            // '-' and '_' are the same character in parameter names.
+            ("nvidia_drm.modeset=0", vec![set("nvidia-drm.modeset", Some("1"))], "nvidia-drm.modeset=1"),
+            ("nowatchdog", vec![remove("no_watchdog")], "nowatchdog"),
+            (r#"acpi_osi="Windows 2015""#, vec![set("acpi_osi", Some(r#""Windows 2020""#))], r#"acpi_osi="Windows 2020""#),
+            ("quiet", vec![set("debug", None), remove("quiet")], "debug"),
+        ];
+        for (cmdline, changes, expected) in cases {
+            assert_eq!(edit(cmdline, &changes), expected, "{:?}", cmdline);
+        }
+    }
+
+    #[test]
+    fn validates_new_values_against_the_catalog() {
+        let catalog = catalog();
+        let issues = |before: &str, after: &str| -> Vec<(IssueLevel, String)> {
+            validate(&params(before), &params(after), &catalog).into_iter().map(|i| (i.level, i.message)).collect()
+        };
+        let error = |message: &str| vec![(IssueLevel::Error, message.to_string())];
+        let warning = |message: &str| vec![(IssueLevel::Warning, message.to_string())];
+
+        assert!(issues("quiet", "quiet loglevel=3 mem_sleep_default=deep nvidia-drm.modeset=1").is_empty());
+        assert_eq!(issues("", "loglevel=9"), error("loglevel must be between 0 and 7"));
+        assert_eq!(issues("", "loglevel=high"), error("loglevel must be a number"));
+        assert_eq!(issues("", "loglevel"), error("loglevel needs a value"));
+        assert_eq!(issues("", "mem_sleep_default=s3"), error("mem_sleep_default must be one of: s2idle, shallow, deep"));
+        assert_eq!(issues("", "nvidia_drm.modeset=2"), error("nvidia-drm.modeset must be 0 or 1"));
+        assert_eq!(issues("", "quiet=1"), warning("quiet does not take a value"));
+        assert_eq!(issues("", "qiuet"), warning("qiuet is not a known parameter; check the spelling"));
+        assert_eq!(issues("root=UUID=1234 rw quiet", "rw quiet"), error("Removing root can leave the system unable to boot"));
+        let injected: Vec<CmdlineParam> = apply_changes(&[], &[CmdlineChange::Add { param: "quiet\nlinux /evil".into() }])
+            .iter()
+            .map(|raw| to_param(raw, &catalog))
+            .collect();
+        assert_eq!(
+            validate(&[], &injected, &catalog).into_iter().map(|i| (i.level, i.message)).collect::<Vec<_>>(),
+            error("quiet\\nlinux /evil contains a line break or control character")
+        );
+        // Values already on the line are not checked again.
+        assert!(issues("loglevel=9", "loglevel=9 quiet").is_empty());
+    }
+}
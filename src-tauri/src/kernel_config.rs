@@ -0,0 +1,318 @@
+// src/kernel_config.rs
+//
+// Kernel build configurations: where to find them for each installed kernel,
+// option-by-option diffs between two of them and a lookup of one option
+// across all kernels. /proc/config.gz is decompressed in-process.
+use flate2::read::GzDecoder;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::kernel::{get_installed_kernels, get_running_kernel_version};
+
+// -----------------------------------------------------------------------------
+// Configuration
+// -----------------------------------------------------------------------------
+const PROC_CONFIG: &str = "/proc/config.gz";
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+#[derive(Debug, Serialize, Clone)]
+pub struct KernelConfigSource {
+    pub kernel: String,
+    pub kernel_version: String,
+    pub running: bool,
+    /// The file the configuration is read from; None if none was found.
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigChange {
+    Added,
+    Removed,
+    Changed,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfigDiffEntry {
+    pub option: String,
+    pub change: ConfigChange,
+    /// "y", "m", "n" (for "is not set"), or the literal value of string/int options.
+    pub left: Option<String>,
+    pub right: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct KernelConfigDiff {
+    pub left: KernelConfigSource,
+    pub right: KernelConfigSource,
+    pub added: usize,
+    pub removed: usize,
+    pub changed: usize,
+    pub entries: Vec<ConfigDiffEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct KernelConfigOption {
+    pub kernel: String,
+    pub kernel_version: String,
+    pub running: bool,
+    pub path: Option<String>,
+    /// None when the option does not appear in this kernel's config at all.
+    pub value: Option<String>,
+}
+
+// -----------------------------------------------------------------------------
+// Helpers
+// -----------------------------------------------------------------------------
+/// Candidate config files for a kernel, best first. /proc/config.gz only
+/// describes the kernel that is running.
+fn candidate_paths(kernel_version: &str, modules_dir: &str, running: bool) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    if running {
+        paths.push(PathBuf::from(PROC_CONFIG));
+    }
+    paths.push(Path::new(modules_dir).join("build/.config"));
+    paths.push(PathBuf::from(format!("/boot/config-{}", kernel_version)));
+    paths
+}
+
+fn read_config(path: &Path) -> Result<String, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    if path.extension().is_some_and(|ext| ext == "gz") {
+        let mut text = String::new();
+        GzDecoder::new(bytes.as_slice())
+            .read_to_string(&mut text)
+            .map_err(|e| format!("Failed to decompress {}: {}", path.display(), e))?;
+        Ok(text)
+    } else {
+        Ok(String::from_utf8_lossy(&bytes).to_string())
+    }
+}
+
+/// Parses `CONFIG_X=value` and `# CONFIG_X is not set` (recorded as "n").
+/// String values keep their quotes so they can't be confused with tristates.
+pub fn parse_config(text: &str) -> BTreeMap<String, String> {
+    let mut options = BTreeMap::new();
+    for line in text.lines().map(str::trim) {
+        if let Some(rest) = line.strip_prefix("# ") {
+            if let Some(name) = rest.strip_suffix(" is not set").filter(|n| n.starts_with("CONFIG_")) {
+                options.insert(name.to_string(), "n".to_string());
+            }
+        } else if let Some((name, value)) = line.split_once('=') {
+            if name.starts_with("CONFIG_") {
+                options.insert(name.to_string(), value.to_string());
+            }
+        }
+    }
+    options
+}
+
+/// Options that differ between two configs. An absent option is off, like
+/// "is not set": Kconfig drops unset options whose dependencies are missing.
+pub fn diff_configs(left: &BTreeMap<String, String>, right: &BTreeMap<String, String>) -> Vec<ConfigDiffEntry> {
+    let names: BTreeSet<&String> = left.keys().chain(right.keys()).collect();
+    let off = |v: Option<&String>| v.is_none_or(|v| v == "n");
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let (l, r) = (left.get(name), right.get(name));
+            if off(l) && off(r) {
+                return None;
+            }
+            let change = match (l, r) {
+                (None, Some(_)) => ConfigChange::Added,
+                (Some(_), None) => ConfigChange::Removed,
+                (Some(a), Some(b)) if a != b => ConfigChange::Changed,
+                _ => return None,
+            };
+            Some(ConfigDiffEntry { option: name.clone(), change, left: l.cloned(), right: r.cloned() })
+        })
+        .collect()
+}
+
+/// Accepts "CONFIG_FOO", "config_foo" or just "foo".
+fn normalize_option(option: &str) -> String {
+    let upper = option.trim().to_uppercase();
+    if upper.starts_with("CONFIG_") { upper } else { format!("CONFIG_{}", upper) }
+}
+
+/// Config sources for every installed kernel, plus the running kernel if its
+/// package is gone (e.g. after an upgrade without a reboot).
+async fn config_sources() -> Result<Vec<KernelConfigSource>, String> {
+    let running = get_running_kernel_version();
+    let (kernels, _) = get_installed_kernels(&running).await.map_err(|e| format!("Failed to get installed kernels: {}", e))?;
+
+    let mut sources: Vec<KernelConfigSource> = kernels
+        .into_iter()
+        .map(|k| KernelConfigSource {
+            path: candidate_paths(&k.version, &k.modules_dir, k.running)
+                .into_iter()
+                .find(|p| p.exists())
+                .map(|p| p.to_string_lossy().to_string()),
+            kernel: k.name,
+            kernel_version: k.version,
+            running: k.running,
+        })
+        .collect();
+
+    if !sources.iter().any(|s| s.running) {
+        sources.push(KernelConfigSource {
+            kernel: "running".to_string(),
+            path: candidate_paths(&running, &format!("/usr/lib/modules/{}", running), true)
+                .into_iter()
+                .find(|p| p.exists())
+                .map(|p| p.to_string_lossy().to_string()),
+            kernel_version: running,
+            running: true,
+        });
+    }
+    Ok(sources)
+}
+
+/// Resolves a kernel version, package name or absolute config path.
+fn find_source(sources: &[KernelConfigSource], wanted: &str) -> Result<KernelConfigSource, String> {
+    if wanted.starts_with('/') {
+        return Ok(KernelConfigSource {
+            kernel: wanted.to_string(),
+            kernel_version: wanted.to_string(),
+            running: false,
+            path: Some(wanted.to_string()),
+        });
+    }
+    sources
+        .iter()
+        .find(|s| s.kernel_version == wanted || s.kernel == wanted)
+        .cloned()
+        .ok_or_else(|| format!("Kernel {} is not installed", wanted))
+}
+
+fn load(source: &KernelConfigSource) -> Result<BTreeMap<String, String>, String> {
+    let path = source
+        .path
+        .as_deref()
+        .ok_or_else(|| format!("No kernel config found for {}", source.kernel_version))?;
+    read_config(Path::new(path)).map(|text| parse_config(&text))
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: list available kernel configs
+// -----------------------------------------------------------------------------
+#[tauri::command]
+pub async fn get_kernel_configs() -> Result<Vec<KernelConfigSource>, String> {
+    config_sources().await
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: diff two kernel configs
+// -----------------------------------------------------------------------------
+/// `left` and `right` are kernel versions (`uname -r`), package names, or
+/// absolute paths to a config file.
+#[tauri::command]
+pub async fn diff_kernel_configs(left: String, right: String) -> Result<KernelConfigDiff, String> {
+    let sources = config_sources().await?;
+    let left = find_source(&sources, &left)?;
+    let right = find_source(&sources, &right)?;
+    let entries = diff_configs(&load(&left)?, &load(&right)?);
+    let count = |kind| entries.iter().filter(|e| e.change == kind).count();
+
+    Ok(KernelConfigDiff {
+        added: count(ConfigChange::Added),
+        removed: count(ConfigChange::Removed),
+        changed: count(ConfigChange::Changed),
+        left,
+        right,
+        entries,
+    })
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: one option across all installed kernels
+// -----------------------------------------------------------------------------
+#[tauri::command]
+pub async fn search_kernel_config_option(option: String) -> Result<Vec<KernelConfigOption>, String> {
+    let option = normalize_option(&option);
+    if option == "CONFIG_" {
+        return Err("No option given".to_string());
+    }
+
+    Ok(config_sources()
+        .await?
+        .into_iter()
+        .map(|source| KernelConfigOption {
+            value: load(&source).ok().and_then(|mut config| config.remove(&option)),
+            kernel: source.kernel,
+            kernel_version: source.kernel_version,
+            running: source.running,
+            path: source.path,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LTS: &str = "\
+#
+# Automatically generated file; DO NOT EDIT.
+# Linux/x86 6.6.32 Kernel Configuration
+#
+CONFIG_CC_VERSION_TEXT=\"gcc (GCC) 14.1.1 20240522\"
+CONFIG_LOCALVERSION=\"-lts\"
+CONFIG_HZ=300
+CONFIG_PREEMPT_VOLUNTARY=y
+# CONFIG_PREEMPT is not set
+CONFIG_BTRFS_FS=m
+# CONFIG_DRM_XE is not set
+";
+
+    const ZEN: &str = "\
+CONFIG_CC_VERSION_TEXT=\"gcc (GCC) 14.1.1 20240522\"
+CONFIG_LOCALVERSION=\"-zen\"
+CONFIG_HZ=1000
+# CONFIG_PREEMPT_VOLUNTARY is not set
+CONFIG_PREEMPT=y
+CONFIG_BTRFS_FS=m
+CONFIG_ZEN_INTERACTIVE=y
+";
+
+    #[test]
+    fn parses_values_and_unset_options() {
+        let options = parse_config(LTS);
+        assert_eq!(options.len(), 7);
+        assert_eq!(options["CONFIG_HZ"], "300");
+        assert_eq!(options["CONFIG_BTRFS_FS"], "m");
+        assert_eq!(options["CONFIG_PREEMPT"], "n");
+        assert_eq!(options["CONFIG_LOCALVERSION"], "\"-lts\"");
+        assert_eq!(options["CONFIG_CC_VERSION_TEXT"], "\"gcc (GCC) 14.1.1 20240522\"");
+        assert!(!options.contains_key("Automatically generated file; DO NOT EDIT."));
+    }
+
+    #[test]
+    fn diff_treats_absent_as_not_set() {
+        let entries = diff_configs(&parse_config(LTS), &parse_config(ZEN));
+        let summary: Vec<(&str, ConfigChange, Option<&str>, Option<&str>)> = entries
+            .iter()
+            .map(|e| (e.option.as_str(), e.change, e.left.as_deref(), e.right.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("CONFIG_HZ", ConfigChange::Changed, Some("300"), Some("1000")),
+                ("CONFIG_LOCALVERSION", ConfigChange::Changed, Some("\"-lts\""), Some("\"-zen\"")),
+                ("CONFIG_PREEMPT", ConfigChange::Changed, Some("n"), Some("y")),
+                ("CONFIG_PREEMPT_VOLUNTARY", ConfigChange::Changed, Some("y"), Some("n")),
+                ("CONFIG_ZEN_INTERACTIVE", ConfigChange::Added, None, Some("y")),
+            ]
+        );
+        // CONFIG_DRM_XE is "not set" on one side and absent on the other.
+        assert!(!entries.iter().any(|e| e.option == "CONFIG_DRM_XE"));
+
+        let removed = diff_configs(&parse_config("CONFIG_A=y\n"), &BTreeMap::new());
+        assert_eq!((removed[0].change, removed[0].right.as_deref()), (ConfigChange::Removed, None));
+    }
+}
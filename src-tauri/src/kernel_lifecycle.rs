@@ -0,0 +1,227 @@
+// src/kernel_lifecycle.rs
+//
+// Upstream support status per kernel series (LTS, EOL, release date). The
+// data ships in `data/kernel-lifecycle.json`; `refresh_kernel_lifecycle`
+// replaces it with a downloaded copy kept in the app cache dir.
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+use tokio::process::Command;
+
+use crate::model::KernelLifecycle;
+
+// -----------------------------------------------------------------------------
+// Configuration
+// -----------------------------------------------------------------------------
+const BUNDLED_LIFECYCLE: &str = include_str!("../data/kernel-lifecycle.json");
+const DEFAULT_LIFECYCLE_URL: &str =
+    "https://raw.githubusercontent.com/oguzkaganeren/linux-hub/main/src-tauri/data/kernel-lifecycle.json";
+const LIFECYCLE_CACHE_FILE: &str = "kernel_lifecycle.json";
+/// Event for installed kernels that are EOL or close to it.
+pub const KERNEL_LIFECYCLE_EVENT: &str = "kernel-lifecycle-warning";
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+#[derive(Debug, Deserialize)]
+struct SeriesEntry {
+    series: String,
+    #[serde(default)]
+    lts: bool,
+    released: Option<String>,
+    /// Projected or actual end of life; `eol` marks series that ended without one.
+    eol_date: Option<String>,
+    #[serde(default)]
+    eol: bool,
+    #[serde(default)]
+    experimental: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LifecycleData {
+    updated: String,
+    #[serde(default = "default_near_eol_days")]
+    near_eol_days: i64,
+    series: Vec<SeriesEntry>,
+}
+
+fn default_near_eol_days() -> i64 {
+    90
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LifecycleWarning {
+    pub package: String,
+    pub version: String,
+    pub lifecycle: KernelLifecycle,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LifecycleSource {
+    pub source: String,
+    pub updated: String,
+    pub series: usize,
+}
+
+// -----------------------------------------------------------------------------
+// Lookup
+// -----------------------------------------------------------------------------
+/// "6.12.3-arch1-1" / "6.12.3-1-MANJARO" / "6.19rc2-1" -> "6.12" / "6.19"
+pub fn kernel_series(version: &str) -> Option<String> {
+    let mut parts = version.split(|c: char| !c.is_ascii_digit());
+    let major = parts.next().filter(|p| !p.is_empty())?;
+    let minor = parts.next().filter(|p| !p.is_empty())?;
+    Some(format!("{}.{}", major, minor))
+}
+
+fn is_release_candidate(version: &str) -> bool {
+    let lower = version.to_lowercase();
+    lower
+        .match_indices("rc")
+        .any(|(i, _)| lower[i + 2..].starts_with(|c: char| c.is_ascii_digit()))
+}
+
+impl LifecycleData {
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        serde_json::from_str(raw).map_err(|e| format!("Invalid kernel lifecycle data: {}", e))
+    }
+
+    pub fn lookup(&self, version: &str) -> Option<KernelLifecycle> {
+        let series = kernel_series(version)?;
+        let rc = is_release_candidate(version);
+        let Some(entry) = self.series.iter().find(|e| e.series == series) else {
+            // Unknown series: only worth reporting when it is clearly a pre-release.
+            return rc.then_some(KernelLifecycle {
+                series,
+                lts: false,
+                released: None,
+                eol_date: None,
+                eol: false,
+                near_eol: false,
+                days_until_eol: None,
+                experimental: true,
+            });
+        };
+
+        let today = Utc::now().date_naive();
+        let eol_day = entry
+            .eol_date
+            .as_deref()
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+        let days_until_eol = eol_day.map(|d| (d - today).num_days());
+        let eol = entry.eol || days_until_eol.is_some_and(|d| d <= 0);
+
+        Some(KernelLifecycle {
+            series,
+            lts: entry.lts,
+            released: entry.released.clone(),
+            eol_date: entry.eol_date.clone(),
+            near_eol: !eol && days_until_eol.is_some_and(|d| d <= self.near_eol_days),
+            eol,
+            days_until_eol,
+            experimental: entry.experimental || rc,
+        })
+    }
+}
+
+pub fn warning_for(package: &str, version: &str, lifecycle: &KernelLifecycle) -> Option<LifecycleWarning> {
+    let message = if lifecycle.eol {
+        format!("{} ({}) has reached end of life and no longer receives fixes.", package, lifecycle.series)
+    } else if lifecycle.near_eol {
+        format!(
+            "{} ({}) reaches end of life in {} days.",
+            package,
+            lifecycle.series,
+            lifecycle.days_until_eol.unwrap_or_default()
+        )
+    } else {
+        return None;
+    };
+    Some(LifecycleWarning {
+        package: package.to_string(),
+        version: version.to_string(),
+        lifecycle: lifecycle.clone(),
+        message,
+    })
+}
+
+// -----------------------------------------------------------------------------
+// Storage helpers
+// -----------------------------------------------------------------------------
+fn cache_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app_handle.path().app_cache_dir().map_err(|e| e.to_string())?;
+    Ok(dir.join(LIFECYCLE_CACHE_FILE))
+}
+
+/// The refreshed copy when there is a valid one, otherwise the bundled data.
+pub async fn load(app_handle: &AppHandle) -> LifecycleData {
+    if let Ok(path) = cache_path(app_handle) {
+        if let Ok(raw) = tokio::fs::read_to_string(&path).await {
+            if let Ok(data) = LifecycleData::parse(&raw) {
+                return data;
+            }
+        }
+    }
+    LifecycleData::parse(BUNDLED_LIFECYCLE).expect("bundled kernel lifecycle data is valid")
+}
+
+async fn download(url: &str) -> Result<String, String> {
+    let output = Command::new("curl")
+        .args(["-fsSL", "--max-time", "20", url])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute curl: {}", e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(format!("Failed to download {}: {}", url, String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: refresh lifecycle data from a URL
+// -----------------------------------------------------------------------------
+#[tauri::command]
+pub async fn refresh_kernel_lifecycle(app_handle: AppHandle, url: Option<String>) -> Result<LifecycleSource, String> {
+    let url = url.filter(|u| !u.trim().is_empty()).unwrap_or_else(|| DEFAULT_LIFECYCLE_URL.to_string());
+    let raw = download(&url).await?;
+    // Validate before replacing the cached copy.
+    let data = LifecycleData::parse(&raw)?;
+
+    let path = cache_path(&app_handle)?;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    tokio::fs::write(&path, &raw)
+        .await
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    Ok(LifecycleSource { source: url, updated: data.updated, series: data.series.len() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_data_parses() {
+        let data = LifecycleData::parse(BUNDLED_LIFECYCLE).unwrap();
+        assert!(!data.series.is_empty());
+        assert!(data.series.iter().all(|e| kernel_series(&e.series).as_deref() == Some(e.series.as_str())));
+        assert!(data.lookup("6.12.3-arch1-1").is_some_and(|l| l.lts));
+    }
+
+    #[test]
+    fn series_from_package_versions() {
+        assert_eq!(kernel_series("6.12.3-arch1-1").as_deref(), Some("6.12"));
+        assert_eq!(kernel_series("6.12.3-1-MANJARO").as_deref(), Some("6.12"));
+        assert_eq!(kernel_series("6.19rc2-1").as_deref(), Some("6.19"));
+        assert!(is_release_candidate("6.19rc2-1"));
+        assert!(!is_release_candidate("6.12.3-arch1-1"));
+    }
+}
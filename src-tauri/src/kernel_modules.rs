@@ -0,0 +1,679 @@
+// src/kernel_modules.rs
+//
+// Loaded kernel modules, their parameters and `modinfo` data, plus the
+// persistent modprobe configuration. Changes the app makes go to one file it
+// owns (MANAGED_CONF); entries elsewhere are only rewritten when removing a
+// blacklist that lives in /etc/modprobe.d.
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+use crate::privileged::{self, RootWrite};
+
+// -----------------------------------------------------------------------------
+// Configuration
+// -----------------------------------------------------------------------------
+const PROC_MODULES: &str = "/proc/modules";
+const SYS_MODULE: &str = "/sys/module";
+/// modprobe.d(5): a file in an earlier directory hides one with the same name in a later one.
+const MODPROBE_DIRS: [&str; 3] = ["/etc/modprobe.d", "/run/modprobe.d", "/usr/lib/modprobe.d"];
+const MODULES_LOAD_DIRS: [&str; 3] = ["/etc/modules-load.d", "/run/modules-load.d", "/usr/lib/modules-load.d"];
+const MANAGED_CONF: &str = "/etc/modprobe.d/linuxhub.conf";
+const MANAGED_HEADER: &str = "# Managed by linuxhub; edited from the kernel module manager.";
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+#[derive(Debug, Serialize)]
+pub struct LoadedModule {
+    pub name: String,
+    pub size: u64,
+    pub use_count: u32,
+    /// Modules using this one, from /proc/modules and /sys/module/*/holders.
+    pub holders: Vec<String>,
+    pub state: String,
+    /// Current parameter values; parameters that aren't world-readable are left out.
+    pub parameters: BTreeMap<String, String>,
+    pub blacklisted: bool,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct ModuleParam {
+    pub name: String,
+    pub description: String,
+    pub r#type: Option<String>,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct ModuleInfo {
+    pub name: String,
+    pub filename: Option<String>,
+    pub builtin: bool,
+    pub description: Option<String>,
+    pub license: Option<String>,
+    pub version: Option<String>,
+    pub firmware: Vec<String>,
+    pub aliases: Vec<String>,
+    pub depends: Vec<String>,
+    pub params: Vec<ModuleParam>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ModprobeDirective {
+    Blacklist,
+    Options,
+    Install,
+    Remove,
+    Alias,
+    Softdep,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ModprobeEntry {
+    pub file: String,
+    pub line: usize,
+    pub directive: ModprobeDirective,
+    pub module: String,
+    /// Everything after the module name.
+    pub value: String,
+}
+
+/// A module listed for loading at boot in modules-load.d.
+#[derive(Debug, Serialize, Clone)]
+pub struct BootLoadEntry {
+    pub file: String,
+    pub line: usize,
+    pub module: String,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ModprobeIssueKind {
+    Duplicate,
+    Conflict,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModprobeIssue {
+    pub kind: ModprobeIssueKind,
+    pub module: String,
+    pub message: String,
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModprobeConfig {
+    pub managed_file: String,
+    /// Effective entries in the order modprobe reads them.
+    pub entries: Vec<ModprobeEntry>,
+    pub load_at_boot: Vec<BootLoadEntry>,
+    pub issues: Vec<ModprobeIssue>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ModprobeEdit {
+    /// `hard` also adds `install <module> /bin/false`, which blocks explicit loads too.
+    Blacklist { module: String, hard: Option<bool> },
+    Unblacklist { module: String },
+    SetOptions { module: String, options: BTreeMap<String, String> },
+    RemoveOptions { module: String },
+}
+
+// -----------------------------------------------------------------------------
+// Helpers
+// -----------------------------------------------------------------------------
+/// Module names treat '-' and '_' as the same character.
+fn normalize(name: &str) -> String {
+    name.replace('-', "_")
+}
+
+fn validate_module_name(name: &str) -> Result<(), String> {
+    // A leading '-' would reach modprobe as an option.
+    if name.is_empty() || name.starts_with('-') || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        return Err(format!("Invalid module name: {}", name));
+    }
+    Ok(())
+}
+
+fn validate_option(key: &str, value: &str) -> Result<(), String> {
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.') {
+        return Err(format!("Invalid option name: {}", key));
+    }
+    if value.is_empty() || value.chars().any(|c| c.is_whitespace() || c == '#') {
+        return Err(format!("Invalid value for {}: {:?}", key, value));
+    }
+    Ok(())
+}
+
+/// Parses one line of /proc/modules:
+/// "snd_hda_intel 61440 4 snd_sof,snd_hda_codec, Live 0x0000000000000000"
+fn parse_proc_modules_line(line: &str) -> Option<(String, u64, u32, Vec<String>, String)> {
+    let mut fields = line.split_whitespace();
+    let name = fields.next()?.to_string();
+    let size = fields.next()?.parse().ok()?;
+    let use_count = fields.next()?.parse().ok()?;
+    let holders = fields
+        .next()?
+        .split(',')
+        .filter(|h| !h.is_empty() && *h != "-")
+        .map(str::to_string)
+        .collect();
+    let state = fields.next().unwrap_or("Live").to_string();
+    Some((name, size, use_count, holders, state))
+}
+
+fn read_dir_names(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else { return Vec::new() };
+    let mut names: Vec<String> = entries.filter_map(|e| e.ok()).map(|e| e.file_name().to_string_lossy().to_string()).collect();
+    names.sort();
+    names
+}
+
+fn module_parameters(name: &str) -> BTreeMap<String, String> {
+    let dir = Path::new(SYS_MODULE).join(name).join("parameters");
+    read_dir_names(&dir)
+        .into_iter()
+        .filter_map(|p| std::fs::read_to_string(dir.join(&p)).ok().map(|v| (p, v.trim().to_string())))
+        .collect()
+}
+
+/// Parses `modinfo` output. Handles both "parm: name:desc (type)" and the
+/// separate "parmtype: name:type" lines newer kmod prints.
+pub fn parse_modinfo(name: &str, output: &str) -> ModuleInfo {
+    let mut info = ModuleInfo { name: name.to_string(), ..Default::default() };
+    let mut params: Vec<ModuleParam> = Vec::new();
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once(':') else { continue };
+        let value = value.trim().to_string();
+        match key.trim() {
+            "filename" => {
+                info.builtin = value == "(builtin)";
+                info.filename = Some(value);
+            }
+            "description" => info.description = Some(value),
+            "license" => info.license = Some(value),
+            "version" => info.version = Some(value),
+            "firmware" => info.firmware.push(value),
+            "alias" => info.aliases.push(value),
+            "depends" => info.depends = value.split(',').filter(|d| !d.is_empty()).map(str::to_string).collect(),
+            "parm" => {
+                let (param, rest) = value.split_once(':').unwrap_or((&value, ""));
+                let (description, r#type) = match rest.rsplit_once(" (") {
+                    Some((d, t)) if t.ends_with(')') => (d.to_string(), Some(t.trim_end_matches(')').to_string())),
+                    _ => (rest.to_string(), None),
+                };
+                params.push(ModuleParam { name: param.to_string(), description, r#type });
+            }
+            "parmtype" => {
+                if let Some((param, t)) = value.split_once(':') {
+                    match params.iter_mut().find(|p| p.name == param) {
+                        Some(p) => p.r#type = p.r#type.take().or(Some(t.to_string())),
+                        None => params.push(ModuleParam { name: param.to_string(), r#type: Some(t.to_string()), ..Default::default() }),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    info.params = params;
+    info
+}
+
+/// Effective *.conf files of a modprobe.d-style directory set, in read order.
+fn effective_conf_files(dirs: &[&str]) -> Vec<PathBuf> {
+    let mut by_name: BTreeMap<String, PathBuf> = BTreeMap::new();
+    for dir in dirs.iter().rev() {
+        for name in read_dir_names(Path::new(dir)).into_iter().filter(|n| n.ends_with(".conf")) {
+            by_name.insert(name.clone(), Path::new(dir).join(name));
+        }
+    }
+    by_name.into_values().collect()
+}
+
+pub fn parse_modprobe_conf(file: &str, contents: &str) -> Vec<ModprobeEntry> {
+    let mut entries = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        let words: Vec<&str> = line.split('#').next().unwrap_or("").split_whitespace().collect();
+        let directive = match words.first() {
+            Some(&"blacklist") => ModprobeDirective::Blacklist,
+            Some(&"options") => ModprobeDirective::Options,
+            Some(&"install") => ModprobeDirective::Install,
+            Some(&"remove") => ModprobeDirective::Remove,
+            Some(&"softdep") => ModprobeDirective::Softdep,
+            Some(&"alias") => ModprobeDirective::Alias,
+            _ => continue,
+        };
+        let (module, value) = match directive {
+            // "alias <wildcard> <module>": the module is what the alias resolves to.
+            ModprobeDirective::Alias => (words.get(2).copied(), words.get(1).copied().unwrap_or("").to_string()),
+            _ => (words.get(1).copied(), words.get(2..).unwrap_or_default().join(" ")),
+        };
+        if let Some(module) = module {
+            entries.push(ModprobeEntry { file: file.to_string(), line: i + 1, directive, module: normalize(module), value });
+        }
+    }
+    entries
+}
+
+fn parse_modules_load(file: &str, contents: &str) -> Vec<BootLoadEntry> {
+    contents
+        .lines()
+        .enumerate()
+        .filter_map(|(i, l)| {
+            let l = l.trim();
+            (!l.is_empty() && !l.starts_with('#') && !l.starts_with(';'))
+                .then(|| BootLoadEntry { file: file.to_string(), line: i + 1, module: normalize(l) })
+        })
+        .collect()
+}
+
+fn read_entries<T>(dirs: &[&str], parse: fn(&str, &str) -> Vec<T>) -> Vec<T> {
+    effective_conf_files(dirs)
+        .into_iter()
+        .filter_map(|p| {
+            let contents = std::fs::read_to_string(&p).ok()?;
+            Some(parse(&p.to_string_lossy(), &contents))
+        })
+        .flatten()
+        .collect()
+}
+
+fn is_hard_blacklist(entry: &ModprobeEntry) -> bool {
+    entry.directive == ModprobeDirective::Install && matches!(entry.value.as_str(), "/bin/false" | "/bin/true" | "/usr/bin/false" | "/usr/bin/true")
+}
+
+fn files_of<'a>(entries: impl IntoIterator<Item = (&'a str, usize)>) -> Vec<String> {
+    entries.into_iter().map(|(file, line)| format!("{}:{}", file, line)).collect()
+}
+
+fn is_blacklist(entry: &ModprobeEntry) -> bool {
+    entry.directive == ModprobeDirective::Blacklist || is_hard_blacklist(entry)
+}
+
+/// Duplicate blacklists, options set more than once (the last one wins) and
+/// blacklisted modules that modules-load.d loads anyway.
+pub fn find_issues(entries: &[ModprobeEntry], load_at_boot: &[BootLoadEntry]) -> Vec<ModprobeIssue> {
+    let mut issues = Vec::new();
+    let mut by_module: BTreeMap<&str, Vec<&ModprobeEntry>> = BTreeMap::new();
+    for entry in entries {
+        by_module.entry(&entry.module).or_default().push(entry);
+    }
+
+    for (module, module_entries) in &by_module {
+        let blacklists: Vec<&ModprobeEntry> =
+            module_entries.iter().copied().filter(|e| e.directive == ModprobeDirective::Blacklist).collect();
+        if blacklists.len() > 1 {
+            issues.push(ModprobeIssue {
+                kind: ModprobeIssueKind::Duplicate,
+                module: module.to_string(),
+                message: format!("{} is blacklisted {} times", module, blacklists.len()),
+                files: files_of(blacklists.iter().map(|e| (e.file.as_str(), e.line))),
+            });
+        }
+
+        // option name -> entries setting it, with their values
+        let mut options: BTreeMap<&str, Vec<(&ModprobeEntry, &str)>> = BTreeMap::new();
+        for entry in module_entries.iter().copied().filter(|e| e.directive == ModprobeDirective::Options) {
+            for option in entry.value.split_whitespace() {
+                let (key, value) = option.split_once('=').unwrap_or((option, ""));
+                options.entry(key).or_default().push((entry, value));
+            }
+        }
+        for (key, settings) in options.into_iter().filter(|(_, s)| s.len() > 1) {
+            let (last, winner) = *settings.last().unwrap();
+            let conflicting = settings.iter().any(|(_, v)| *v != winner);
+            issues.push(ModprobeIssue {
+                kind: if conflicting { ModprobeIssueKind::Conflict } else { ModprobeIssueKind::Duplicate },
+                module: module.to_string(),
+                message: if conflicting {
+                    format!(
+                        "{}.{} is set to {}; {}:{} wins with {}",
+                        module,
+                        key,
+                        settings.iter().map(|(_, v)| *v).collect::<Vec<_>>().join(", "),
+                        last.file,
+                        last.line,
+                        winner
+                    )
+                } else {
+                    format!("{}.{}={} is set {} times", module, key, winner, settings.len())
+                },
+                files: files_of(settings.iter().map(|(e, _)| (e.file.as_str(), e.line))),
+            });
+        }
+
+        let blocked: Vec<&ModprobeEntry> = module_entries.iter().copied().filter(|e| is_blacklist(e)).collect();
+        let loaded: Vec<&BootLoadEntry> = load_at_boot.iter().filter(|e| e.module == *module).collect();
+        if !blocked.is_empty() && !loaded.is_empty() {
+            issues.push(ModprobeIssue {
+                kind: ModprobeIssueKind::Conflict,
+                module: module.to_string(),
+                message: format!("{} is blacklisted but also listed in modules-load.d", module),
+                files: files_of(
+                    blocked.iter().map(|e| (e.file.as_str(), e.line)).chain(loaded.iter().map(|e| (e.file.as_str(), e.line))),
+                ),
+            });
+        }
+    }
+    issues
+}
+
+fn read_modprobe_config() -> ModprobeConfig {
+    let entries = read_entries(&MODPROBE_DIRS, parse_modprobe_conf);
+    let load_at_boot = read_entries(&MODULES_LOAD_DIRS, parse_modules_load);
+    ModprobeConfig {
+        managed_file: MANAGED_CONF.to_string(),
+        issues: find_issues(&entries, &load_at_boot),
+        entries,
+        load_at_boot,
+    }
+}
+
+/// Applies `edit` to the text of MANAGED_CONF.
+fn edit_managed_conf(contents: &str, edit: &ModprobeEdit) -> String {
+    let (module, blacklist_edit) = match edit {
+        ModprobeEdit::Blacklist { module, .. } | ModprobeEdit::Unblacklist { module } => (normalize(module), true),
+        ModprobeEdit::SetOptions { module, .. } | ModprobeEdit::RemoveOptions { module } => (normalize(module), false),
+    };
+    // Entries for the module that this edit replaces.
+    let replaced = |e: &ModprobeEntry| {
+        e.module == module && if blacklist_edit { is_blacklist(e) } else { e.directive == ModprobeDirective::Options }
+    };
+
+    let mut lines: Vec<String> = contents
+        .lines()
+        .filter(|line| {
+            parse_modprobe_conf(MANAGED_CONF, line)
+                .first()
+                .is_none_or(|e| !replaced(e))
+        })
+        .map(str::to_string)
+        .collect();
+    if lines.is_empty() {
+        lines.push(MANAGED_HEADER.to_string());
+    }
+
+    match edit {
+        ModprobeEdit::Blacklist { hard, .. } => {
+            lines.push(format!("blacklist {}", module));
+            if hard.unwrap_or(false) {
+                lines.push(format!("install {} /bin/false", module));
+            }
+        }
+        ModprobeEdit::SetOptions { options, .. } if !options.is_empty() => {
+            let options: Vec<String> = options.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+            lines.push(format!("options {} {}", module, options.join(" ")));
+        }
+        _ => {}
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Removes the blacklist lines for `module` from a file outside MANAGED_CONF.
+fn remove_blacklist_lines(contents: &str, file: &str, module: &str) -> String {
+    let kept: Vec<&str> = contents
+        .lines()
+        .filter(|line| {
+            parse_modprobe_conf(file, line)
+                .first()
+                .is_none_or(|e| e.module != module || !is_blacklist(e))
+        })
+        .collect();
+    kept.join("\n") + "\n"
+}
+
+/// Why the blacklist of `module` in `file` can't be edited away, or None for
+/// files under /etc. An /etc/modprobe.d file of the same name hides either kind.
+fn read_only_blacklist(module: &str, file: &str) -> Option<String> {
+    if file.starts_with("/etc/") {
+        return None;
+    }
+    let origin = if file.starts_with("/run/") {
+        "a runtime file generated at boot, so edits to it don't last"
+    } else {
+        "which belongs to a package"
+    };
+    Some(format!(
+        "{} is blacklisted by {}, {}; override it with an empty /etc/modprobe.d/{} first",
+        module,
+        file,
+        origin,
+        Path::new(file).file_name().unwrap_or_default().to_string_lossy()
+    ))
+}
+
+async fn modprobe_as_root(args: &[&str]) -> Result<(), String> {
+    privileged::run_as_root("modprobe", args).await.map(|_| ())
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: loaded modules
+// -----------------------------------------------------------------------------
+#[tauri::command]
+pub async fn get_kernel_modules() -> Result<Vec<LoadedModule>, String> {
+    let contents = tokio::fs::read_to_string(PROC_MODULES)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", PROC_MODULES, e))?;
+    let blacklisted: HashSet<String> = read_entries(&MODPROBE_DIRS, parse_modprobe_conf)
+        .into_iter()
+        .filter(is_blacklist)
+        .map(|e| e.module)
+        .collect();
+
+    let mut modules: Vec<LoadedModule> = contents
+        .lines()
+        .filter_map(parse_proc_modules_line)
+        .map(|(name, size, use_count, mut holders, state)| {
+            for holder in read_dir_names(&Path::new(SYS_MODULE).join(&name).join("holders")) {
+                if !holders.contains(&holder) {
+                    holders.push(holder);
+                }
+            }
+            LoadedModule {
+                parameters: module_parameters(&name),
+                blacklisted: blacklisted.contains(&name),
+                name,
+                size,
+                use_count,
+                holders,
+                state,
+            }
+        })
+        .collect();
+    modules.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(modules)
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: modinfo
+// -----------------------------------------------------------------------------
+#[tauri::command]
+pub async fn get_module_info(module: String) -> Result<ModuleInfo, String> {
+    validate_module_name(&module)?;
+    let output = Command::new("modinfo")
+        .arg(&module)
+        .env("LC_ALL", "C")
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run modinfo: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("modinfo {} failed: {}", module, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(parse_modinfo(&module, &String::from_utf8_lossy(&output.stdout)))
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: load / unload
+// -----------------------------------------------------------------------------
+/// Loads `module` with optional one-off parameters (not persisted).
+#[tauri::command]
+pub async fn load_kernel_module(module: String, options: Option<BTreeMap<String, String>>) -> Result<(), String> {
+    validate_module_name(&module)?;
+    let options = options.unwrap_or_default();
+    let mut args = vec![module.clone()];
+    for (key, value) in &options {
+        validate_option(key, value)?;
+        args.push(format!("{}={}", key, value));
+    }
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    modprobe_as_root(&args).await
+}
+
+#[tauri::command]
+pub async fn unload_kernel_module(module: String) -> Result<(), String> {
+    validate_module_name(&module)?;
+    let name = normalize(&module);
+    let contents = tokio::fs::read_to_string(PROC_MODULES).await.unwrap_or_default();
+    if let Some((_, _, use_count, holders, _)) =
+        contents.lines().filter_map(parse_proc_modules_line).find(|(n, ..)| *n == name)
+    {
+        if !holders.is_empty() {
+            return Err(format!("{} is in use by {}", name, holders.join(", ")));
+        }
+        if use_count > 0 {
+            return Err(format!("{} is in use ({} references)", name, use_count));
+        }
+    } else {
+        return Err(format!("{} is not loaded", name));
+    }
+    modprobe_as_root(&["-r", &name]).await
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: persistent modprobe configuration
+// -----------------------------------------------------------------------------
+#[tauri::command]
+pub async fn get_modprobe_config() -> Result<ModprobeConfig, String> {
+    Ok(read_modprobe_config())
+}
+
+/// Writes `edit` to MANAGED_CONF. Unblacklisting also removes the entries
+/// from other files in /etc/modprobe.d; package-provided ones in /usr/lib and
+/// generated ones in /run can't be removed and are reported as an error.
+#[tauri::command]
+pub async fn apply_modprobe_edit(edit: ModprobeEdit) -> Result<ModprobeConfig, String> {
+    match &edit {
+        ModprobeEdit::Blacklist { module, .. } | ModprobeEdit::Unblacklist { module } | ModprobeEdit::RemoveOptions { module } => {
+            validate_module_name(module)?
+        }
+        ModprobeEdit::SetOptions { module, options } => {
+            validate_module_name(module)?;
+            for (key, value) in options {
+                validate_option(key, value)?;
+            }
+        }
+    }
+
+    let current = std::fs::read_to_string(MANAGED_CONF).unwrap_or_default();
+    let mut writes = vec![RootWrite::config(MANAGED_CONF, edit_managed_conf(&current, &edit))];
+
+    if let ModprobeEdit::Unblacklist { module } = &edit {
+        let module = normalize(module);
+        let config = read_modprobe_config();
+        let mut elsewhere: Vec<&str> = config
+            .entries
+            .iter()
+            .filter(|e| e.module == module && e.file != MANAGED_CONF && is_blacklist(e))
+            .map(|e| e.file.as_str())
+            .collect();
+        elsewhere.dedup();
+        for file in elsewhere {
+            if let Some(reason) = read_only_blacklist(&module, file) {
+                return Err(reason);
+            }
+            let contents = std::fs::read_to_string(file).map_err(|e| format!("Failed to read {}: {}", file, e))?;
+            writes.push(RootWrite::config(file, remove_blacklist_lines(&contents, file, &module)));
+        }
+    }
+
+    privileged::apply_as_root(&writes, &[]).await?;
+    Ok(read_modprobe_config())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NVIDIA: &str = "/usr/lib/modprobe.d/nvidia-utils.conf";
+    const LOCAL: &str = "/etc/modprobe.d/local.conf";
+
+    fn entries() -> Vec<ModprobeEntry> {
+        let mut entries = parse_modprobe_conf(NVIDIA, "blacklist nouveau\n");
+        entries.extend(parse_modprobe_conf(
+            LOCAL,
+            "\
+# Keep nouveau away from the GPU
+blacklist nouveau
+install nouveau /bin/false
+options snd-hda-intel power_save=1 model=auto # laptop
+options snd_hda_intel power_save=0
+options iwlwifi power_save=1
+options iwlwifi power_save=1
+alias pci:v000010DEd*sv*sd*bc03sc*i* nvidia
+softdep nvidia pre: i2c_nvidia_gpu
+",
+        ));
+        entries
+    }
+
+    #[test]
+    fn parses_directives_and_normalizes_names() {
+        let entries = parse_modprobe_conf(LOCAL, "blacklist pcspkr\noptions snd-hda-intel power_save=1 model=auto # laptop\nalias pci:v000010DE* nvidia\nbogus line\n");
+        let summary: Vec<(usize, ModprobeDirective, &str, &str)> =
+            entries.iter().map(|e| (e.line, e.directive, e.module.as_str(), e.value.as_str())).collect();
+        assert_eq!(
+            summary,
+            [
+                (1, ModprobeDirective::Blacklist, "pcspkr", ""),
+                (2, ModprobeDirective::Options, "snd_hda_intel", "power_save=1 model=auto"),
+                (3, ModprobeDirective::Alias, "nvidia", "pci:v000010DE*"),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_duplicate_and_conflicting_entries() {
+        let load_at_boot = parse_modules_load("/etc/modules-load.d/gpu.conf", "# early\nnouveau\n");
+        let issues = find_issues(&entries(), &load_at_boot);
+        let summary: Vec<(ModprobeIssueKind, &str, &str)> =
+            issues.iter().map(|i| (i.kind, i.module.as_str(), i.message.as_str())).collect();
+        assert_eq!(
+            summary,
+            [
+                (ModprobeIssueKind::Duplicate, "iwlwifi", "iwlwifi.power_save=1 is set 2 times"),
+                (ModprobeIssueKind::Duplicate, "nouveau", "nouveau is blacklisted 2 times"),
+                (ModprobeIssueKind::Conflict, "nouveau", "nouveau is blacklisted but also listed in modules-load.d"),
+                (
+                    ModprobeIssueKind::Conflict,
+                    "snd_hda_intel",
+                    "snd_hda_intel.power_save is set to 1, 0; /etc/modprobe.d/local.conf:5 wins with 0"
+                ),
+            ]
+        );
+        assert_eq!(issues[1].files, [format!("{}:1", NVIDIA), format!("{}:2", LOCAL)]);
+        assert_eq!(issues[2].files.last().map(String::as_str), Some("/etc/modules-load.d/gpu.conf:2"));
+        assert!(find_issues(&parse_modprobe_conf(LOCAL, "blacklist pcspkr\n"), &[]).is_empty());
+    }
+
+    #[test]
+    fn module_names_cannot_look_like_options() {
+        assert!(validate_module_name("snd-hda-intel").is_ok());
+        assert!(validate_module_name("nvidia_drm").is_ok());
+        assert!(validate_module_name("-r").is_err());
+        assert!(validate_module_name("--remove").is_err());
+        assert!(validate_module_name("i915 nouveau").is_err());
+        assert!(validate_module_name("").is_err());
+    }
+
+    #[test]
+    fn only_etc_blacklists_are_editable() {
+        assert_eq!(read_only_blacklist("nouveau", LOCAL), None);
+        assert!(read_only_blacklist("nouveau", NVIDIA).unwrap().contains("belongs to a package"));
+        let runtime = read_only_blacklist("nouveau", "/run/modprobe.d/nvidia-prime.conf").unwrap();
+        assert!(runtime.contains("runtime file generated at boot"));
+        assert!(runtime.ends_with("empty /etc/modprobe.d/nvidia-prime.conf first"));
+    }
+}
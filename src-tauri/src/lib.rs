@@ -5,7 +5,6 @@ use std::path::PathBuf;
 use base64::{engine::general_purpose, Engine as _};
 use std::process::Command as StdCommand;
 use serde::{Serialize};
-use sysinfo::{System}; // Only System is needed here after refactoring
 
 mod pacman_manager;
 mod hardware;
@@ -13,11 +12,39 @@ mod model; // NEW: Import the model module
 mod system; // NEW: Import the system module
 
 use hardware::get_hardware_info;
-use model::*; // Import all structs from model.rs
 use system::start_system_monitor; // Import the new command
 
 mod bluetooth;
 mod printers;
+mod vercmp;
+mod advisories;
+mod news;
+mod partial_upgrade;
+mod package_files;
+mod local_package;
+mod kernel;
+mod kernel_lifecycle;
+mod privileged;
+mod bootloader;
+mod kernel_cmdline;
+mod mkinitcpio;
+mod dkms;
+mod kernel_config;
+mod kernel_modules;
+mod sysctl;
+mod hwids;
+mod pci;
+mod usb;
+mod audio;
+mod input;
+mod platform;
+mod guest_tools;
+mod edid;
+mod drm;
+mod driver_profiles;
+mod driver_manager;
+mod hybrid;
+mod sync_db;
 
 
 #[tauri::command]
@@ -51,154 +78,6 @@ pub struct UserInfo {
 
 
 
-// --- The Core Tauri Command Function ---
-
-#[tauri::command]
-async fn get_system_kernels() -> Result<String, String> {
-    let running_kernel = get_running_kernel_version();
-    let installed_kernels = get_installed_kernels().map_err(|e| format!("Failed to get installed kernels: {}", e))?;
-    let installable_kernels = get_installable_kernels_from_repos().await.map_err(|e| format!("Failed to get installable kernels: {}", e))?;
-
-    let result = KernelInfo {
-        running_kernel,
-        installed_kernels,
-        installable_kernels,
-    };
-
-    // Serialize the final struct to a JSON string
-    serde_json::to_string_pretty(&result)
-        .map_err(|e| format!("Failed to serialize kernel info to JSON: {}", e))
-}
-
-fn get_running_kernel_version() -> String {
-    let mut sys = System::new();
-    // This call is now redundant if kernel_version() is an associated function
-    sys.refresh_all(); 
-
-    
-    sysinfo::System::kernel_version()
-        .unwrap_or_else(|| "<unknown>".to_owned())
-}
-async fn run_command(cmd: &str, args: &[&str]) -> Result<String, String> {
-    let output = tokio::process::Command::new(cmd)
-        .args(args)
-        .output()
-        .await
-        .map_err(|e| format!("Failed to execute command '{}': {}", cmd, e))?;
-
-    if output.status.success() {
-        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
-    } else {
-        Err(format!(
-            "Command failed: {} {}", 
-            cmd, 
-            String::from_utf8_lossy(&output.stderr)
-        ))
-    }
-}
-
-fn get_installed_kernels() -> Result<Vec<InstalledKernel>, std::io::Error> {
-    // NOTE: For a real-world Tauri app, you might want to use 
-    // `tokio::process::Command` here as well, but for simplicity
-    // and correctness, we'll shell out to 'pacman' later on.
-    
-    // Simplified logic: Read files in /lib/modules/
-    let mut installed_kernels = Vec::new();
-    let modules_path = "/lib/modules";
-
-    for entry in std::fs::read_dir(modules_path)? {
-        let entry = entry?;
-        let path = entry.path();
-        if path.is_dir() {
-            if let Some(dir_name) = path.file_name().and_then(|s| s.to_str()) {
-                // A very simplified attempt to determine flavor based on common suffixes
-                let flavor = if dir_name.contains("lts") {
-                    "lts".to_string()
-                } else if dir_name.contains("rt") {
-                    "rt".to_string()
-                } else if dir_name.contains("zen") {
-                    "zen".to_string()
-                } else {
-                    "default".to_string() // Fallback for the main or other kernels
-                };
-
-                installed_kernels.push(InstalledKernel {
-                    name: format!("linux-{}", flavor), // Guess a package name
-                    version: dir_name.to_string(),
-                    flavor,
-                });
-            }
-        }
-    }
-
-    Ok(installed_kernels)
-}
-// Revised function to make parsing more defensive
-async fn get_installable_kernels_from_repos() -> Result<Vec<InstallableKernel>, String> {
-    // Search for all packages starting with 'linux-' (kernels, headers, etc.)
-    // We use the regex anchor '^' to only get packages that start with the prefix.
-    let output = run_command("pacman", &["-Ss", "^linux-"]).await?;
-
-    let mut installable_list = Vec::new();
-    let mut current_pkg_name: Option<String> = None;
-
-    for line in output.lines() {
-        // Line 1: 'repo/package-name version'
-        // Example: 'core/linux 6.6.1-arch1-1'
-        if line.starts_with("core/") || line.starts_with("extra/") || line.starts_with("community/") {
-            // New package entry starts
-            
-            // 1. Extract package name and version
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() < 2 { continue; } // Skip malformed lines
-
-            // parts[0] is typically 'repo/package-name'
-            let full_name = parts[0];
-            let pkg_name = full_name.split('/').nth(1).unwrap_or("unknown-pkg").to_string();
-            let pkg_version = parts[1].to_string();
-
-            // Skip packages that are clearly headers or docs, we only want the kernel image package
-            if pkg_name.ends_with("-headers") || pkg_name.ends_with("-docs") {
-                current_pkg_name = None;
-                continue;
-            }
-
-            // 2. Determine flavor
-            let flavor = if pkg_name.contains("lts") {
-                "lts"
-            } else if pkg_name.contains("rt") {
-                "rt"
-            } else if pkg_name.contains("zen") {
-                "zen"
-            } else if pkg_name == "linux" {
-                "main" // Standard mainline kernel
-            } else {
-                "other"
-            };
-
-            // 3. Create a new entry and store the name to look for the description next
-            current_pkg_name = Some(pkg_name.clone());
-            installable_list.push(InstallableKernel {
-                package_name: pkg_name,
-                version: pkg_version,
-                description: String::new(), // Will be filled in the next step
-                flavor: flavor.to_string(),
-            });
-
-        // Line 2: '    Description: ...'
-        } else if line.trim().starts_with("Description:") {
-            if let Some(name) = current_pkg_name.take() {
-                // Find the last entry we just created
-                if let Some(entry) = installable_list.iter_mut().rev().find(|e| e.package_name == name) {
-                    let desc = line.split(':').nth(1).unwrap_or("").trim().to_string();
-                    entry.description = desc;
-                }
-            }
-        }
-    }
-
-    Ok(installable_list)
-}
 #[tauri::command]
 fn get_user_profile_photo_base64() -> Result<String, String> {
     // 1. Get the user's home directory
@@ -318,6 +197,14 @@ printers::get_printers,
             pacman_manager::check_package_status,
             pacman_manager::check_system_updates,
              pacman_manager::check_packages_status,
+            advisories::audit_installed_packages,
+            news::get_distro_news,
+            news::mark_news_read,
+            partial_upgrade::check_partial_upgrade,
+            package_files::list_package_files,
+            package_files::find_file_owner,
+            package_files::verify_package_files,
+            local_package::inspect_local_package,
 
        bluetooth::start_discovery,
             bluetooth::connect_device,
@@ -325,7 +212,40 @@ printers::get_printers,
             bluetooth::pair_device,
             bluetooth::remove_device,
             bluetooth::list_paired_devices,
-            get_system_kernels])
+            kernel::get_system_kernels,
+            kernel::install_kernel,
+            kernel::remove_kernel,
+            kernel_lifecycle::refresh_kernel_lifecycle,
+            bootloader::get_bootloader_info,
+            bootloader::set_default_boot_entry,
+            kernel_cmdline::get_kernel_cmdline,
+            kernel_cmdline::preview_kernel_cmdline_change,
+            kernel_cmdline::apply_kernel_cmdline_change,
+            mkinitcpio::get_mkinitcpio_config,
+            mkinitcpio::preview_mkinitcpio_change,
+            mkinitcpio::apply_mkinitcpio_change,
+            mkinitcpio::regenerate_initramfs,
+            dkms::get_dkms_status,
+            dkms::rebuild_dkms_modules,
+            kernel_config::get_kernel_configs,
+            kernel_config::diff_kernel_configs,
+            kernel_config::search_kernel_config_option,
+            kernel_modules::get_kernel_modules,
+            kernel_modules::get_module_info,
+            kernel_modules::load_kernel_module,
+            kernel_modules::unload_kernel_module,
+            kernel_modules::get_modprobe_config,
+            kernel_modules::apply_modprobe_edit,
+            sysctl::get_sysctl_settings,
+            sysctl::apply_sysctl_changes,
+            driver_manager::get_installed_driver_profiles,
+            driver_manager::install_driver_profile,
+            driver_manager::switch_driver_profile,
+            driver_manager::remove_driver_profile,
+            hybrid::get_hybrid_graphics,
+            hybrid::set_app_gpu_offload,
+            guest_tools::get_guest_tools,
+            guest_tools::install_guest_tools])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
@@ -0,0 +1,313 @@
+// src/local_package.rs
+//
+// Preview of a downloaded package archive (`.pkg.tar.zst` / `.pkg.tar.xz`).
+// The metadata is read straight from the compressed tar stream; nothing is
+// extracted to disk. Installation goes through `manage_pacman_package` with
+// the "install_local" operation so it shares the frontend install queue.
+use chrono::DateTime;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use tokio::process::Command;
+
+use crate::vercmp::vercmp;
+
+// -----------------------------------------------------------------------------
+// Configuration
+// -----------------------------------------------------------------------------
+const PACMAN_KEYRING: &str = "/etc/pacman.d/gnupg/pubring.gpg";
+const PACKAGE_EXTENSIONS: [&str; 3] = [".pkg.tar.zst", ".pkg.tar.xz", ".pkg.tar"];
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SignatureStatus {
+    /// A detached `.sig` verified against the pacman keyring.
+    Valid { signer: Option<String> },
+    Invalid { reason: String },
+    /// No `.sig` next to the archive; pacman will apply LocalFileSigLevel.
+    Unsigned,
+    /// The signature exists but could not be checked (e.g. gpgv missing).
+    Unknown { reason: String },
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallAction {
+    Install,
+    Upgrade,
+    Downgrade,
+    Reinstall,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct BuildInfo {
+    pub packager: Option<String>,
+    pub build_date: Option<String>,
+    pub build_dir: Option<String>,
+    pub build_env: Vec<String>,
+    pub options: Vec<String>,
+    /// Number of packages that were installed in the build environment.
+    pub build_dependencies: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LocalPackagePreview {
+    pub path: String,
+    pub name: String,
+    pub base: Option<String>,
+    pub version: String,
+    pub description: Option<String>,
+    pub architecture: String,
+    pub url: Option<String>,
+    pub packager: Option<String>,
+    pub build_date: Option<String>,
+    pub installed_size: Option<u64>,
+    pub licenses: Vec<String>,
+    pub depends: Vec<String>,
+    pub optdepends: Vec<String>,
+    pub provides: Vec<String>,
+    pub conflicts: Vec<String>,
+    pub replaces: Vec<String>,
+    pub build_info: Option<BuildInfo>,
+    pub signature: SignatureStatus,
+    pub installed_version: Option<String>,
+    pub action: InstallAction,
+}
+
+// -----------------------------------------------------------------------------
+// Archive reading
+// -----------------------------------------------------------------------------
+pub fn is_package_archive(path: &str) -> bool {
+    PACKAGE_EXTENSIONS.iter().any(|ext| path.ends_with(ext))
+}
+
+fn open_archive(path: &Path) -> Result<Box<dyn Read>, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let reader = BufReader::new(file);
+    let name = path.to_string_lossy();
+
+    if name.ends_with(".zst") {
+        let decoder = zstd::stream::read::Decoder::new(reader).map_err(|e| format!("Invalid zstd stream: {}", e))?;
+        Ok(Box::new(decoder))
+    } else if name.ends_with(".xz") {
+        Ok(Box::new(xz2::read::XzDecoder::new(reader)))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+/// Returns the contents of `.PKGINFO` and `.BUILDINFO`. Both sit at the start of
+/// the archive, so the stream is abandoned as soon as they're found.
+fn read_metadata(path: &Path) -> Result<(String, Option<String>), String> {
+    let mut archive = tar::Archive::new(open_archive(path)?);
+    let mut pkginfo = None;
+    let mut buildinfo = None;
+
+    for entry in archive.entries().map_err(|e| format!("Invalid package archive: {}", e))? {
+        let mut entry = entry.map_err(|e| format!("Invalid package archive: {}", e))?;
+        let entry_path = entry.path().map_err(|e| e.to_string())?.to_string_lossy().to_string();
+
+        let target = match entry_path.as_str() {
+            ".PKGINFO" => &mut pkginfo,
+            ".BUILDINFO" => &mut buildinfo,
+            // Metadata files are all dot-files at the top; real content means we're done.
+            p if !p.starts_with('.') => break,
+            _ => continue,
+        };
+        let mut contents = String::new();
+        entry.read_to_string(&mut contents).map_err(|e| format!("Failed to read {}: {}", entry_path, e))?;
+        *target = Some(contents);
+
+        if pkginfo.is_some() && buildinfo.is_some() {
+            break;
+        }
+    }
+
+    let pkginfo = pkginfo.ok_or("Not a pacman package: .PKGINFO is missing")?;
+    Ok((pkginfo, buildinfo))
+}
+
+/// Parses `key = value` metadata; repeated keys (depend, license, ...) accumulate.
+fn parse_key_values(raw: &str) -> HashMap<String, Vec<String>> {
+    let mut map: HashMap<String, Vec<String>> = HashMap::new();
+    for line in raw.lines() {
+        if line.starts_with('#') {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once(" = ") {
+            map.entry(key.trim().to_string()).or_default().push(value.trim().to_string());
+        }
+    }
+    map
+}
+
+fn first(map: &HashMap<String, Vec<String>>, key: &str) -> Option<String> {
+    map.get(key).and_then(|v| v.first()).cloned()
+}
+
+fn all(map: &HashMap<String, Vec<String>>, key: &str) -> Vec<String> {
+    map.get(key).cloned().unwrap_or_default()
+}
+
+fn format_timestamp(raw: Option<String>) -> Option<String> {
+    let secs: i64 = raw?.parse().ok()?;
+    DateTime::from_timestamp(secs, 0).map(|d| d.to_rfc3339())
+}
+
+fn parse_build_info(raw: &str) -> BuildInfo {
+    let map = parse_key_values(raw);
+    BuildInfo {
+        packager: first(&map, "packager"),
+        build_date: format_timestamp(first(&map, "builddate")),
+        build_dir: first(&map, "builddir"),
+        build_env: all(&map, "buildenv"),
+        options: all(&map, "options"),
+        build_dependencies: map.get("installed").map_or(0, |v| v.len()),
+    }
+}
+
+/// `depend` entries of the archive's `.PKGINFO`, version constraints included.
+pub fn archive_depends(path: &Path) -> Result<Vec<String>, String> {
+    let (pkginfo, _) = read_metadata(path)?;
+    Ok(all(&parse_key_values(&pkginfo), "depend"))
+}
+
+fn install_action(version: &str, installed_version: Option<&str>) -> InstallAction {
+    match installed_version.map(|v| vercmp(version, v)) {
+        None => InstallAction::Install,
+        Some(Ordering::Greater) => InstallAction::Upgrade,
+        Some(Ordering::Less) => InstallAction::Downgrade,
+        Some(Ordering::Equal) => InstallAction::Reinstall,
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Signature and local database
+// -----------------------------------------------------------------------------
+async fn check_signature(path: &str) -> SignatureStatus {
+    let sig = format!("{}.sig", path);
+    if !Path::new(&sig).exists() {
+        return SignatureStatus::Unsigned;
+    }
+
+    // gpgv works read-only on the keyring, so no root or trustdb is needed.
+    let output = match Command::new("gpgv")
+        .args(["--keyring", PACMAN_KEYRING, &sig, path])
+        .env("LC_ALL", "C")
+        .output()
+        .await
+    {
+        Ok(o) => o,
+        Err(e) => return SignatureStatus::Unknown { reason: format!("Failed to execute gpgv: {}", e) },
+    };
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if output.status.success() {
+        let signer = stderr
+            .lines()
+            .find_map(|l| l.split_once("Good signature from ").map(|(_, s)| s.trim_matches('"').to_string()));
+        SignatureStatus::Valid { signer }
+    } else if stderr.contains("BAD signature") {
+        SignatureStatus::Invalid { reason: stderr.trim().to_string() }
+    } else {
+        // Typically "No public key": the signer isn't in the pacman keyring.
+        SignatureStatus::Unknown { reason: stderr.trim().to_string() }
+    }
+}
+
+async fn installed_version(name: &str) -> Option<String> {
+    let output = Command::new("pacman").args(["-Q", name]).output().await.ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .nth(1)
+        .map(|v| v.to_string())
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: preview a local package archive
+// -----------------------------------------------------------------------------
+#[tauri::command]
+pub async fn inspect_local_package(path: String) -> Result<LocalPackagePreview, String> {
+    if !is_package_archive(&path) {
+        return Err(format!("{} is not a pacman package archive", path));
+    }
+
+    let archive_path = path.clone();
+    let (pkginfo, buildinfo) = tokio::task::spawn_blocking(move || read_metadata(Path::new(&archive_path)))
+        .await
+        .map_err(|e| e.to_string())??;
+
+    let info = parse_key_values(&pkginfo);
+    let name = first(&info, "pkgname").ok_or(".PKGINFO has no pkgname")?;
+    let version = first(&info, "pkgver").ok_or(".PKGINFO has no pkgver")?;
+
+    let installed_version = installed_version(&name).await;
+    let action = install_action(&version, installed_version.as_deref());
+
+    Ok(LocalPackagePreview {
+        base: first(&info, "pkgbase"),
+        description: first(&info, "pkgdesc"),
+        architecture: first(&info, "arch").unwrap_or_else(|| "any".into()),
+        url: first(&info, "url"),
+        packager: first(&info, "packager"),
+        build_date: format_timestamp(first(&info, "builddate")),
+        installed_size: first(&info, "size").and_then(|s| s.parse().ok()),
+        licenses: all(&info, "license"),
+        depends: all(&info, "depend"),
+        optdepends: all(&info, "optdepend"),
+        provides: all(&info, "provides"),
+        conflicts: all(&info, "conflict"),
+        replaces: all(&info, "replaces"),
+        build_info: buildinfo.as_deref().map(parse_build_info),
+        signature: check_signature(&path).await,
+        installed_version,
+        action,
+        path,
+        name,
+        version,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/packages/hello-2.12.1-2-x86_64.pkg.tar.zst")
+    }
+
+    #[test]
+    fn reads_pkginfo_and_buildinfo_from_archive() {
+        let (pkginfo, buildinfo) = read_metadata(&fixture()).unwrap();
+        let info = parse_key_values(&pkginfo);
+        assert_eq!(first(&info, "pkgname").as_deref(), Some("hello"));
+        assert_eq!(first(&info, "pkgver").as_deref(), Some("2.12.1-2"));
+        assert_eq!(all(&info, "depend"), ["glibc>=2.39", "libintl.so=8-64"]);
+        assert_eq!(all(&info, "optdepend"), ["texinfo: read the info manual"]);
+        assert_eq!(format_timestamp(first(&info, "builddate")).as_deref(), Some("2024-05-31T16:08:37+00:00"));
+
+        let build_info = parse_build_info(&buildinfo.unwrap());
+        assert_eq!(build_info.packager.as_deref(), Some("Jane Packager <jane@example.org>"));
+        assert_eq!(build_info.build_env, ["!distcc", "color", "!ccache"]);
+        assert_eq!(build_info.options, ["strip", "!debug", "lto"]);
+        assert_eq!(build_info.build_dependencies, 3);
+        assert_eq!(archive_depends(&fixture()).unwrap(), ["glibc>=2.39", "libintl.so=8-64"]);
+    }
+
+    #[test]
+    fn action_compares_against_installed_version() {
+        assert_eq!(install_action("2.12.1-2", None), InstallAction::Install);
+        assert_eq!(install_action("2.12.1-2", Some("2.12.1-1")), InstallAction::Upgrade);
+        assert_eq!(install_action("2.12.1-2", Some("1:2.10-1")), InstallAction::Downgrade);
+        assert_eq!(install_action("2.12.1-2", Some("2.12.1-2")), InstallAction::Reinstall);
+    }
+}
@@ -0,0 +1,806 @@
+// src/mkinitcpio.rs
+//
+// mkinitcpio configuration: reads mkinitcpio.conf and the kernel presets,
+// validates hook/module edits (including ready-made recipes such as early
+// KMS) before writing them, and regenerates the images with streamed output.
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use std::process::Stdio;
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::mpsc;
+
+use crate::bootloader::read_shell_var;
+use crate::privileged::{self, RootWrite};
+
+// -----------------------------------------------------------------------------
+// Configuration
+// -----------------------------------------------------------------------------
+const MKINITCPIO_CONF: &str = "/etc/mkinitcpio.conf";
+const MKINITCPIO_CONF_D: &str = "/etc/mkinitcpio.conf.d";
+const PRESET_DIR: &str = "/etc/mkinitcpio.d";
+const HOOK_DIRS: [&str; 2] = ["/usr/lib/initcpio/install", "/etc/initcpio/install"];
+/// Event for streamed `mkinitcpio` output.
+pub const MKINITCPIO_EVENT: &str = "mkinitcpio-progress";
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct InitcpioArrays {
+    pub modules: Vec<String>,
+    pub binaries: Vec<String>,
+    pub files: Vec<String>,
+    pub hooks: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PresetImage {
+    /// "default", "fallback", ...
+    pub name: String,
+    pub image: Option<String>,
+    pub uki: Option<String>,
+    pub options: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MkinitcpioPreset {
+    /// File stem, e.g. "linux-lts"; what `mkinitcpio -p` expects.
+    pub name: String,
+    pub kernel_image: Option<String>,
+    pub images: Vec<PresetImage>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct MkinitcpioRecipe {
+    pub id: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+    pub add_modules: Vec<&'static str>,
+    /// (hook, insert before the first of these hooks; appended when none is present)
+    pub add_hooks: Vec<(&'static str, Vec<&'static str>)>,
+    pub remove_hooks: Vec<&'static str>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueLevel {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfigIssue {
+    pub level: IssueLevel,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MkinitcpioConfig {
+    pub path: String,
+    pub arrays: InitcpioArrays,
+    pub compression: Option<String>,
+    /// Drop-ins in mkinitcpio.conf.d override the main file; `arrays` and
+    /// `compression` already have them merged in.
+    pub drop_ins: Vec<String>,
+    pub presets: Vec<MkinitcpioPreset>,
+    pub available_hooks: Vec<String>,
+    pub recipes: Vec<MkinitcpioRecipe>,
+    pub issues: Vec<ConfigIssue>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct MkinitcpioEdit {
+    pub modules: Option<Vec<String>>,
+    pub binaries: Option<Vec<String>>,
+    pub files: Option<Vec<String>>,
+    pub hooks: Option<Vec<String>>,
+    /// Recipe ids, applied after the explicit arrays.
+    #[serde(default)]
+    pub recipes: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MkinitcpioPreview {
+    pub before: InitcpioArrays,
+    pub after: InitcpioArrays,
+    pub issues: Vec<ConfigIssue>,
+    pub can_apply: bool,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum InitramfsIssue {
+    MissingHook { hook: String },
+    MissingFirmware { module: String },
+    Warning { message: String },
+    Error { message: String },
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct InitramfsProgress {
+    pub line: String,
+    pub issue: Option<InitramfsIssue>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InitramfsResult {
+    pub success: bool,
+    pub issues: Vec<InitramfsIssue>,
+}
+
+// -----------------------------------------------------------------------------
+// Parsing
+// -----------------------------------------------------------------------------
+/// Splits bash array contents into words, honouring quotes and comments.
+fn split_words(body: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') => {
+                // Comment until end of line.
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+                if !current.is_empty() {
+                    words.push(std::mem::take(&mut current));
+                }
+            }
+            (None, c) if c.is_whitespace() => {
+                if !current.is_empty() {
+                    words.push(std::mem::take(&mut current));
+                }
+            }
+            (None, c) => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// Byte range of the last `KEY=( ... )` assignment, which may span lines.
+fn array_span(contents: &str, key: &str) -> Option<(usize, usize)> {
+    let mut found = None;
+    let mut offset = 0;
+    for line in contents.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.strip_prefix(key).is_some_and(|r| r.starts_with("=(")) {
+            let start = offset + (line.len() - trimmed.len());
+            let open = start + key.len() + 2;
+            let end = contents[open..].find(')').map(|i| open + i + 1)?;
+            found = Some((start, end));
+        }
+        offset += line.len();
+    }
+    found
+}
+
+fn read_array(contents: &str, key: &str) -> Vec<String> {
+    array_span(contents, key)
+        .map(|(start, end)| split_words(&contents[start + key.len() + 2..end - 1]))
+        .unwrap_or_default()
+}
+
+/// Characters an array entry may contain. mkinitcpio.conf is sourced by bash
+/// as root, so anything that could end the word or expand is refused.
+fn is_entry_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "_.+/@:-".contains(c)
+}
+
+/// mkinitcpio marks optional MODULES entries with a trailing '?'.
+fn is_valid_entry(key: &str, entry: &str) -> bool {
+    let name = if key == "MODULES" { entry.strip_suffix('?').unwrap_or(entry) } else { entry };
+    !name.is_empty() && name.chars().all(is_entry_char)
+}
+
+/// Single-quotes entries bash would otherwise glob or split.
+fn quote_entry(entry: &str) -> String {
+    if entry.chars().all(|c| c.is_ascii_alphanumeric() || "_./-".contains(c)) {
+        entry.to_string()
+    } else {
+        format!("'{}'", entry.replace('\'', "'\\''"))
+    }
+}
+
+fn write_array(contents: &str, key: &str, values: &[String]) -> String {
+    let words: Vec<String> = values.iter().map(|v| quote_entry(v)).collect();
+    let assignment = format!("{}=({})", key, words.join(" "));
+    match array_span(contents, key) {
+        Some((start, end)) => format!("{}{}{}", &contents[..start], assignment, &contents[end..]),
+        None => format!("{}\n{}\n", contents.trim_end(), assignment),
+    }
+}
+
+pub fn parse_arrays(contents: &str) -> InitcpioArrays {
+    InitcpioArrays {
+        modules: read_array(contents, "MODULES"),
+        binaries: read_array(contents, "BINARIES"),
+        files: read_array(contents, "FILES"),
+        hooks: read_array(contents, "HOOKS"),
+    }
+}
+
+impl InitcpioArrays {
+    fn entries(&self) -> [(&'static str, &Vec<String>); 4] {
+        [("MODULES", &self.modules), ("BINARIES", &self.binaries), ("FILES", &self.files), ("HOOKS", &self.hooks)]
+    }
+
+    fn entries_mut(&mut self) -> [(&'static str, &mut Vec<String>); 4] {
+        [
+            ("MODULES", &mut self.modules),
+            ("BINARIES", &mut self.binaries),
+            ("FILES", &mut self.files),
+            ("HOOKS", &mut self.hooks),
+        ]
+    }
+}
+
+/// Drop-ins in the order mkinitcpio sources them, as (path, contents).
+fn read_drop_ins() -> Vec<(String, String)> {
+    list_dir(MKINITCPIO_CONF_D, Some("conf"))
+        .iter()
+        .filter_map(|p| Some((p.to_string_lossy().to_string(), std::fs::read_to_string(p).ok()?)))
+        .collect()
+}
+
+/// The arrays mkinitcpio ends up with: each drop-in that assigns an array
+/// replaces the value from mkinitcpio.conf.
+fn merge_drop_ins(arrays: &InitcpioArrays, drop_ins: &[(String, String)]) -> InitcpioArrays {
+    let mut merged = arrays.clone();
+    for (_, contents) in drop_ins {
+        for (key, values) in merged.entries_mut() {
+            if array_span(contents, key).is_some() {
+                *values = read_array(contents, key);
+            }
+        }
+    }
+    merged
+}
+
+/// Last drop-in assigning `key`, which then decides its value.
+fn overriding_drop_in<'a>(drop_ins: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    drop_ins.iter().rev().find(|(_, contents)| array_span(contents, key).is_some()).map(|(path, _)| path.as_str())
+}
+
+fn parse_preset(name: &str, contents: &str) -> MkinitcpioPreset {
+    let images = read_array(contents, "PRESETS")
+        .into_iter()
+        .map(|preset| PresetImage {
+            image: read_shell_var(contents, &format!("{}_image", preset)),
+            uki: read_shell_var(contents, &format!("{}_uki", preset)),
+            options: read_shell_var(contents, &format!("{}_options", preset)),
+            name: preset,
+        })
+        .collect();
+    MkinitcpioPreset {
+        name: name.to_string(),
+        kernel_image: read_shell_var(contents, "ALL_kver"),
+        images,
+    }
+}
+
+fn list_dir(dir: &str, extension: Option<&str>) -> Vec<std::path::PathBuf> {
+    let mut paths: Vec<_> = std::fs::read_dir(dir)
+        .map(|d| d.filter_map(|e| e.ok()).map(|e| e.path()).collect())
+        .unwrap_or_default();
+    if let Some(ext) = extension {
+        paths.retain(|p: &std::path::PathBuf| p.extension().is_some_and(|e| e == ext));
+    }
+    paths.sort();
+    paths
+}
+
+fn available_hooks() -> Vec<String> {
+    let mut hooks: Vec<String> = HOOK_DIRS
+        .iter()
+        .flat_map(|d| list_dir(d, None))
+        .filter_map(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+        .collect();
+    hooks.sort();
+    hooks.dedup();
+    hooks
+}
+
+// -----------------------------------------------------------------------------
+// Recipes
+// -----------------------------------------------------------------------------
+pub fn recipes() -> Vec<MkinitcpioRecipe> {
+    vec![
+        MkinitcpioRecipe {
+            id: "nvidia-early-kms",
+            title: "Early KMS for NVIDIA",
+            description: "Loads the NVIDIA modules from the initramfs and drops the kms hook so nouveau is not included.",
+            add_modules: vec!["nvidia", "nvidia_modeset", "nvidia_uvm", "nvidia_drm"],
+            add_hooks: vec![],
+            remove_hooks: vec!["kms"],
+        },
+        MkinitcpioRecipe {
+            id: "amdgpu-early-kms",
+            title: "Early KMS for AMD",
+            description: "Loads amdgpu from the initramfs for a flicker-free, native-resolution boot.",
+            add_modules: vec!["amdgpu"],
+            add_hooks: vec![],
+            remove_hooks: vec![],
+        },
+        MkinitcpioRecipe {
+            id: "intel-early-kms",
+            title: "Early KMS for Intel",
+            description: "Loads i915 from the initramfs.",
+            add_modules: vec!["i915"],
+            add_hooks: vec![],
+            remove_hooks: vec![],
+        },
+        MkinitcpioRecipe {
+            id: "encrypt",
+            title: "Encrypted root (busybox init)",
+            description: "Adds keyboard, keymap and encrypt so the LUKS passphrase can be entered at boot. Needs cryptdevice= on the kernel command line.",
+            add_modules: vec![],
+            add_hooks: vec![
+                ("keyboard", vec!["keymap", "consolefont", "block", "encrypt", "filesystems"]),
+                ("keymap", vec!["consolefont", "block", "encrypt", "filesystems"]),
+                ("encrypt", vec!["lvm2", "filesystems"]),
+            ],
+            remove_hooks: vec![],
+        },
+        MkinitcpioRecipe {
+            id: "sd-encrypt",
+            title: "Encrypted root (systemd init)",
+            description: "Adds keyboard, sd-vconsole and sd-encrypt for systemd-based initramfs. Needs rd.luks.name= on the kernel command line.",
+            add_modules: vec![],
+            add_hooks: vec![
+                ("keyboard", vec!["sd-vconsole", "block", "sd-encrypt", "filesystems"]),
+                ("sd-vconsole", vec!["block", "sd-encrypt", "filesystems"]),
+                ("sd-encrypt", vec!["lvm2", "filesystems"]),
+            ],
+            remove_hooks: vec![],
+        },
+        MkinitcpioRecipe {
+            id: "lvm2",
+            title: "Root on LVM",
+            description: "Adds the lvm2 hook before filesystems.",
+            add_modules: vec![],
+            add_hooks: vec![("lvm2", vec!["filesystems"])],
+            remove_hooks: vec![],
+        },
+    ]
+}
+
+fn apply_recipe(arrays: &mut InitcpioArrays, recipe: &MkinitcpioRecipe) {
+    for module in &recipe.add_modules {
+        if !arrays.modules.iter().any(|m| m == module) {
+            arrays.modules.push(module.to_string());
+        }
+    }
+    arrays.hooks.retain(|h| !recipe.remove_hooks.contains(&h.as_str()));
+    for (hook, before) in &recipe.add_hooks {
+        if arrays.hooks.iter().any(|h| h == hook) {
+            continue;
+        }
+        let position = arrays.hooks.iter().position(|h| before.contains(&h.as_str()));
+        match position {
+            Some(i) => arrays.hooks.insert(i, hook.to_string()),
+            None => arrays.hooks.push(hook.to_string()),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Validation
+// -----------------------------------------------------------------------------
+/// Pairs that must appear in this order when both are present.
+const HOOK_ORDER: [(&str, &str); 10] = [
+    ("base", "udev"),
+    ("base", "systemd"),
+    ("udev", "autodetect"),
+    ("systemd", "autodetect"),
+    ("keyboard", "encrypt"),
+    ("keyboard", "sd-encrypt"),
+    ("block", "encrypt"),
+    ("encrypt", "lvm2"),
+    ("sd-encrypt", "lvm2"),
+    ("filesystems", "fsck"),
+];
+/// Hooks that set up the root device and so must run before `filesystems`.
+const BEFORE_FILESYSTEMS: [&str; 6] = ["block", "encrypt", "sd-encrypt", "lvm2", "mdadm_udev", "resume"];
+
+pub fn validate(arrays: &InitcpioArrays, available: &[String]) -> Vec<ConfigIssue> {
+    let hooks = &arrays.hooks;
+    let mut issues = Vec::new();
+    let mut error = |message: String| issues.push(ConfigIssue { level: IssueLevel::Error, message });
+    for (key, list) in arrays.entries() {
+        for entry in list.iter().filter(|e| !is_valid_entry(key, e)) {
+            error(format!("{} entry '{}' contains characters that are not allowed", key, entry.escape_debug()));
+        }
+    }
+    let has = |h: &str| hooks.iter().any(|x| x == h);
+    let pos = |h: &str| hooks.iter().position(|x| x == h);
+
+    if !available.is_empty() {
+        for hook in hooks.iter().filter(|h| !available.contains(h)) {
+            error(format!("Hook '{}' is not installed", hook));
+        }
+    }
+    if !has("base") && !has("systemd") {
+        error("HOOKS needs either base or systemd".to_string());
+    }
+    if !has("filesystems") {
+        error("Without the filesystems hook the root filesystem cannot be mounted".to_string());
+    }
+    if has("encrypt") && has("systemd") {
+        error("The encrypt hook does not work with systemd; use sd-encrypt".to_string());
+    }
+    if has("sd-encrypt") && !has("systemd") {
+        error("sd-encrypt requires the systemd hook".to_string());
+    }
+    for (first, second) in HOOK_ORDER {
+        if let (Some(a), Some(b)) = (pos(first), pos(second)) {
+            if a > b {
+                error(format!("'{}' must come before '{}'", first, second));
+            }
+        }
+    }
+    if let Some(fs) = pos("filesystems") {
+        for hook in BEFORE_FILESYSTEMS {
+            if pos(hook).is_some_and(|p| p > fs) {
+                error(format!("'{}' must come before 'filesystems'", hook));
+            }
+        }
+    }
+
+    let mut warn = |message: String| issues.push(ConfigIssue { level: IssueLevel::Warning, message });
+    if has("udev") && has("systemd") {
+        warn("udev and systemd both provide udev; keep only one".to_string());
+    }
+    for (name, list) in [("HOOKS", hooks), ("MODULES", &arrays.modules)] {
+        let mut seen = HashSet::new();
+        for item in list.iter().filter(|i| !seen.insert(i.as_str())) {
+            warn(format!("{} lists '{}' more than once", name, item));
+        }
+    }
+    if arrays.modules.iter().any(|m| m.starts_with("nvidia")) && has("kms") {
+        warn("With NVIDIA modules in MODULES, remove the kms hook so nouveau is not loaded early".to_string());
+    }
+    issues
+}
+
+fn preview(edit: &MkinitcpioEdit) -> Result<(MkinitcpioPreview, String), String> {
+    let contents = std::fs::read_to_string(MKINITCPIO_CONF).map_err(|e| format!("Failed to read {}: {}", MKINITCPIO_CONF, e))?;
+    let drop_ins = read_drop_ins();
+    let before = merge_drop_ins(&parse_arrays(&contents), &drop_ins);
+    let mut after = InitcpioArrays {
+        modules: edit.modules.clone().unwrap_or_else(|| before.modules.clone()),
+        binaries: edit.binaries.clone().unwrap_or_else(|| before.binaries.clone()),
+        files: edit.files.clone().unwrap_or_else(|| before.files.clone()),
+        hooks: edit.hooks.clone().unwrap_or_else(|| before.hooks.clone()),
+    };
+    let all_recipes = recipes();
+    for id in &edit.recipes {
+        let recipe = all_recipes.iter().find(|r| r.id == id).ok_or_else(|| format!("Unknown recipe: {}", id))?;
+        apply_recipe(&mut after, recipe);
+    }
+
+    let mut issues = validate(&after, &available_hooks());
+    // Edits go to mkinitcpio.conf, where a drop-in assigning the same array hides them.
+    for ((key, old), (_, new)) in before.entries().into_iter().zip(after.entries()) {
+        if let Some(path) = overriding_drop_in(&drop_ins, key).filter(|_| old != new) {
+            issues.push(ConfigIssue {
+                level: IssueLevel::Error,
+                message: format!("{} is set in {}, which overrides mkinitcpio.conf; change it there", key, path),
+            });
+        }
+    }
+    let preview = MkinitcpioPreview {
+        can_apply: !issues.iter().any(|i| i.level == IssueLevel::Error),
+        before,
+        after,
+        issues,
+    };
+    Ok((preview, contents))
+}
+
+// -----------------------------------------------------------------------------
+// Regeneration
+// -----------------------------------------------------------------------------
+fn classify(line: &str) -> Option<InitramfsIssue> {
+    if let Some(msg) = line.trim().strip_prefix("==> ERROR: ") {
+        if let Some(hook) = msg.strip_prefix("Hook '").and_then(|r| r.strip_suffix("' cannot be found")) {
+            return Some(InitramfsIssue::MissingHook { hook: hook.to_string() });
+        }
+        return Some(InitramfsIssue::Error { message: msg.to_string() });
+    }
+    if let Some(msg) = line.trim().strip_prefix("==> WARNING: ") {
+        if let Some(module) = msg.strip_prefix("Possibly missing firmware for module: ") {
+            return Some(InitramfsIssue::MissingFirmware { module: module.trim_matches('\'').to_string() });
+        }
+        return Some(InitramfsIssue::Warning { message: msg.to_string() });
+    }
+    None
+}
+
+async fn run_mkinitcpio(app_handle: &AppHandle, args: &[&str]) -> Result<InitramfsResult, String> {
+    let mut child = privileged::command_as_root("mkinitcpio", args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to spawn pkexec process: {}", e))?;
+
+    // mkinitcpio reports warnings and errors on stderr; merge both streams in order of arrival.
+    let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+    let stdout = child.stdout.take().ok_or("mkinitcpio stdout unavailable")?;
+    let stderr = child.stderr.take().ok_or("mkinitcpio stderr unavailable")?;
+    let out_tx = tx.clone();
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(stdout).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let _ = out_tx.send(line);
+        }
+    });
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(stderr).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let _ = tx.send(line);
+        }
+    });
+
+    let mut issues = Vec::new();
+    let mut output = Vec::new();
+    while let Some(line) = rx.recv().await {
+        let issue = classify(&line);
+        if let Some(i) = &issue {
+            issues.push(i.clone());
+        }
+        output.push(line.clone());
+        let _ = app_handle.emit(MKINITCPIO_EVENT, InitramfsProgress { line, issue });
+    }
+
+    let status = child.wait().await.map_err(|e| e.to_string())?;
+    if matches!(status.code(), Some(126) | Some(127)) {
+        return Err(privileged::describe_failure(status, &output.join("\n")));
+    }
+    Ok(InitramfsResult { success: status.success(), issues })
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: read mkinitcpio configuration
+// -----------------------------------------------------------------------------
+#[tauri::command]
+pub async fn get_mkinitcpio_config() -> Result<MkinitcpioConfig, String> {
+    let contents = std::fs::read_to_string(MKINITCPIO_CONF).map_err(|e| format!("Failed to read {}: {}", MKINITCPIO_CONF, e))?;
+    let drop_ins = read_drop_ins();
+    let arrays = merge_drop_ins(&parse_arrays(&contents), &drop_ins);
+    let available_hooks = available_hooks();
+
+    let presets = list_dir(PRESET_DIR, Some("preset"))
+        .iter()
+        .filter_map(|p| {
+            let name = p.file_stem()?.to_string_lossy().to_string();
+            Some(parse_preset(&name, &std::fs::read_to_string(p).ok()?))
+        })
+        .collect();
+
+    Ok(MkinitcpioConfig {
+        path: MKINITCPIO_CONF.to_string(),
+        compression: drop_ins
+            .iter()
+            .rev()
+            .find_map(|(_, c)| read_shell_var(c, "COMPRESSION"))
+            .or_else(|| read_shell_var(&contents, "COMPRESSION")),
+        drop_ins: drop_ins.into_iter().map(|(path, _)| path).collect(),
+        issues: validate(&arrays, &available_hooks),
+        arrays,
+        presets,
+        available_hooks,
+        recipes: recipes(),
+    })
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: preview / apply an edit
+// -----------------------------------------------------------------------------
+#[tauri::command]
+pub async fn preview_mkinitcpio_change(edit: MkinitcpioEdit) -> Result<MkinitcpioPreview, String> {
+    preview(&edit).map(|(p, _)| p)
+}
+
+/// Writes mkinitcpio.conf (the old file is kept as `mkinitcpio.conf~`) and
+/// optionally rebuilds every preset right after.
+#[tauri::command]
+pub async fn apply_mkinitcpio_change(
+    app_handle: AppHandle,
+    edit: MkinitcpioEdit,
+    regenerate: Option<bool>,
+) -> Result<MkinitcpioPreview, String> {
+    let (preview, contents) = preview(&edit)?;
+    if !preview.can_apply {
+        let errors: Vec<&str> = preview
+            .issues
+            .iter()
+            .filter(|i| i.level == IssueLevel::Error)
+            .map(|i| i.message.as_str())
+            .collect();
+        return Err(format!("Refusing to apply: {}", errors.join("; ")));
+    }
+
+    if preview.after != preview.before {
+        let mut updated = contents;
+        for ((key, old), (_, new)) in preview.before.entries().into_iter().zip(preview.after.entries()) {
+            if old != new {
+                updated = write_array(&updated, key, new);
+            }
+        }
+        privileged::apply_as_root(&[RootWrite::config(MKINITCPIO_CONF, updated)], &[]).await?;
+    }
+
+    if regenerate.unwrap_or(false) {
+        let result = run_mkinitcpio(&app_handle, &["-P"]).await?;
+        if !result.success {
+            return Err("mkinitcpio.conf was saved, but regenerating the initramfs failed".to_string());
+        }
+    }
+    Ok(preview)
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: regenerate initramfs images
+// -----------------------------------------------------------------------------
+/// Runs `mkinitcpio -P` (or `-p <preset>`), streaming every line as a
+/// `MKINITCPIO_EVENT` with missing hooks and firmware picked out.
+#[tauri::command]
+pub async fn regenerate_initramfs(app_handle: AppHandle, preset: Option<String>) -> Result<InitramfsResult, String> {
+    match preset {
+        Some(p) => {
+            if !Path::new(PRESET_DIR).join(format!("{}.preset", p)).exists() {
+                return Err(format!("Unknown mkinitcpio preset: {}", p));
+            }
+            run_mkinitcpio(&app_handle, &["-p", &p]).await
+        }
+        None => run_mkinitcpio(&app_handle, &["-P"]).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    const CONF: &str = "\
+# vim:set ft=sh
+MODULES=()
+BINARIES=()
+FILES=()
+#HOOKS=(base udev)
+HOOKS=(base udev autodetect microcode modconf kms keyboard keymap consolefont block filesystems fsck)
+COMPRESSION=\"zstd\"
+";
+
+    #[test]
+    fn splits_words_with_quotes_and_comments() {
+        assert_eq!(split_words("base udev  'a b' \"c d\""), words(&["base", "udev", "a b", "c d"]));
+        assert_eq!(split_words("\n  base # the base hook\n  udev\n"), words(&["base", "udev"]));
+        assert_eq!(split_words("nvidia# trailing"), words(&["nvidia"]));
+        assert!(split_words("  \n").is_empty());
+    }
+
+    #[test]
+    fn finds_the_last_uncommented_array() {
+        let (start, end) = array_span(CONF, "HOOKS").unwrap();
+        assert!(CONF[start..end].starts_with("HOOKS=(base udev autodetect"));
+        assert!(CONF[start..end].ends_with("fsck)"));
+
+        let multiline = "MODULES=(\n  i915\n  nvidia\n)\nMODULES=(amdgpu)\n";
+        assert_eq!(&multiline[array_span(multiline, "MODULES").unwrap().0..], "MODULES=(amdgpu)\n");
+        assert_eq!(read_array("MODULES=(\n  i915 # early KMS\n  nvidia\n)\n", "MODULES"), words(&["i915", "nvidia"]));
+        assert_eq!(array_span(CONF, "PRESETS"), None);
+    }
+
+    #[test]
+    fn writes_arrays_in_place_or_appends() {
+        let updated = write_array(CONF, "MODULES", &words(&["i915"]));
+        assert!(updated.contains("\nMODULES=(i915)\nBINARIES=()"));
+        assert_eq!(updated.len(), CONF.len() + 4);
+
+        let appended = write_array("COMPRESSION=\"zstd\"\n\n", "HOOKS", &words(&["base", "udev"]));
+        assert_eq!(appended, "COMPRESSION=\"zstd\"\nHOOKS=(base udev)\n");
+    }
+
+    #[test]
+    fn rejects_shell_syntax_and_quotes_what_bash_would_expand() {
+        let edit = InitcpioArrays {
+            modules: words(&["i915", "nvidia?"]),
+            binaries: words(&["fsck.ext4", "`id`", "a\nb"]),
+            files: words(&["/etc/modprobe.d/nvidia.conf", "/root/key;touch /x", "$(id)"]),
+            hooks: words(&["base", "udev", "filesystems"]),
+        };
+        let errors: Vec<String> = validate(&edit, &[]).into_iter().map(|i| i.message).collect();
+        assert_eq!(
+            errors,
+            [
+                "BINARIES entry '`id`' contains characters that are not allowed",
+                "BINARIES entry 'a\\nb' contains characters that are not allowed",
+                "FILES entry '/root/key;touch /x' contains characters that are not allowed",
+                "FILES entry '$(id)' contains characters that are not allowed",
+            ]
+        );
+
+        let updated = write_array("", "MODULES", &words(&["i915", "nvidia?", "crc32c@x"]));
+        assert_eq!(updated, "\nMODULES=(i915 'nvidia?' 'crc32c@x')\n");
+        assert_eq!(read_array(&updated, "MODULES"), words(&["i915", "nvidia?", "crc32c@x"]));
+    }
+
+    #[test]
+    fn recipes_insert_hooks_in_order() {
+        let mut arrays = parse_arrays(CONF);
+        let all = recipes();
+        let recipe = |id: &str| all.iter().find(|r| r.id == id).unwrap();
+
+        apply_recipe(&mut arrays, recipe("nvidia-early-kms"));
+        assert_eq!(arrays.modules, words(&["nvidia", "nvidia_modeset", "nvidia_uvm", "nvidia_drm"]));
+        assert!(!arrays.hooks.contains(&"kms".to_string()));
+
+        apply_recipe(&mut arrays, recipe("encrypt"));
+        apply_recipe(&mut arrays, recipe("lvm2"));
+        assert_eq!(
+            arrays.hooks,
+            words(&["base", "udev", "autodetect", "microcode", "modconf", "keyboard", "keymap", "consolefont", "block", "encrypt", "lvm2", "filesystems", "fsck"])
+        );
+        // Applying twice changes nothing.
+        let again = arrays.clone();
+        apply_recipe(&mut arrays, recipe("encrypt"));
+        assert_eq!(arrays, again);
+    }
+
+    #[test]
+    fn validation_catches_order_and_missing_hooks() {
+        let available = words(&["base", "udev", "systemd", "autodetect", "block", "encrypt", "sd-encrypt", "filesystems", "fsck", "kms"]);
+        let arrays = |hooks: &[&str]| InitcpioArrays { hooks: words(hooks), ..Default::default() };
+        let errors = |a: &InitcpioArrays| -> Vec<String> {
+            validate(a, &available).into_iter().filter(|i| i.level == IssueLevel::Error).map(|i| i.message).collect()
+        };
+
+        assert!(validate(&arrays(&["base", "udev", "autodetect", "block", "filesystems", "fsck"]), &available).is_empty());
+        assert_eq!(errors(&arrays(&["base", "udev", "block", "filesystems", "encrypt"])), ["'encrypt' must come before 'filesystems'"]);
+        assert_eq!(
+            errors(&arrays(&["systemd", "encrypt", "filesystems"])),
+            ["The encrypt hook does not work with systemd; use sd-encrypt"]
+        );
+        assert_eq!(errors(&arrays(&["udev", "filesystems", "plymouth"])), ["Hook 'plymouth' is not installed", "HOOKS needs either base or systemd"]);
+
+        let nvidia = InitcpioArrays { modules: words(&["nvidia", "nvidia"]), ..arrays(&["base", "udev", "kms", "filesystems"]) };
+        let warnings: Vec<String> = validate(&nvidia, &available).into_iter().map(|i| i.message).collect();
+        assert_eq!(
+            warnings,
+            [
+                "MODULES lists 'nvidia' more than once",
+                "With NVIDIA modules in MODULES, remove the kms hook so nouveau is not loaded early",
+            ]
+        );
+    }
+
+    #[test]
+    fn drop_ins_replace_the_arrays_they_assign() {
+        let drop_ins = vec![
+            ("/etc/mkinitcpio.conf.d/10-nvidia.conf".to_string(), "MODULES=(nvidia nvidia_drm)\n".to_string()),
+            ("/etc/mkinitcpio.conf.d/20-sd.conf".to_string(), "HOOKS=(systemd autodetect filesystems)\n".to_string()),
+        ];
+        let merged = merge_drop_ins(&parse_arrays(CONF), &drop_ins);
+        assert_eq!(merged.modules, words(&["nvidia", "nvidia_drm"]));
+        assert_eq!(merged.hooks, words(&["systemd", "autodetect", "filesystems"]));
+        assert_eq!(overriding_drop_in(&drop_ins, "HOOKS"), Some("/etc/mkinitcpio.conf.d/20-sd.conf"));
+        assert_eq!(overriding_drop_in(&drop_ins, "FILES"), None);
+    }
+}
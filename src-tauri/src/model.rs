@@ -108,13 +108,32 @@ pub struct KernelInfo {
     pub running_kernel: String,
     pub installed_kernels: Vec<InstalledKernel>,
     pub installable_kernels: Vec<InstallableKernel>,
+    pub orphaned_module_dirs: Vec<OrphanedModuleDir>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct InstalledKernel {
+    /// Package owning `vmlinuz`, e.g. "linux-lts" or "linux612".
     pub name: String,
+    /// Module directory name, i.e. the `uname -r` of this kernel.
     pub version: String,
     pub flavor: String,
+    pub package_version: Option<String>,
+    pub pkgbase: Option<String>,
+    pub headers_package: String,
+    pub headers_installed: bool,
+    pub modules_dir: String,
+    pub running: bool,
+    pub lifecycle: Option<KernelLifecycle>,
+}
+
+/// A `/usr/lib/modules` entry no installed package accounts for, typically
+/// left behind by a removed kernel or DKMS build.
+#[derive(Debug, Serialize)]
+pub struct OrphanedModuleDir {
+    pub path: String,
+    pub pkgbase: Option<String>,
+    pub reason: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -123,6 +142,22 @@ pub struct InstallableKernel {
     pub version: String,
     pub description: String,
     pub flavor: String,
+    pub lifecycle: Option<KernelLifecycle>,
+}
+
+/// Upstream support status of a kernel series, from `data/kernel-lifecycle.json`.
+#[derive(Debug, Serialize, Clone)]
+pub struct KernelLifecycle {
+    pub series: String,
+    pub lts: bool,
+    pub released: Option<String>,
+    pub eol_date: Option<String>,
+    pub eol: bool,
+    /// Within the data file's warning window of `eol_date`.
+    pub near_eol: bool,
+    pub days_until_eol: Option<i64>,
+    /// Release candidate or a series marked experimental.
+    pub experimental: bool,
 }
 /// Event name for continuous device updates sent to the frontend.
 pub const BLUETOOTH_DEVICE_EVENT: &str = "bluetooth-device-update";
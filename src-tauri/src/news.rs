@@ -0,0 +1,402 @@
+// src/news.rs
+//
+// Distribution news (Arch / Manjaro announcements). The feed is cached in the
+// app cache dir and read state is kept in the app data dir, so the update gate
+// in pacman_manager can work offline against the last fetched copy.
+use chrono::{DateTime, Utc};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+use tokio::process::Command;
+
+use crate::pacman_manager::last_full_upgrade;
+
+// -----------------------------------------------------------------------------
+// Configuration
+// -----------------------------------------------------------------------------
+const ARCH_NEWS_FEED: &str = "https://archlinux.org/feeds/news/";
+const MANJARO_NEWS_FEED: &str = "https://forum.manjaro.org/c/announcements/stable-updates/12.rss";
+const NEWS_CACHE_DIR: &str = "news";
+const NEWS_STATE_FILE: &str = "news_state.json";
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NewsItem {
+    pub id: String,
+    pub title: String,
+    pub link: String,
+    /// RFC 3339, `None` when the feed entry has no parseable date.
+    pub published: Option<String>,
+    pub summary: String,
+    /// Title mentions "manual intervention" (Arch's convention for breaking updates).
+    pub requires_intervention: bool,
+    pub read: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct NewsFeed {
+    pub feed_url: String,
+    /// False when the network fetch failed and the cached copy was used.
+    pub fresh: bool,
+    pub items: Vec<NewsItem>,
+}
+
+/// Persisted between runs: the configured feed and the ids that were read.
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct NewsState {
+    feed_url: Option<String>,
+    read: HashSet<String>,
+}
+
+// -----------------------------------------------------------------------------
+// Feed parsing (RSS 2.0 and Atom)
+// -----------------------------------------------------------------------------
+#[derive(Default)]
+struct EntryBuilder {
+    id: String,
+    title: String,
+    link: String,
+    date: String,
+    updated: String,
+    summary: String,
+}
+
+impl EntryBuilder {
+    fn build(self) -> NewsItem {
+        // Atom entries may only carry <updated>.
+        let date = if self.date.trim().is_empty() { &self.updated } else { &self.date };
+        let published = parse_feed_date(date.trim());
+        let title = self.title.trim().to_string();
+        NewsItem {
+            // RSS guid is optional; the link is the next best stable key.
+            id: if self.id.trim().is_empty() { self.link.trim().to_string() } else { self.id.trim().to_string() },
+            requires_intervention: title.to_lowercase().contains("manual intervention"),
+            title,
+            link: self.link.trim().to_string(),
+            published: published.map(|d| d.to_rfc3339()),
+            summary: strip_html(&self.summary),
+            read: false,
+        }
+    }
+}
+
+fn parse_feed_date(raw: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(raw)
+        .or_else(|_| DateTime::parse_from_rfc3339(raw))
+        .ok()
+        .map(|d| d.with_timezone(&Utc))
+}
+
+fn strip_html(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut in_tag = false;
+    for c in raw.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+fn atom_link(e: &BytesStart) -> Option<String> {
+    let rel = e
+        .try_get_attribute("rel")
+        .ok()
+        .flatten()
+        .and_then(|a| a.unescape_value().ok().map(|v| v.into_owned()));
+    if rel.as_deref().is_some_and(|r| r != "alternate") {
+        return None;
+    }
+    e.try_get_attribute("href")
+        .ok()
+        .flatten()
+        .and_then(|a| a.unescape_value().ok().map(|v| v.into_owned()))
+}
+
+pub fn parse_feed(xml: &str) -> Result<Vec<NewsItem>, String> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut items = Vec::new();
+    let mut entry: Option<EntryBuilder> = None;
+    let mut field = String::new();
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                match name.as_str() {
+                    "item" | "entry" => entry = Some(EntryBuilder::default()),
+                    "link" => {
+                        if let (Some(cur), Some(href)) = (entry.as_mut(), atom_link(&e)) {
+                            cur.link = href;
+                        }
+                    }
+                    _ => {}
+                }
+                field = name;
+            }
+            Ok(Event::Empty(e)) if e.local_name().as_ref() == b"link" => {
+                if let (Some(cur), Some(href)) = (entry.as_mut(), atom_link(&e)) {
+                    cur.link = href;
+                }
+            }
+            Ok(Event::Text(t)) => {
+                if let Some(cur) = entry.as_mut() {
+                    let text = t.unescape().map_err(|e| e.to_string())?;
+                    append_field(cur, &field, &text);
+                }
+            }
+            Ok(Event::CData(c)) => {
+                if let Some(cur) = entry.as_mut() {
+                    append_field(cur, &field, &String::from_utf8_lossy(&c));
+                }
+            }
+            Ok(Event::End(e)) => {
+                let name = e.local_name();
+                if name.as_ref() == b"item" || name.as_ref() == b"entry" {
+                    if let Some(done) = entry.take() {
+                        items.push(done.build());
+                    }
+                }
+                field.clear();
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("Invalid news feed at {}: {}", reader.buffer_position(), e)),
+            _ => {}
+        }
+    }
+
+    Ok(items)
+}
+
+fn append_field(entry: &mut EntryBuilder, field: &str, text: &str) {
+    let target = match field {
+        "title" => &mut entry.title,
+        "guid" | "id" => &mut entry.id,
+        // Atom links come from the href attribute, RSS links are text.
+        "link" if entry.link.is_empty() => &mut entry.link,
+        "pubDate" | "published" | "date" => &mut entry.date,
+        "updated" => &mut entry.updated,
+        "description" | "summary" | "content" | "encoded" => &mut entry.summary,
+        _ => return,
+    };
+    target.push_str(text);
+}
+
+// -----------------------------------------------------------------------------
+// Storage helpers
+// -----------------------------------------------------------------------------
+/// One cached copy per feed URL, so switching feeds never serves another feed's items.
+fn cache_file_name(url: &str) -> String {
+    let key: String = url
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+        .collect();
+    format!("{}.xml", key.trim_matches('_'))
+}
+
+fn cache_path(app_handle: &AppHandle, url: &str) -> Result<PathBuf, String> {
+    let dir = app_handle.path().app_cache_dir().map_err(|e| e.to_string())?;
+    Ok(dir.join(NEWS_CACHE_DIR).join(cache_file_name(url)))
+}
+
+fn state_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app_handle.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(dir.join(NEWS_STATE_FILE))
+}
+
+async fn load_state(app_handle: &AppHandle) -> NewsState {
+    let Ok(path) = state_path(app_handle) else {
+        return NewsState::default();
+    };
+    match tokio::fs::read_to_string(&path).await {
+        Ok(raw) => serde_json::from_str(&raw).unwrap_or_default(),
+        Err(_) => NewsState::default(),
+    }
+}
+
+async fn save_state(app_handle: &AppHandle, state: &NewsState) -> Result<(), String> {
+    let path = state_path(app_handle)?;
+    write_file(&path, &serde_json::to_string_pretty(state).map_err(|e| e.to_string())?).await
+}
+
+async fn write_file(path: &PathBuf, contents: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    tokio::fs::write(path, contents)
+        .await
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Picks the distro feed from /etc/os-release when the user hasn't configured one.
+fn default_feed_url() -> &'static str {
+    let os_release = std::fs::read_to_string("/etc/os-release").unwrap_or_default();
+    let is_manjaro = os_release
+        .lines()
+        .any(|l| l.starts_with("ID=") && l.trim_start_matches("ID=").trim_matches('"') == "manjaro");
+    if is_manjaro {
+        MANJARO_NEWS_FEED
+    } else {
+        ARCH_NEWS_FEED
+    }
+}
+
+async fn download(url: &str) -> Result<String, String> {
+    let output = Command::new("curl")
+        .args(["-fsSL", "--max-time", "20", url])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute curl: {}", e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(format!("Failed to download {}: {}", url, String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
+
+/// Fetches the feed, refreshing the cache; falls back to the cached copy when offline.
+async fn fetch_feed(app_handle: &AppHandle, state: &NewsState) -> Result<NewsFeed, String> {
+    let url = state.feed_url.clone().unwrap_or_else(|| default_feed_url().to_string());
+    let cache = cache_path(app_handle, &url)?;
+
+    let (xml, fresh) = match download(&url).await {
+        Ok(xml) => {
+            // A failed cache write shouldn't hide the news we just fetched.
+            let _ = write_file(&cache, &xml).await;
+            (xml, true)
+        }
+        Err(download_err) => match tokio::fs::read_to_string(&cache).await {
+            Ok(xml) => (xml, false),
+            Err(_) => return Err(download_err),
+        },
+    };
+
+    let mut items = parse_feed(&xml)?;
+    for item in &mut items {
+        item.read = state.read.contains(&item.id);
+    }
+
+    Ok(NewsFeed { feed_url: url, fresh, items })
+}
+
+// -----------------------------------------------------------------------------
+// Update gate (used by pacman_manager)
+// -----------------------------------------------------------------------------
+/// Unread items published after the last full system upgrade.
+pub async fn unread_news_since_last_update(app_handle: &AppHandle) -> Result<Vec<NewsItem>, String> {
+    let state = load_state(app_handle).await;
+    let feed = fetch_feed(app_handle, &state).await?;
+    let last_upgrade = last_full_upgrade().await;
+
+    Ok(feed
+        .items
+        .into_iter()
+        .filter(|item| !item.read)
+        .filter(|item| {
+            let published = item.published.as_deref().and_then(|p| DateTime::parse_from_rfc3339(p).ok());
+            match (published, last_upgrade) {
+                (Some(published), Some(last)) => published.with_timezone(&Utc) > last,
+                // Never upgraded through pacman (or no log): everything unread counts.
+                (Some(_), None) => true,
+                (None, _) => false,
+            }
+        })
+        .collect())
+}
+
+pub async fn mark_read(app_handle: &AppHandle, ids: impl IntoIterator<Item = String>) -> Result<(), String> {
+    let mut state = load_state(app_handle).await;
+    state.read.extend(ids);
+    save_state(app_handle, &state).await
+}
+
+// -----------------------------------------------------------------------------
+// Tauri commands
+// -----------------------------------------------------------------------------
+/// Returns the distro news. Passing `feed_url` changes the configured feed.
+#[tauri::command]
+pub async fn get_distro_news(app_handle: AppHandle, feed_url: Option<String>) -> Result<NewsFeed, String> {
+    let mut state = load_state(&app_handle).await;
+    if let Some(url) = feed_url {
+        state.feed_url = if url.trim().is_empty() { None } else { Some(url) };
+        save_state(&app_handle, &state).await?;
+    }
+    fetch_feed(&app_handle, &state).await
+}
+
+#[tauri::command]
+pub async fn mark_news_read(app_handle: AppHandle, ids: Vec<String>) -> Result<(), String> {
+    mark_read(&app_handle, ids).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn fixture(name: &str) -> String {
+        std::fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)).unwrap()
+    }
+
+    #[test]
+    fn parses_rss_feed() {
+        let items = parse_feed(&fixture("arch-news-feed.xml")).unwrap();
+        assert_eq!(items.len(), 2);
+
+        assert_eq!(items[0].id, "tag:archlinux.org,2025-01-14:/news/critical-rsync-security-release-340/");
+        assert_eq!(items[0].link, "https://archlinux.org/news/critical-rsync-security-release-340/");
+        assert_eq!(items[0].published.as_deref(), Some("2025-01-14T18:38:53+00:00"));
+        assert_eq!(items[0].summary, "We'd like to raise awareness about the rsync security release version 3.4.0-1.");
+        assert!(!items[0].requires_intervention);
+
+        // No guid: the link is the id.
+        assert_eq!(items[1].id, items[1].link);
+        assert!(items[1].requires_intervention);
+        assert!(items[1].summary.contains("separate user & dropping privileges"));
+    }
+
+    #[test]
+    fn parses_atom_feed() {
+        let items = parse_feed(&fixture("atom-news-feed.xml")).unwrap();
+        assert_eq!(items.len(), 2);
+
+        assert_eq!(items[0].title, "Stable update 2024-11-30");
+        assert_eq!(items[0].link, "https://example.org/news/stable-update-2024-11-30/");
+        assert_eq!(items[0].published.as_deref(), Some("2024-11-30T08:15:00+00:00"));
+        assert_eq!(items[0].summary, "Kernels & firmware were updated.");
+
+        // Only <updated>, explicit rel="alternate" link, CDATA content.
+        assert_eq!(items[1].link, "https://example.org/news/mirror-changes/");
+        assert_eq!(items[1].published.as_deref(), Some("2024-10-02T12:00:00+00:00"));
+        assert_eq!(items[1].summary, "Old mirrors were retired.");
+    }
+
+    #[test]
+    fn caches_each_feed_separately() {
+        assert_eq!(cache_file_name(ARCH_NEWS_FEED), "archlinux.org_feeds_news.xml");
+        assert_ne!(cache_file_name(ARCH_NEWS_FEED), cache_file_name(MANJARO_NEWS_FEED));
+    }
+}
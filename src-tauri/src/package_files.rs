@@ -0,0 +1,355 @@
+// src/package_files.rs
+//
+// File-level package queries: installed file lists (`-Ql`), file ownership
+// (`-Qo`, falling back to the sync file databases with `-F`) and integrity
+// checks against the package mtree (`-Qkk`).
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tokio::process::Command;
+
+/// Event name for streamed verification progress.
+pub const PACKAGE_SCAN_EVENT: &str = "package-scan-progress";
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+#[derive(Debug, Serialize)]
+pub struct PackageFile {
+    pub path: String,
+    pub is_dir: bool,
+    /// `None` when the file is missing or unreadable.
+    pub size: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PackageFileList {
+    pub package: String,
+    pub files: Vec<PackageFile>,
+    pub total_size: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileOwner {
+    pub package: String,
+    pub version: String,
+    /// Sync repository for owners found through `-F`, `None` for installed packages.
+    pub repository: Option<String>,
+    pub installed: bool,
+    pub file: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileOwnership {
+    pub query: String,
+    pub owners: Vec<FileOwner>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifyIssueKind {
+    Missing,
+    Checksum,
+    Permissions,
+    Ownership,
+    Size,
+    ModificationTime,
+    Symlink,
+    FileType,
+    Other,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct VerifyIssue {
+    pub package: String,
+    pub path: String,
+    pub kind: VerifyIssueKind,
+    /// pacman's own wording, e.g. "SHA256 checksum mismatch".
+    pub detail: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PackageVerifyResult {
+    pub package: String,
+    pub total_files: u64,
+    pub altered_files: u64,
+    pub mtree_available: bool,
+    pub issues: Vec<VerifyIssue>,
+    /// `error:` lines from pacman; the package could not be (fully) checked.
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ScanProgress {
+    pub package: String,
+    pub processed: usize,
+    pub total: usize,
+    pub issue: Option<VerifyIssue>,
+}
+
+// -----------------------------------------------------------------------------
+// Helpers
+// -----------------------------------------------------------------------------
+async fn pacman(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("pacman")
+        .args(args)
+        .env("LC_ALL", "C")
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute pacman: {}", e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+fn classify(detail: &str) -> VerifyIssueKind {
+    match detail {
+        "No such file or directory" => VerifyIssueKind::Missing,
+        "Permissions mismatch" => VerifyIssueKind::Permissions,
+        "UID mismatch" | "GID mismatch" => VerifyIssueKind::Ownership,
+        "Size mismatch" => VerifyIssueKind::Size,
+        "Modification time mismatch" => VerifyIssueKind::ModificationTime,
+        "Symlink path mismatch" => VerifyIssueKind::Symlink,
+        "File type mismatch" => VerifyIssueKind::FileType,
+        d if d.ends_with("checksum mismatch") => VerifyIssueKind::Checksum,
+        _ => VerifyIssueKind::Other,
+    }
+}
+
+/// "warning: pkg: /path (Reason)" → issue
+fn parse_issue(line: &str) -> Option<VerifyIssue> {
+    let rest = line.strip_prefix("warning: ")?;
+    let (package, rest) = rest.split_once(": ")?;
+    let rest = rest.strip_suffix(')')?;
+    let (path, detail) = rest.rsplit_once(" (")?;
+    Some(VerifyIssue {
+        package: package.to_string(),
+        path: path.to_string(),
+        kind: classify(detail),
+        detail: detail.to_string(),
+    })
+}
+
+/// "pkg: 123 total files, 2 altered files" → (pkg, total, altered)
+fn parse_summary(line: &str) -> Option<(String, u64, u64)> {
+    let (package, rest) = line.split_once(": ")?;
+    let (total, altered) = rest.split_once(", ")?;
+    let total = total.strip_suffix(" total files").or_else(|| total.strip_suffix(" total file"))?;
+    let altered = altered.split_whitespace().next()?;
+    Some((package.to_string(), total.parse().ok()?, altered.parse().ok()?))
+}
+
+/// The result of one `pacman -Qkk <package>` run from its output.
+fn parse_verify_output(package: &str, stdout: &str, stderr: &str) -> PackageVerifyResult {
+    let mut result = PackageVerifyResult {
+        package: package.to_string(),
+        total_files: 0,
+        altered_files: 0,
+        mtree_available: true,
+        issues: Vec::new(),
+        errors: Vec::new(),
+    };
+    for line in stderr.lines() {
+        if let Some(issue) = parse_issue(line) {
+            result.issues.push(issue);
+        } else if line.ends_with(": no mtree file") {
+            result.mtree_available = false;
+        } else if let Some(error) = line.strip_prefix("error: ") {
+            result.errors.push(error.to_string());
+        }
+    }
+    if let Some((_, total_files, altered_files)) = stdout.lines().find_map(parse_summary) {
+        result.total_files = total_files;
+        result.altered_files = altered_files;
+    }
+    result
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: list files of an installed package
+// -----------------------------------------------------------------------------
+#[tauri::command]
+pub async fn list_package_files(package_name: String) -> Result<PackageFileList, String> {
+    let out = pacman(&["-Ql", &package_name]).await?;
+
+    let files: Vec<PackageFile> = out
+        .lines()
+        .filter_map(|l| l.split_once(' ').map(|(_, path)| path.to_string()))
+        .map(|path| {
+            let meta = std::fs::symlink_metadata(&path).ok();
+            PackageFile {
+                is_dir: path.ends_with('/'),
+                size: meta.filter(|m| !m.is_dir()).map(|m| m.len()),
+                path,
+            }
+        })
+        .collect();
+
+    let total_size = files.iter().filter_map(|f| f.size).sum();
+    Ok(PackageFileList { package: package_name, files, total_size })
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: which package owns a path
+// -----------------------------------------------------------------------------
+#[tauri::command]
+pub async fn find_file_owner(path: String) -> Result<FileOwnership, String> {
+    // Installed packages first: "/usr/bin/ls is owned by coreutils 9.4-3"
+    if let Ok(out) = pacman(&["-Qo", &path]).await {
+        let owners = out
+            .lines()
+            .filter_map(|l| {
+                let (file, owner) = l.split_once(" is owned by ")?;
+                let (package, version) = owner.trim().split_once(' ')?;
+                Some(FileOwner {
+                    package: package.to_string(),
+                    version: version.to_string(),
+                    repository: None,
+                    installed: true,
+                    file: file.to_string(),
+                })
+            })
+            .collect::<Vec<_>>();
+        if !owners.is_empty() {
+            return Ok(FileOwnership { query: path, owners });
+        }
+    }
+
+    // Not installed: search the sync file databases ("pacman -Fy" keeps them fresh).
+    // Machine readable format: repo\0pkgname\0pkgver\0path
+    let out = pacman(&["-F", "--machinereadable", &path])
+        .await
+        .map_err(|e| if e.is_empty() { format!("No package owns {}", path) } else { e })?;
+    let installed = pacman(&["-Qq"]).await.unwrap_or_default();
+    let installed: Vec<&str> = installed.lines().collect();
+
+    let owners = out
+        .lines()
+        .filter_map(|l| {
+            let mut fields = l.split('\0');
+            let repository = fields.next()?.to_string();
+            let package = fields.next()?.to_string();
+            let version = fields.next()?.to_string();
+            let file = format!("/{}", fields.next()?);
+            Some(FileOwner {
+                installed: installed.contains(&package.as_str()),
+                package,
+                version,
+                repository: Some(repository),
+                file,
+            })
+        })
+        .collect();
+
+    Ok(FileOwnership { query: path, owners })
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: verify package files against their mtree (-Qkk)
+// -----------------------------------------------------------------------------
+/// Verifies the given packages (all installed packages when empty), one
+/// `pacman -Qkk` run per package so a `PACKAGE_SCAN_EVENT` can be emitted for
+/// every finding and every finished package.
+#[tauri::command]
+pub async fn verify_package_files(
+    app_handle: AppHandle,
+    package_names: Vec<String>,
+) -> Result<Vec<PackageVerifyResult>, String> {
+    let packages: Vec<String> = if package_names.is_empty() {
+        pacman(&["-Qq"]).await?.lines().map(str::to_string).collect()
+    } else {
+        package_names
+    };
+    let total = packages.len();
+    let mut results = Vec::with_capacity(total);
+
+    for package in packages {
+        let output = Command::new("pacman")
+            .args(["-Qkk", &package])
+            .env("LC_ALL", "C")
+            .output()
+            .await
+            .map_err(|e| format!("Failed to execute pacman: {}", e))?;
+
+        // -Qkk exits non-zero when it finds altered files, so judge by the output instead.
+        // An `error:` is recorded against this package and the scan goes on.
+        let result = parse_verify_output(
+            &package,
+            &String::from_utf8_lossy(&output.stdout),
+            &String::from_utf8_lossy(&output.stderr),
+        );
+        for issue in &result.issues {
+            let _ = app_handle.emit(
+                PACKAGE_SCAN_EVENT,
+                ScanProgress { package: package.clone(), processed: results.len(), total, issue: Some(issue.clone()) },
+            );
+        }
+
+        results.push(result);
+        let _ = app_handle.emit(
+            PACKAGE_SCAN_EVENT,
+            ScanProgress { package, processed: results.len(), total, issue: None },
+        );
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn captured(package: &str) -> PackageVerifyResult {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/qkk");
+        let read = |ext: &str| std::fs::read_to_string(dir.join(format!("{}.{}", package, ext))).unwrap_or_default();
+        parse_verify_output(package, &read("stdout"), &read("stderr"))
+    }
+
+    #[test]
+    fn parses_issue_lines() {
+        let issue = parse_issue("warning: pacman: /etc/makepkg.conf (SHA256 checksum mismatch)").unwrap();
+        assert_eq!((issue.package.as_str(), issue.path.as_str()), ("pacman", "/etc/makepkg.conf"));
+        assert_eq!((issue.kind, issue.detail.as_str()), (VerifyIssueKind::Checksum, "SHA256 checksum mismatch"));
+
+        // Paths may contain parentheses themselves.
+        let odd = parse_issue("warning: foo: /opt/foo (x86)/bin (No such file or directory)").unwrap();
+        assert_eq!((odd.path.as_str(), odd.kind), ("/opt/foo (x86)/bin", VerifyIssueKind::Missing));
+        assert!(parse_issue("warning: go-yq: no mtree file").is_none());
+        assert!(parse_issue("pacman: 322 total files, 2 altered files").is_none());
+    }
+
+    #[test]
+    fn parses_summary_lines() {
+        assert_eq!(parse_summary("pacman: 322 total files, 2 altered files"), Some(("pacman".to_string(), 322, 2)));
+        assert_eq!(parse_summary("filesystem: 1 total file, 0 altered files"), Some(("filesystem".to_string(), 1, 0)));
+        assert_eq!(parse_summary("warning: pacman: /etc/makepkg.conf (Size mismatch)"), None);
+    }
+
+    #[test]
+    fn reads_captured_scan_output() {
+        let pacman = captured("pacman");
+        assert_eq!((pacman.total_files, pacman.altered_files), (322, 2));
+        let kinds: Vec<VerifyIssueKind> = pacman.issues.iter().map(|i| i.kind.clone()).collect();
+        assert_eq!(
+            kinds,
+            [
+                VerifyIssueKind::ModificationTime,
+                VerifyIssueKind::Size,
+                VerifyIssueKind::Checksum,
+                VerifyIssueKind::Missing,
+                VerifyIssueKind::Permissions,
+                VerifyIssueKind::Ownership,
+            ]
+        );
+        assert!(pacman.mtree_available && pacman.errors.is_empty());
+
+        let no_mtree = captured("go-yq");
+        assert!(!no_mtree.mtree_available);
+
+        let ghost = captured("ghost-pkg");
+        assert_eq!(ghost.errors, ["package 'ghost-pkg' was not found"]);
+        assert_eq!(ghost.total_files, 0);
+    }
+}
@@ -10,6 +10,10 @@ use tokio::fs::File;
 use chrono::{DateTime, Utc};
 use futures::future;
 
+use crate::local_package;
+use crate::news::{self, NewsItem};
+use crate::partial_upgrade::{self, GuardVerdict, PartialUpgradeReport};
+
 // -----------------------------------------------------------------------------
 // Configuration
 // -----------------------------------------------------------------------------
@@ -26,6 +30,18 @@ pub struct PacmanResult {
     pub message: String,
     pub operation: String,
     pub package_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<PacmanError>,
+}
+
+/// Typed reasons for refusing an operation before pacman is started.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PacmanError {
+    /// Distro news posted since the last full upgrade that the user hasn't read.
+    UnreadNews { items: Vec<NewsItem> },
+    /// Installing would upgrade shared libraries without a full system upgrade.
+    PartialUpgrade { report: PartialUpgradeReport },
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -59,7 +75,7 @@ pub struct SystemUpdateStatus {
 // -----------------------------------------------------------------------------
 // Helper: emit progress to the frontend
 // -----------------------------------------------------------------------------
-fn emit_progress(handle: &AppHandle, step: &str, detail: &str) {
+pub(crate) fn emit_progress(handle: &AppHandle, step: &str, detail: &str) {
     let progress = PacmanProgress {
         current_step: step.to_string(),
         detail: detail.to_string(),
@@ -71,12 +87,7 @@ fn emit_progress(handle: &AppHandle, step: &str, detail: &str) {
 // -----------------------------------------------------------------------------
 // Helper: run a command with streamed output + timeout
 // -----------------------------------------------------------------------------
-async fn run_command_with_output(
-    program: &str,
-    args: &[&str],
-    app_handle: &AppHandle,
-    op_desc: &str,
-) -> Result<(String, String), String> {
+async fn run_streamed(program: &str, args: &[&str], app_handle: &AppHandle, op_desc: &str) -> Result<std::process::Output, String> {
     let prog = program.to_string();
     let args_str = args.join(" ");
     emit_progress(app_handle, op_desc, &format!("Running: {} {}", prog, args_str));
@@ -117,6 +128,16 @@ async fn run_command_with_output(
         .map_err(|e| e.to_string())?;
 
     let _ = tokio::join!(stdout_task, stderr_task);
+    Ok(output)
+}
+
+pub(crate) async fn run_command_with_output(
+    program: &str,
+    args: &[&str],
+    app_handle: &AppHandle,
+    op_desc: &str,
+) -> Result<(String, String), String> {
+    let output = run_streamed(program, args, app_handle, op_desc).await?;
 
     let stdout = String::from_utf8_lossy(&output.stdout).to_string();
     let stderr = String::from_utf8_lossy(&output.stderr).to_string();
@@ -124,6 +145,16 @@ async fn run_command_with_output(
     Ok((stdout, stderr))
 }
 
+/// Like `run_command_with_output`, but a non-zero exit status is an error.
+pub(crate) async fn run_checked_command(program: &str, args: &[&str], app_handle: &AppHandle, op_desc: &str) -> Result<(), String> {
+    let output = run_streamed(program, args, app_handle, op_desc).await?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!("{} {} exited with {}", program, args.join(" "), output.status))
+    }
+}
+
 // -----------------------------------------------------------------------------
 // Tauri command: install / remove / update (single operation)
 // -----------------------------------------------------------------------------
@@ -132,6 +163,7 @@ pub async fn manage_pacman_package(
     app_handle: AppHandle,
     operation: String,
     package_name: Option<String>,
+    acknowledge_news: Option<bool>,
 ) -> String {
     // Note: The logic for "update" here is now primarily handled by run_system_update,
     // but kept here for backward compatibility/simplicity of single package update if needed.
@@ -146,6 +178,7 @@ pub async fn manage_pacman_package(
                 message: "Package name required for install/remove.".into(),
                 operation,
                 package_name: None,
+                error: None,
             })
             .to_string();
         },
@@ -163,6 +196,24 @@ pub async fn manage_pacman_package(
             vec!["pacman", "-Rns", "--noconfirm", &pkg_owned],
             "Removal",
         ),
+        "install_local" => {
+            // The package name is the path of a downloaded archive.
+            if !local_package::is_package_archive(&pkg_owned) || !std::path::Path::new(&pkg_owned).is_file() {
+                return json!(PacmanResult {
+                    success: false,
+                    message: format!("{} is not a package archive.", pkg_owned),
+                    operation,
+                    package_name: original_pkg,
+                    error: None,
+                })
+                .to_string();
+            }
+            (
+                "pkexec",
+                vec!["pacman", "-U", "--noconfirm", &pkg_owned],
+                "Local Installation",
+            )
+        }
         "update" => ("pkexec", vec!["pacman", "-Syu", "--noconfirm"], "System Update"),
         _ => {
             return json!(PacmanResult {
@@ -170,11 +221,67 @@ pub async fn manage_pacman_package(
                 message: format!("Invalid operation: {}", operation),
                 operation,
                 package_name: original_pkg,
+                error: None,
             })
             .to_string();
         }
     };
 
+    // Refuse a system update while there is unread news the user should act on.
+    if operation == "update" {
+        match news::unread_news_since_last_update(&app_handle).await {
+            Ok(items) if !items.is_empty() => {
+                if acknowledge_news.unwrap_or(false) {
+                    let ids = items.iter().map(|i| i.id.clone());
+                    if let Err(e) = news::mark_read(&app_handle, ids).await {
+                        emit_progress(&app_handle, "NEWS", &format!("Failed to save read news: {}", e));
+                    }
+                } else {
+                    return json!(PacmanResult {
+                        success: false,
+                        message: format!(
+                            "{} unread news item(s) were posted since the last update. Read them before updating.",
+                            items.len()
+                        ),
+                        operation,
+                        package_name: original_pkg,
+                        error: Some(PacmanError::UnreadNews { items }),
+                    })
+                    .to_string();
+                }
+            }
+            Ok(_) => {}
+            // No feed and no cache: don't block updates on a news outage.
+            Err(e) => emit_progress(&app_handle, "NEWS", &format!("Could not check distribution news: {}", e)),
+        }
+    }
+
+    // Refuse installs that would turn into an unsupported partial upgrade.
+    let guard = match operation.as_str() {
+        "install" => Some(partial_upgrade::check_install(&pkg_owned).await),
+        "install_local" => Some(partial_upgrade::check_local_install(&pkg_owned).await),
+        _ => None,
+    };
+    if let Some(guard) = guard {
+        match guard {
+            Ok(report) if report.verdict == GuardVerdict::Block => {
+                return json!(PacmanResult {
+                    success: false,
+                    message: report.explanation.clone(),
+                    operation,
+                    package_name: original_pkg,
+                    error: Some(PacmanError::PartialUpgrade { report }),
+                })
+                .to_string();
+            }
+            Ok(report) if report.verdict == GuardVerdict::Warn => {
+                emit_progress(&app_handle, "PARTIAL_UPGRADE", &report.explanation);
+            }
+            Ok(_) => {}
+            Err(e) => emit_progress(&app_handle, "PARTIAL_UPGRADE", &format!("Could not check for a partial upgrade: {}", e)),
+        }
+    }
+
     emit_progress(&app_handle, op_desc, &format!("Starting {}...", op_desc));
 
     match run_command_with_output(program, &args_vec, &app_handle, op_desc).await {
@@ -186,6 +293,7 @@ pub async fn manage_pacman_package(
                 message: msg,
                 operation,
                 package_name: original_pkg,
+                error: None,
             })
             .to_string()
         }
@@ -196,6 +304,7 @@ pub async fn manage_pacman_package(
                 message: e,
                 operation,
                 package_name: original_pkg,
+                error: None,
             })
             .to_string()
         }
@@ -396,6 +505,49 @@ async fn get_last_update_date(app_handle: &AppHandle) -> Option<String> {
     }
 }
 
+// -----------------------------------------------------------------------------
+// Helper: time of the last full system upgrade (`pacman -Syu`)
+// -----------------------------------------------------------------------------
+fn parse_log_timestamp(line: &str) -> Option<DateTime<Utc>> {
+    // "[2024-03-01T10:15:02+0100] [PACMAN] starting full system upgrade"
+    let end = line.find(']')?;
+    let timestamp_str = line.get(1..end)?;
+    DateTime::parse_from_str(timestamp_str, "%Y-%m-%dT%H:%M:%S%#z")
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Start and end of the last `pacman -Syu` run in `log`: its "starting full
+/// system upgrade" line and the last line logged before the next command.
+fn last_full_upgrade_run(log: &str) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut last = None;
+    let mut in_upgrade = false;
+    for line in log.lines() {
+        if line.contains("[PACMAN] starting full system upgrade") {
+            if let Some(dt) = parse_log_timestamp(line) {
+                last = Some((dt, dt));
+                in_upgrade = true;
+            }
+        } else if line.contains("[PACMAN] Running ") {
+            in_upgrade = false;
+        } else if in_upgrade {
+            if let (Some((_, end)), Some(dt)) = (last.as_mut(), parse_log_timestamp(line)) {
+                *end = dt;
+            }
+        }
+    }
+    last
+}
+
+pub async fn last_full_upgrade_span() -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let log = tokio::fs::read_to_string(PACMAN_LOG_PATH).await.ok()?;
+    last_full_upgrade_run(&log)
+}
+
+pub async fn last_full_upgrade() -> Option<DateTime<Utc>> {
+    last_full_upgrade_span().await.map(|(start, _)| start)
+}
+
 // -----------------------------------------------------------------------------
 // TAURI COMMAND: Check System Updates
 // -----------------------------------------------------------------------------
@@ -460,3 +612,25 @@ pub async fn check_system_updates(app_handle: AppHandle) -> String {
 
     json!(status).to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_upgrade_run_ends_before_the_next_command() {
+        let log = "\
+[2024-03-01T10:15:00+0100] [PACMAN] Running 'pacman -Syu'
+[2024-03-01T10:15:00+0100] [PACMAN] synchronizing package lists
+[2024-03-01T10:15:02+0100] [PACMAN] starting full system upgrade
+[2024-03-01T10:15:40+0100] [ALPM] upgraded glibc (2.39-1 -> 2.39-2)
+[2024-03-01T10:15:41+0100] [ALPM] transaction completed
+[2024-03-01T11:00:00+0100] [PACMAN] Running 'pacman -S htop'
+[2024-03-01T11:00:03+0100] [ALPM] installed htop (3.3.0-3)
+";
+        let (start, end) = last_full_upgrade_run(log).unwrap();
+        assert_eq!(start.to_rfc3339(), "2024-03-01T09:15:02+00:00");
+        assert_eq!(end.to_rfc3339(), "2024-03-01T09:15:41+00:00");
+        assert_eq!(last_full_upgrade_run("[2024-03-01T10:15:00+0100] [PACMAN] Running 'pacman -Sy'\n"), None);
+    }
+}
@@ -0,0 +1,384 @@
+// src/partial_upgrade.rs
+//
+// Guards `pacman -S <pkg>` against partial upgrades: when the sync databases
+// were refreshed after the last full `-Syu`, installing a package may pull in
+// newer libraries than the rest of the system was built against.
+use chrono::{DateTime, SubsecRound, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use tokio::process::Command;
+
+use crate::local_package;
+use crate::pacman_manager::last_full_upgrade_span;
+use crate::sync_db::SYNC_DB_DIR;
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GuardVerdict {
+    /// Sync DBs match the last full upgrade, or nothing installed would change.
+    Ok,
+    /// Installed dependencies would be upgraded, but no shared library changes soname.
+    Warn,
+    /// A dependency upgrade changes a soname other installed packages link against.
+    Block,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SonameChange {
+    pub library: String,
+    pub installed: String,
+    /// `None` when the new version no longer provides the library at all.
+    pub new: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DependencyUpgrade {
+    pub name: String,
+    pub installed_version: String,
+    pub new_version: String,
+    pub soname_changes: Vec<SonameChange>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PartialUpgradeReport {
+    pub package: String,
+    pub last_full_upgrade: Option<String>,
+    pub sync_db_updated: Option<String>,
+    pub sync_db_newer_than_upgrade: bool,
+    pub upgrades: Vec<DependencyUpgrade>,
+    pub verdict: GuardVerdict,
+    pub explanation: String,
+}
+
+// -----------------------------------------------------------------------------
+// Helpers
+// -----------------------------------------------------------------------------
+async fn pacman(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("pacman")
+        .args(args)
+        .env("LC_ALL", "C")
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute pacman: {}", e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(format!("pacman {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
+
+/// Newest modification time of the sync databases, i.e. the last `-Sy`.
+fn sync_db_refreshed(dir: &Path) -> Option<DateTime<Utc>> {
+    std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "db"))
+        .filter_map(|e| e.metadata().ok()?.modified().ok())
+        .max()
+        .map(DateTime::<Utc>::from)
+}
+
+/// Whether the sync databases were refreshed after a full upgrade that ran
+/// until `upgrade_end`. pacman.log only has whole seconds, so the mtime is
+/// truncated to match; a refresh within the `-Syu` run is never newer.
+fn refreshed_after(db: DateTime<Utc>, upgrade_end: DateTime<Utc>) -> bool {
+    db.trunc_subsecs(0) > upgrade_end
+}
+
+/// Parses `pacman -Qi`/`-Si` output into name -> "Provides" entries.
+/// The first block wins when a package shows up in several repos.
+fn parse_provides(info: &str) -> HashMap<String, Vec<String>> {
+    let mut result = HashMap::new();
+    let mut name: Option<String> = None;
+    let mut provides: Vec<String> = Vec::new();
+    let mut in_provides = false;
+
+    let mut flush = |name: &mut Option<String>, provides: &mut Vec<String>| {
+        if let Some(n) = name.take() {
+            result.entry(n).or_insert_with(|| std::mem::take(provides));
+        }
+        provides.clear();
+    };
+
+    for line in info.lines() {
+        if line.trim().is_empty() {
+            flush(&mut name, &mut provides);
+            in_provides = false;
+            continue;
+        }
+        // Continuation lines of a wrapped field are indented.
+        if line.starts_with(' ') {
+            if in_provides {
+                provides.extend(line.split_whitespace().map(str::to_string));
+            }
+            continue;
+        }
+        let (key, value) = match line.split_once(':') {
+            Some((k, v)) => (k.trim(), v.trim()),
+            None => continue,
+        };
+        in_provides = key == "Provides";
+        match key {
+            "Name" => name = Some(value.to_string()),
+            "Provides" if value != "None" => provides.extend(value.split_whitespace().map(str::to_string)),
+            _ => {}
+        }
+    }
+    flush(&mut name, &mut provides);
+    result
+}
+
+/// Soname provides look like `libicuuc.so=74-64`.
+fn sonames(provides: &[String]) -> HashMap<&str, &str> {
+    provides
+        .iter()
+        .filter_map(|p| p.split_once('='))
+        .filter(|(lib, _)| lib.contains(".so"))
+        .collect()
+}
+
+fn soname_changes(installed: &[String], new: &[String]) -> Vec<SonameChange> {
+    let new_sonames = sonames(new);
+    let mut changes: Vec<SonameChange> = sonames(installed)
+        .into_iter()
+        .filter(|(lib, ver)| new_sonames.get(lib) != Some(ver))
+        .map(|(lib, ver)| SonameChange {
+            library: lib.to_string(),
+            installed: ver.to_string(),
+            new: new_sonames.get(lib).map(|v| v.to_string()),
+        })
+        .collect();
+    changes.sort_by(|a, b| a.library.cmp(&b.library));
+    changes
+}
+
+// -----------------------------------------------------------------------------
+// Guard
+// -----------------------------------------------------------------------------
+/// Entries of `depends` that no installed package satisfies (`pacman -T`);
+/// these are what `pacman -U` pulls from the repositories.
+async fn unsatisfied(depends: &[String]) -> Result<Vec<String>, String> {
+    if depends.is_empty() {
+        return Ok(Vec::new());
+    }
+    let output = Command::new("pacman")
+        .arg("-T")
+        .args(depends)
+        .env("LC_ALL", "C")
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute pacman: {}", e))?;
+    // Exit status 127 only means some dependencies are missing.
+    match output.status.code() {
+        Some(0) | Some(127) => Ok(String::from_utf8_lossy(&output.stdout).lines().map(str::to_string).collect()),
+        _ => Err(format!("pacman -T failed: {}", String::from_utf8_lossy(&output.stderr).trim())),
+    }
+}
+
+pub async fn check_install(package: &str) -> Result<PartialUpgradeReport, String> {
+    check_targets(package, &[package.to_string()]).await
+}
+
+/// Runs `check_install` for each package of a transaction and returns the
+/// first report that blocks it.
+pub async fn first_blocked(packages: &[String]) -> Result<Option<PartialUpgradeReport>, String> {
+    for package in packages {
+        let report = check_install(package).await?;
+        if report.verdict == GuardVerdict::Block {
+            return Ok(Some(report));
+        }
+    }
+    Ok(None)
+}
+
+/// The guard for `pacman -U <archive>`: only dependencies missing from the
+/// system come from the repositories, so those are what is checked.
+pub async fn check_local_install(archive: &str) -> Result<PartialUpgradeReport, String> {
+    let path = std::path::PathBuf::from(archive);
+    let depends = tokio::task::spawn_blocking(move || local_package::archive_depends(&path))
+        .await
+        .map_err(|e| e.to_string())??;
+    let missing = unsatisfied(&depends).await?;
+    check_targets(archive, &missing).await
+}
+
+/// Checks what installing `targets` from the sync databases would upgrade;
+/// `package` names the install in the report.
+async fn check_targets(package: &str, targets: &[String]) -> Result<PartialUpgradeReport, String> {
+    let last_upgrade = last_full_upgrade_span().await;
+    let db_refreshed = sync_db_refreshed(Path::new(SYNC_DB_DIR));
+    let db_newer = match (db_refreshed, last_upgrade) {
+        (Some(db), Some((_, upgrade_end))) => refreshed_after(db, upgrade_end),
+        // The DB was synced but there is no record of a full upgrade.
+        (Some(_), None) => true,
+        (None, _) => false,
+    };
+
+    let mut report = PartialUpgradeReport {
+        package: package.to_string(),
+        last_full_upgrade: last_upgrade.map(|(start, _)| start.to_rfc3339()),
+        sync_db_updated: db_refreshed.map(|d| d.to_rfc3339()),
+        sync_db_newer_than_upgrade: db_newer,
+        upgrades: Vec::new(),
+        verdict: GuardVerdict::Ok,
+        explanation: "Sync databases match the last full system upgrade.".into(),
+    };
+
+    if !db_newer {
+        return Ok(report);
+    }
+
+    if targets.is_empty() {
+        report.explanation = "Sync databases are newer than the last full upgrade, but no installed package would change.".into();
+        return Ok(report);
+    }
+
+    // Everything pacman would install for these targets, dependencies included.
+    let mut sp_args = vec!["-Sp", "--print-format", "%n %v"];
+    sp_args.extend(targets.iter().map(String::as_str));
+    let targets = pacman(&sp_args).await?;
+    let installed_list = pacman(&["-Q"]).await?;
+    let installed: HashMap<&str, &str> = installed_list
+        .lines()
+        .filter_map(|l| l.split_once(' '))
+        .collect();
+
+    let mut upgrades: Vec<(String, String, String)> = Vec::new();
+    for line in targets.lines() {
+        if let Some((name, new_version)) = line.split_once(' ') {
+            if let Some(installed_version) = installed.get(name) {
+                if *installed_version != new_version {
+                    upgrades.push((name.to_string(), installed_version.to_string(), new_version.to_string()));
+                }
+            }
+        }
+    }
+
+    if upgrades.is_empty() {
+        report.explanation = "Sync databases are newer than the last full upgrade, but no installed package would change.".into();
+        return Ok(report);
+    }
+
+    let names: Vec<&str> = upgrades.iter().map(|(n, _, _)| n.as_str()).collect();
+    let mut qi_args = vec!["-Qi"];
+    qi_args.extend(&names);
+    let mut si_args = vec!["-Si"];
+    si_args.extend(&names);
+    let local_provides = parse_provides(&pacman(&qi_args).await?);
+    let sync_provides = parse_provides(&pacman(&si_args).await?);
+
+    let empty = Vec::new();
+    report.upgrades = upgrades
+        .into_iter()
+        .map(|(name, installed_version, new_version)| {
+            let soname_changes = soname_changes(
+                local_provides.get(&name).unwrap_or(&empty),
+                sync_provides.get(&name).unwrap_or(&empty),
+            );
+            DependencyUpgrade { name, installed_version, new_version, soname_changes }
+        })
+        .collect();
+
+    let bumped: Vec<&str> = report
+        .upgrades
+        .iter()
+        .filter(|u| !u.soname_changes.is_empty())
+        .map(|u| u.name.as_str())
+        .collect();
+
+    if bumped.is_empty() {
+        report.verdict = GuardVerdict::Warn;
+        report.explanation = format!(
+            "Installing {} would upgrade {} installed package(s) without a full system upgrade. Run a system update first.",
+            package,
+            report.upgrades.len()
+        );
+    } else {
+        report.verdict = GuardVerdict::Block;
+        report.explanation = format!(
+            "Installing {} would upgrade {} to new shared library versions that the rest of the system was not built against. Run a full system update first.",
+            package,
+            bumped.join(", ")
+        );
+    }
+
+    Ok(report)
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: preview the guard for a package
+// -----------------------------------------------------------------------------
+#[tauri::command]
+pub async fn check_partial_upgrade(package_name: String) -> Result<PartialUpgradeReport, String> {
+    check_install(&package_name).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn provides_follow_wrapped_lines_and_first_repo_wins() {
+        let info = "\
+Repository      : core
+Name            : icu
+Version         : 75.1-1
+Provides        : libicudata.so=75-64  libicui18n.so=75-64
+                  libicuuc.so=75-64
+Depends On      : gcc-libs  sh
+
+Repository      : core-testing
+Name            : icu
+Provides        : libicuuc.so=76-64
+
+Name            : bash
+Provides        : sh
+Depends On      : readline
+
+Name            : nano
+Provides        : None
+";
+        let provides = parse_provides(info);
+        assert_eq!(provides["icu"], strings(&["libicudata.so=75-64", "libicui18n.so=75-64", "libicuuc.so=75-64"]));
+        assert_eq!(provides["bash"], strings(&["sh"]));
+        assert!(provides["nano"].is_empty());
+    }
+
+    #[test]
+    fn soname_changes_ignore_plain_provides() {
+        let installed = strings(&["libicuuc.so=74-64", "libicudata.so=74-64", "libfoo.so=1-64", "sh"]);
+        let new = strings(&["libicuuc.so=75-64", "libicudata.so=75-64", "libfoo.so=1-64", "bash"]);
+        let changes: Vec<(String, String, Option<String>)> =
+            soname_changes(&installed, &new).into_iter().map(|c| (c.library, c.installed, c.new)).collect();
+        assert_eq!(
+            changes,
+            [
+                ("libicudata.so".to_string(), "74-64".to_string(), Some("75-64".to_string())),
+                ("libicuuc.so".to_string(), "74-64".to_string(), Some("75-64".to_string())),
+            ]
+        );
+
+        let dropped = soname_changes(&strings(&["libold.so=2-64"]), &[]);
+        assert_eq!((dropped[0].library.as_str(), dropped[0].new.as_deref()), ("libold.so", None));
+        assert!(soname_changes(&installed, &installed).is_empty());
+    }
+
+    #[test]
+    fn refresh_is_compared_at_log_precision() {
+        let end = DateTime::parse_from_rfc3339("2024-03-01T09:15:41Z").unwrap().with_timezone(&Utc);
+        // Synced within the last logged second of the upgrade.
+        let same_second = end + chrono::Duration::milliseconds(700);
+        assert!(!refreshed_after(same_second, end));
+        assert!(!refreshed_after(end - chrono::Duration::seconds(39), end));
+        assert!(refreshed_after(end + chrono::Duration::seconds(1), end));
+    }
+}
@@ -0,0 +1,217 @@
+// src/pci.rs
+//
+// PCI devices read straight from sysfs, with names resolved from pci.ids.
+// The sysfs root is a parameter so fixture trees can stand in for /sys.
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::hwids::IdDatabase;
+
+// -----------------------------------------------------------------------------
+// Configuration
+// -----------------------------------------------------------------------------
+pub const SYSFS_ROOT: &str = "/sys";
+pub const PROC_ROOT: &str = "/proc";
+/// hwdata on Arch, pciutils on Debian-based systems.
+pub const PCI_IDS: [&str; 3] = ["/usr/share/hwdata/pci.ids", "/usr/share/misc/pci.ids", "/usr/share/pci.ids"];
+
+pub const CLASS_NETWORK: u8 = 0x02;
+pub const CLASS_DISPLAY: u8 = 0x03;
+pub const CLASS_MULTIMEDIA: u8 = 0x04;
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct PciDevice {
+    /// Domain:bus:device.function, e.g. "0000:01:00.0".
+    pub address: String,
+    /// Class, subclass and programming interface, e.g. "030000".
+    pub class: String,
+    pub class_name: Option<String>,
+    /// Lower-case hex IDs, e.g. "10de".
+    pub vendor_id: String,
+    pub device_id: String,
+    pub subsystem_vendor_id: Option<String>,
+    pub subsystem_device_id: Option<String>,
+    pub revision: Option<String>,
+    pub vendor_name: Option<String>,
+    pub device_name: Option<String>,
+    pub subsystem_name: Option<String>,
+    /// Bound kernel driver; None if no driver has claimed the device.
+    pub driver: Option<String>,
+    pub modalias: Option<String>,
+    /// Set by firmware on the GPU used for the boot console.
+    pub boot_vga: bool,
+}
+
+impl PciDevice {
+    pub fn class_code(&self) -> u8 {
+        u8::from_str_radix(self.class.get(0..2).unwrap_or(""), 16).unwrap_or(0)
+    }
+
+    pub fn is_gpu(&self) -> bool {
+        self.class_code() == CLASS_DISPLAY
+    }
+
+    pub fn is_network(&self) -> bool {
+        self.class_code() == CLASS_NETWORK
+    }
+
+    /// Audio devices (0401) and HD Audio controllers (0403).
+    pub fn is_audio(&self) -> bool {
+        self.class_code() == CLASS_MULTIMEDIA && matches!(self.class.get(2..4), Some("01" | "03"))
+    }
+
+    /// "vendor:device", as lspci -nn prints it.
+    pub fn id(&self) -> String {
+        format!("{}:{}", self.vendor_id, self.device_id)
+    }
+
+    pub fn model(&self) -> String {
+        self.device_name.clone().unwrap_or_else(|| format!("Device {}", self.device_id))
+    }
+
+    /// A line in the style of `lspci -nn`.
+    pub fn describe(&self) -> String {
+        format!(
+            "{} {} [{}]: {} {} [{}]",
+            self.address.strip_prefix("0000:").unwrap_or(&self.address),
+            self.class_name.as_deref().unwrap_or("Unclassified device"),
+            self.class.get(0..4).unwrap_or(&self.class),
+            self.vendor_name.as_deref().unwrap_or("Unknown vendor"),
+            self.model(),
+            self.id()
+        )
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Helpers
+// -----------------------------------------------------------------------------
+/// Reads a "0x10de"-style attribute as bare lower-case hex.
+fn read_hex(dir: &Path, name: &str) -> Option<String> {
+    read_attr(dir, name).map(|v| v.trim_start_matches("0x").to_lowercase())
+}
+
+fn hex_u16(value: &str) -> Option<u16> {
+    u16::from_str_radix(value, 16).ok()
+}
+
+/// A sysfs attribute, trimmed; `None` when it is missing or empty.
+pub fn read_attr(dir: &Path, name: &str) -> Option<String> {
+    std::fs::read_to_string(dir.join(name)).ok().map(|s| s.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+/// Basename of a sysfs link such as `driver` or `subsystem`.
+pub fn link_name(path: &Path) -> Option<String> {
+    std::fs::read_link(path).ok()?.file_name().map(|n| n.to_string_lossy().to_string())
+}
+
+pub fn read_device(dir: &Path, ids: &IdDatabase) -> Option<PciDevice> {
+    let address = dir.file_name()?.to_string_lossy().to_string();
+    let class = read_hex(dir, "class")?;
+    let vendor_id = read_hex(dir, "vendor")?;
+    let device_id = read_hex(dir, "device")?;
+    let subsystem_vendor_id = read_hex(dir, "subsystem_vendor");
+    let subsystem_device_id = read_hex(dir, "subsystem_device");
+
+    let class_byte = |i: usize| class.get(i..i + 2).and_then(|b| u8::from_str_radix(b, 16).ok()).unwrap_or(0);
+    let (vendor, device) = (hex_u16(&vendor_id), hex_u16(&device_id));
+    let subsystem_key = subsystem_vendor_id.as_ref().zip(subsystem_device_id.as_ref()).map(|(v, d)| format!("{} {}", v, d));
+
+    Some(PciDevice {
+        class_name: ids.class(class_byte(0), class_byte(2)).map(str::to_string),
+        vendor_name: vendor.and_then(|v| ids.vendor(v)).map(str::to_string),
+        device_name: vendor.zip(device).and_then(|(v, d)| ids.device(v, d)).map(str::to_string),
+        subsystem_name: vendor
+            .zip(device)
+            .zip(subsystem_key)
+            .and_then(|((v, d), key)| ids.subdevice(v, d, &key))
+            .map(str::to_string),
+        revision: read_hex(dir, "revision"),
+        driver: link_name(&dir.join("driver")),
+        modalias: read_attr(dir, "modalias"),
+        boot_vga: read_attr(dir, "boot_vga").as_deref() == Some("1"),
+        address,
+        class,
+        vendor_id,
+        device_id,
+        subsystem_vendor_id,
+        subsystem_device_id,
+    })
+}
+
+/// All devices under `<sysfs_root>/bus/pci/devices`, sorted by address.
+pub fn enumerate(sysfs_root: &Path, ids: &IdDatabase) -> Vec<PciDevice> {
+    let Ok(entries) = std::fs::read_dir(sysfs_root.join("bus/pci/devices")) else { return Vec::new() };
+    let mut devices: Vec<PciDevice> = entries.filter_map(|e| e.ok()).filter_map(|e| read_device(&e.path(), ids)).collect();
+    devices.sort_by(|a, b| a.address.cmp(&b.address));
+    devices
+}
+
+/// The fixture tree standing in for SYSFS_ROOT in tests.
+#[cfg(test)]
+pub(crate) fn fixture_root() -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sysfs")
+}
+
+/// The fixture tree standing in for PROC_ROOT in tests.
+#[cfg(test)]
+pub(crate) fn proc_fixture_root() -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/proc")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PCI_IDS_FIXTURE: &str = include_str!("../tests/fixtures/pci.ids");
+
+    fn devices() -> Vec<PciDevice> {
+        enumerate(&fixture_root(), &IdDatabase::parse(PCI_IDS_FIXTURE))
+    }
+
+    #[test]
+    fn enumerates_fixture_tree_in_address_order() {
+        let addresses: Vec<String> = devices().into_iter().map(|d| d.address).collect();
+        assert_eq!(addresses, ["0000:00:02.0", "0000:00:14.3", "0000:00:1f.3", "0000:01:00.0"]);
+    }
+
+    #[test]
+    fn resolves_names_from_pci_ids() {
+        let devices = devices();
+        let igpu = &devices[0];
+        assert_eq!(igpu.vendor_name.as_deref(), Some("Intel Corporation"));
+        assert_eq!(igpu.device_name.as_deref(), Some("CoffeeLake-H GT2 [UHD Graphics 630]"));
+        assert_eq!(igpu.class_name.as_deref(), Some("VGA compatible controller"));
+        assert_eq!(igpu.subsystem_name.as_deref(), Some("XPS 15 9570"));
+        assert_eq!(igpu.driver.as_deref(), Some("i915"));
+        assert!(igpu.boot_vga);
+        assert!(igpu.is_gpu());
+
+        let wifi = &devices[1];
+        assert!(wifi.is_network());
+        assert_eq!(wifi.id(), "8086:a370");
+    }
+
+    #[test]
+    fn keeps_devices_without_a_driver() {
+        let dgpu = devices().into_iter().find(|d| d.vendor_id == "10de").expect("NVIDIA GPU present");
+        assert_eq!(dgpu.driver, None);
+        assert_eq!(dgpu.class_name.as_deref(), Some("3D controller"));
+        assert_eq!(dgpu.model(), "GP107M [GeForce GTX 1050 Mobile]");
+        assert!(!dgpu.boot_vga);
+        assert_eq!(
+            dgpu.describe(),
+            "01:00.0 3D controller [0302]: NVIDIA Corporation GP107M [GeForce GTX 1050 Mobile] [10de:1c8d]"
+        );
+    }
+
+    #[test]
+    fn unknown_ids_fall_back_to_numbers() {
+        let devices = enumerate(&fixture_root(), &IdDatabase::default());
+        assert_eq!(devices[3].vendor_name, None);
+        assert_eq!(devices[3].model(), "Device 1c8d");
+    }
+}
@@ -0,0 +1,238 @@
+// src/platform.rs
+//
+// The machine itself: DMI identification, firmware boot mode, Secure Boot,
+// TPM and virtualization. Everything is read from sysfs without root; the
+// DMI serial numbers and UUID are root-only and left out.
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::pci::read_attr;
+
+// -----------------------------------------------------------------------------
+// Configuration
+// -----------------------------------------------------------------------------
+/// EFI global variable vendor GUID.
+const EFI_GLOBAL_GUID: &str = "8be4df61-93ca-11d2-aa0d-00e098032b8c";
+
+/// Values vendors leave in DMI fields they didn't fill in.
+const DMI_PLACEHOLDERS: [&str; 9] = [
+    "To Be Filled By O.E.M.",
+    "To be filled by O.E.M.",
+    "Default string",
+    "System Product Name",
+    "System manufacturer",
+    "System Version",
+    "Not Specified",
+    "Not Applicable",
+    "None",
+];
+
+/// DMI substrings that identify a hypervisor, with the systemd-detect-virt
+/// name for it. Checked in order against vendor, product, board and BIOS.
+const DMI_HYPERVISORS: [(&str, &str); 16] = [
+    ("KVM", "kvm"),
+    ("OpenStack", "kvm"),
+    ("KubeVirt", "kvm"),
+    ("Amazon EC2", "amazon"),
+    ("QEMU", "qemu"),
+    ("VMware", "vmware"),
+    ("VMW", "vmware"),
+    ("innotek GmbH", "oracle"),
+    ("VirtualBox", "oracle"),
+    ("Oracle Corporation", "oracle"),
+    ("Xen", "xen"),
+    ("Bochs", "bochs"),
+    ("Parallels", "parallels"),
+    ("BHYVE", "bhyve"),
+    ("Apple Virtualization", "apple"),
+    ("Google Compute Engine", "google"),
+];
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct TpmInfo {
+    /// e.g. "tpm0"
+    pub device: String,
+    /// "2.0" or "1.2".
+    pub version: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct PlatformInfo {
+    pub system_vendor: Option<String>,
+    pub product_name: Option<String>,
+    pub product_version: Option<String>,
+    pub product_family: Option<String>,
+    pub board_vendor: Option<String>,
+    pub board_name: Option<String>,
+    pub board_version: Option<String>,
+    /// e.g. "Laptop", from the SMBIOS chassis type.
+    pub chassis: Option<String>,
+    pub bios_vendor: Option<String>,
+    pub bios_version: Option<String>,
+    /// As the firmware reports it, usually MM/DD/YYYY.
+    pub bios_date: Option<String>,
+    /// "uefi" or "bios".
+    pub boot_mode: String,
+    /// 64 or 32 for UEFI; a 32-bit UEFI needs a 32-bit boot loader.
+    pub efi_platform_size: Option<u32>,
+    /// None when it can't be read, e.g. when booted in BIOS mode.
+    pub secure_boot: Option<bool>,
+    /// Firmware accepts new Secure Boot keys without authentication.
+    pub setup_mode: Option<bool>,
+    pub tpm: Option<TpmInfo>,
+    /// systemd-detect-virt name of the hypervisor, e.g. "kvm"; None on bare metal.
+    pub virtualization: Option<String>,
+}
+
+// -----------------------------------------------------------------------------
+// Helpers
+// -----------------------------------------------------------------------------
+fn read_dmi(dir: &Path, name: &str) -> Option<String> {
+    read_attr(dir, name).filter(|v| !DMI_PLACEHOLDERS.contains(&v.as_str()))
+}
+
+fn chassis_name(code: &str) -> Option<&'static str> {
+    Some(match code.parse::<u8>().ok()? {
+        3 | 4 | 5 | 6 | 7 | 15 | 16 => "Desktop",
+        8 | 9 | 10 | 14 => "Laptop",
+        11 => "Handheld",
+        13 => "All-in-one",
+        17 | 23 | 28 | 29 => "Server",
+        30 => "Tablet",
+        31 => "Convertible",
+        32 => "Detachable",
+        35 | 36 => "Mini PC",
+        _ => return None,
+    })
+}
+
+/// A boolean EFI variable: four attribute bytes followed by the value.
+fn read_efi_flag(sysfs_root: &Path, name: &str) -> Option<bool> {
+    let path = sysfs_root.join("firmware/efi/efivars").join(format!("{}-{}", name, EFI_GLOBAL_GUID));
+    std::fs::read(path).ok()?.get(4).map(|v| *v == 1)
+}
+
+fn read_tpm(sysfs_root: &Path) -> Option<TpmInfo> {
+    let dir = sysfs_root.join("class/tpm/tpm0");
+    if !dir.exists() {
+        return None;
+    }
+    // tpm_version_major appeared in 5.6; TPM 1.2 chips also expose "caps".
+    let version = match read_attr(&dir, "tpm_version_major").as_deref() {
+        Some("2") => Some("2.0".to_string()),
+        Some("1") => Some("1.2".to_string()),
+        _ if dir.join("caps").exists() || dir.join("device/caps").exists() => Some("1.2".to_string()),
+        _ => None,
+    };
+    Some(TpmInfo { device: "tpm0".to_string(), version })
+}
+
+/// The hypervisor we run under, as systemd-detect-virt names it: DMI strings
+/// first, then the Xen hypervisor node, then the CPU's hypervisor flag.
+pub fn detect_virtualization(dmi: &[Option<&str>], hypervisor_type: Option<&str>, cpuinfo: &str) -> Option<String> {
+    let dmi: Vec<&str> = dmi.iter().flatten().copied().collect();
+    if let Some((_, name)) = DMI_HYPERVISORS.iter().find(|(needle, _)| dmi.iter().any(|v| v.contains(needle))) {
+        return Some(name.to_string());
+    }
+    // Surface devices share the vendor string; only the product says VM.
+    if dmi.contains(&"Microsoft Corporation") && dmi.contains(&"Virtual Machine") {
+        return Some("microsoft".to_string());
+    }
+    if hypervisor_type == Some("xen") {
+        return Some("xen".to_string());
+    }
+    let flagged = cpuinfo
+        .lines()
+        .filter(|l| l.starts_with("flags"))
+        .any(|l| l.split_whitespace().any(|f| f == "hypervisor"));
+    flagged.then(|| "vm-other".to_string())
+}
+
+/// Platform details from `sysfs_root` and the contents of /proc/cpuinfo.
+pub fn read(sysfs_root: &Path, cpuinfo: &str) -> PlatformInfo {
+    let dmi = sysfs_root.join("class/dmi/id");
+    let efi = sysfs_root.join("firmware/efi");
+    let uefi = efi.exists();
+
+    let mut info = PlatformInfo {
+        system_vendor: read_dmi(&dmi, "sys_vendor"),
+        product_name: read_dmi(&dmi, "product_name"),
+        product_version: read_dmi(&dmi, "product_version"),
+        product_family: read_dmi(&dmi, "product_family"),
+        board_vendor: read_dmi(&dmi, "board_vendor"),
+        board_name: read_dmi(&dmi, "board_name"),
+        board_version: read_dmi(&dmi, "board_version"),
+        chassis: read_attr(&dmi, "chassis_type").as_deref().and_then(chassis_name).map(str::to_string),
+        bios_vendor: read_dmi(&dmi, "bios_vendor"),
+        bios_version: read_dmi(&dmi, "bios_version"),
+        bios_date: read_dmi(&dmi, "bios_date"),
+        boot_mode: if uefi { "uefi" } else { "bios" }.to_string(),
+        efi_platform_size: read_attr(&efi, "fw_platform_size").and_then(|s| s.parse().ok()),
+        secure_boot: if uefi { read_efi_flag(sysfs_root, "SecureBoot") } else { None },
+        setup_mode: if uefi { read_efi_flag(sysfs_root, "SetupMode") } else { None },
+        tpm: read_tpm(sysfs_root),
+        virtualization: None,
+    };
+    info.virtualization = detect_virtualization(
+        &[
+            info.system_vendor.as_deref(),
+            info.product_name.as_deref(),
+            info.board_vendor.as_deref(),
+            info.bios_vendor.as_deref(),
+            info.bios_version.as_deref(),
+        ],
+        read_attr(sysfs_root, "hypervisor/type").as_deref(),
+        cpuinfo,
+    );
+    info
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci::fixture_root;
+
+    #[test]
+    fn reads_fixture_laptop() {
+        let root = fixture_root();
+        let info = read(&root, "flags\t\t: fpu vme de pse tsc msr\n");
+
+        assert_eq!(info.system_vendor.as_deref(), Some("Dell Inc."));
+        assert_eq!(info.product_name.as_deref(), Some("XPS 15 9570"));
+        assert_eq!(info.product_version, None, "placeholder values are dropped");
+        assert_eq!(info.chassis.as_deref(), Some("Laptop"));
+        assert_eq!(info.bios_version.as_deref(), Some("1.39.0"));
+        assert_eq!(info.bios_date.as_deref(), Some("07/04/2025"));
+        assert_eq!(info.boot_mode, "uefi");
+        assert_eq!(info.efi_platform_size, Some(64));
+        assert_eq!(info.secure_boot, Some(true));
+        assert_eq!(info.setup_mode, Some(false));
+        assert_eq!(info.tpm.and_then(|t| t.version).as_deref(), Some("2.0"));
+        assert_eq!(info.virtualization, None);
+    }
+
+    #[test]
+    fn missing_firmware_means_bios_boot() {
+        let info = read(Path::new("/nonexistent"), "");
+        assert_eq!(info.boot_mode, "bios");
+        assert_eq!(info.secure_boot, None);
+        assert!(info.tpm.is_none());
+    }
+
+    #[test]
+    fn hypervisors_are_recognised() {
+        assert_eq!(detect_virtualization(&[Some("QEMU"), Some("Standard PC (Q35 + ICH9, 2009)")], None, "").as_deref(), Some("qemu"));
+        assert_eq!(detect_virtualization(&[Some("innotek GmbH"), Some("VirtualBox")], None, "").as_deref(), Some("oracle"));
+        assert_eq!(detect_virtualization(&[Some("VMware, Inc."), Some("VMware20,1")], None, "").as_deref(), Some("vmware"));
+        assert_eq!(
+            detect_virtualization(&[Some("Microsoft Corporation"), Some("Virtual Machine")], None, "").as_deref(),
+            Some("microsoft")
+        );
+        assert_eq!(detect_virtualization(&[Some("Microsoft Corporation"), Some("Surface Laptop 5")], None, ""), None);
+        assert_eq!(detect_virtualization(&[None], Some("xen"), "").as_deref(), Some("xen"));
+        assert_eq!(detect_virtualization(&[None], None, "flags\t: fpu hypervisor lahf_lm\n").as_deref(), Some("vm-other"));
+    }
+}
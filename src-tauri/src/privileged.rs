@@ -0,0 +1,162 @@
+// src/privileged.rs
+//
+// The one way the backend changes root-owned state: a pkexec'd `sh` script
+// whose file contents and arguments are passed as positional parameters, so
+// nothing user-supplied is ever interpolated into the script text. Writes and
+// follow-up commands are batched so the user sees a single password prompt.
+// File contents are staged in a private 0700 directory, each file created
+// exclusively, so no other user can swap them before root installs them.
+use std::fs::Permissions;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+/// A file to install with root ownership.
+#[derive(Clone)]
+pub struct RootWrite {
+    pub path: String,
+    pub contents: String,
+    pub mode: &'static str,
+}
+
+impl RootWrite {
+    pub fn config(path: impl Into<String>, contents: impl Into<String>) -> Self {
+        RootWrite { path: path.into(), contents: contents.into(), mode: "644" }
+    }
+}
+
+/// One step of a privileged script, for callers that need writes and
+/// commands interleaved.
+pub enum RootStep {
+    Write(RootWrite),
+    Run(Vec<String>),
+}
+
+// -----------------------------------------------------------------------------
+// Helpers
+// -----------------------------------------------------------------------------
+/// A fresh directory only we can read, removed when dropped.
+fn staging_dir() -> Result<TempDir, String> {
+    tempfile::Builder::new()
+        .prefix("linuxhub-")
+        .permissions(Permissions::from_mode(0o700))
+        .tempdir()
+        .map_err(|e| format!("Failed to create staging directory: {}", e))
+}
+
+/// Creates `<dir>/<index>` (failing if it exists) with `contents`.
+async fn stage(dir: &Path, index: usize, contents: &str) -> std::io::Result<PathBuf> {
+    let path = dir.join(index.to_string());
+    let mut file = tokio::fs::OpenOptions::new().write(true).create_new(true).mode(0o600).open(&path).await?;
+    file.write_all(contents.as_bytes()).await?;
+    file.flush().await?;
+    Ok(path)
+}
+
+pub fn describe_failure(status: std::process::ExitStatus, stderr: &str) -> String {
+    match status.code() {
+        // pkexec: 126 = dialog dismissed, 127 = not authorized.
+        Some(126) | Some(127) => "Root permission denied or cancelled by user.".to_string(),
+        code => format!(
+            "Privileged operation failed (exit code {}): {}",
+            code.unwrap_or(-1),
+            stderr.trim()
+        ),
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Privileged execution
+// -----------------------------------------------------------------------------
+/// Installs `writes` (keeping a `~` backup of replaced files) and then runs
+/// `commands` in order, stopping at the first failure. Returns stdout.
+pub async fn apply_as_root(writes: &[RootWrite], commands: &[Vec<String>]) -> Result<String, String> {
+    let mut steps: Vec<RootStep> = writes.iter().cloned().map(RootStep::Write).collect();
+    steps.extend(commands.iter().cloned().map(RootStep::Run));
+    run_steps_as_root(&steps).await
+}
+
+/// Runs `steps` in order in one privileged script, stopping at the first
+/// failure; a write after a failed command never happens. Returns stdout.
+pub async fn run_steps_as_root(steps: &[RootStep]) -> Result<String, String> {
+    let mut script = Vec::new();
+    let mut args: Vec<String> = Vec::new();
+    let staging = staging_dir()?;
+
+    for (i, step) in steps.iter().enumerate() {
+        match step {
+            RootStep::Write(write) => {
+                let tmp = stage(staging.path(), i, &write.contents)
+                    .await
+                    .map_err(|e| format!("Failed to stage {}: {}", write.path, e))?;
+                args.push(tmp.to_string_lossy().to_string());
+                args.push(write.path.clone());
+                let (src, dst) = (args.len() - 1, args.len());
+                script.push(format!("install -D -b -m {} \"${{{}}}\" \"${{{}}}\"", write.mode, src, dst));
+            }
+            RootStep::Run(command) => {
+                let mut line = Vec::new();
+                for arg in command {
+                    args.push(arg.clone());
+                    line.push(format!("\"${{{}}}\"", args.len()));
+                }
+                script.push(line.join(" "));
+            }
+        }
+    }
+
+    let output = Command::new("pkexec")
+        .args(["sh", "-c", &format!("set -e\n{}", script.join("\n")), "linuxhub"])
+        .args(&args)
+        .env("LC_ALL", "C")
+        .output()
+        .await
+        .map_err(|e| format!("Failed to spawn pkexec process: {}", e));
+    drop(staging);
+
+    let output = output?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(describe_failure(output.status, &String::from_utf8_lossy(&output.stderr)))
+    }
+}
+
+/// Runs a single command as root.
+pub async fn run_as_root(program: &str, args: &[&str]) -> Result<String, String> {
+    let mut command = vec![program.to_string()];
+    command.extend(args.iter().map(|a| a.to_string()));
+    apply_as_root(&[], &[command]).await
+}
+
+/// A pkexec'd command for callers that stream its output themselves.
+pub fn command_as_root(program: &str, args: &[&str]) -> Command {
+    let mut command = Command::new("pkexec");
+    command.arg(program).args(args).env("LC_ALL", "C");
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn staged_files_are_private_and_exclusive() {
+        let dir = staging_dir().unwrap();
+        assert_eq!(std::fs::metadata(dir.path()).unwrap().permissions().mode() & 0o777, 0o700);
+
+        let path = stage(dir.path(), 0, "options nvidia-drm modeset=1\n").await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "options nvidia-drm modeset=1\n");
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        assert!(stage(dir.path(), 0, "replaced").await.is_err(), "an existing file is never reused");
+
+        // Two operations at once get separate directories.
+        let other = staging_dir().unwrap();
+        assert_ne!(dir.path(), other.path());
+    }
+}
@@ -0,0 +1,166 @@
+// src/sync_db.rs
+//
+// Package entries read straight from pacman's sync databases. `pacman -Si`
+// leaves out fields such as `%BASE%`, and calling it once per candidate is
+// slow, so the `desc` files are read from the database archives instead.
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+// -----------------------------------------------------------------------------
+// Configuration
+// -----------------------------------------------------------------------------
+pub const SYNC_DB_DIR: &str = "/var/lib/pacman/sync";
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SyncPackage {
+    /// Database the entry came from, e.g. "core".
+    pub repo: String,
+    pub name: String,
+    /// pkgbase; the package name when the database doesn't record one.
+    pub base: String,
+    pub version: String,
+    pub description: String,
+    /// Dependencies without version constraints.
+    pub depends: Vec<String>,
+    pub provides: Vec<String>,
+}
+
+// -----------------------------------------------------------------------------
+// Helpers
+// -----------------------------------------------------------------------------
+/// Decompresses by magic bytes: repo-add writes gzip by default, but zstd
+/// and xz databases are in use as well.
+fn open_db(path: &Path) -> std::io::Result<Box<dyn Read>> {
+    let mut magic = [0u8; 4];
+    let read = File::open(path)?.read(&mut magic)?;
+    let reader = BufReader::new(File::open(path)?);
+    Ok(match &magic[..read] {
+        [0x1f, 0x8b, ..] => Box::new(flate2::read::GzDecoder::new(reader)),
+        [0x28, 0xb5, 0x2f, 0xfd] => Box::new(zstd::stream::read::Decoder::new(reader)?),
+        [0xfd, 0x37, 0x7a, 0x58] => Box::new(xz2::read::XzDecoder::new(reader)),
+        _ => Box::new(reader),
+    })
+}
+
+fn strip_constraint(entry: &str) -> String {
+    entry.split(['<', '>', '=']).next().unwrap_or(entry).to_string()
+}
+
+/// One `desc` file: `%FIELD%` headers each followed by their values, one per line.
+pub fn parse_desc(repo: &str, contents: &str) -> Option<SyncPackage> {
+    let mut package = SyncPackage { repo: repo.to_string(), ..Default::default() };
+    let mut field = "";
+    for line in contents.lines() {
+        if line.starts_with('%') && line.ends_with('%') {
+            field = line;
+            continue;
+        }
+        if line.is_empty() {
+            continue;
+        }
+        match field {
+            "%NAME%" => package.name = line.to_string(),
+            "%BASE%" => package.base = line.to_string(),
+            "%VERSION%" => package.version = line.to_string(),
+            "%DESC%" => package.description = line.to_string(),
+            "%DEPENDS%" => package.depends.push(strip_constraint(line)),
+            "%PROVIDES%" => package.provides.push(strip_constraint(line)),
+            _ => {}
+        }
+    }
+    if package.name.is_empty() {
+        return None;
+    }
+    if package.base.is_empty() {
+        package.base = package.name.clone();
+    }
+    Some(package)
+}
+
+/// Entries of one database archive whose name satisfies `wanted`.
+fn read_db(path: &Path, repo: &str, wanted: &dyn Fn(&str) -> bool) -> std::io::Result<Vec<SyncPackage>> {
+    let mut archive = tar::Archive::new(open_db(path)?);
+    let mut packages = Vec::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let entry_path = entry.path()?.to_string_lossy().to_string();
+        // Entries are "<name>-<pkgver>-<pkgrel>/desc"; the name is checked
+        // before the file is read, which keeps scanning a large repo cheap.
+        let Some(dir) = entry_path.strip_suffix("/desc") else { continue };
+        let name = dir.rsplitn(3, '-').nth(2).unwrap_or(dir);
+        if !wanted(name) {
+            continue;
+        }
+        let mut contents = String::new();
+        entry.read_to_string(&mut contents)?;
+        packages.extend(parse_desc(repo, &contents));
+    }
+    Ok(packages)
+}
+
+/// Entries from every `*.db` in `dir` whose name satisfies `wanted`, by
+/// repository name. Unreadable databases are skipped.
+pub fn packages_in(dir: &Path, wanted: impl Fn(&str) -> bool) -> Vec<SyncPackage> {
+    let Ok(entries) = std::fs::read_dir(dir) else { return Vec::new() };
+    let mut dbs: Vec<_> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "db"))
+        .collect();
+    dbs.sort();
+    dbs.iter()
+        .filter_map(|path| {
+            let repo = path.file_stem()?.to_string_lossy().to_string();
+            read_db(path, &repo, &wanted).ok()
+        })
+        .flatten()
+        .collect()
+}
+
+/// Entries from the system's sync databases whose name satisfies `wanted`.
+pub fn packages(wanted: impl Fn(&str) -> bool) -> Vec<SyncPackage> {
+    packages_in(Path::new(SYNC_DB_DIR), wanted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_desc_fields() {
+        let desc = "%FILENAME%\nlinux-lts-6.12.8-1-x86_64.pkg.tar.zst\n\n%NAME%\nlinux-lts\n\n%BASE%\nlinux-lts\n\n\
+                    %VERSION%\n6.12.8-1\n\n%DESC%\nThe LTS Linux kernel and modules\n\n\
+                    %DEPENDS%\ncoreutils\ninitramfs\nkmod>=31\n\n%PROVIDES%\nWIREGUARD-MODULE\n";
+        let package = parse_desc("core", desc).unwrap();
+        assert_eq!((package.name.as_str(), package.base.as_str()), ("linux-lts", "linux-lts"));
+        assert_eq!(package.version, "6.12.8-1");
+        assert_eq!(package.depends, ["coreutils", "initramfs", "kmod"]);
+        assert_eq!(package.provides, ["WIREGUARD-MODULE"]);
+
+        let unsplit = parse_desc("extra", "%NAME%\nnvidia-dkms\n\n%VERSION%\n565.77-1\n").unwrap();
+        assert_eq!(unsplit.base, "nvidia-dkms");
+        assert_eq!(parse_desc("core", "%VERSION%\n1-1\n"), None);
+    }
+
+    #[test]
+    fn reads_fixture_databases() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sync");
+        let names: Vec<(String, String)> =
+            packages_in(&dir, |n| n.starts_with("linux")).into_iter().map(|p| (p.repo, p.name)).collect();
+        assert_eq!(
+            names,
+            [
+                ("core", "linux"),
+                ("core", "linux-firmware"),
+                ("core", "linux-headers"),
+                ("core", "linux-lts"),
+                ("extra", "linux-zen"),
+            ]
+            .map(|(r, n)| (r.to_string(), n.to_string()))
+        );
+    }
+}
@@ -0,0 +1,474 @@
+// src/sysctl.rs
+//
+// Kernel tunables: live values from /proc/sys, persistent assignments from the
+// sysctl.d directories resolved the way systemd-sysctl does, and a curated
+// catalog with safe ranges. The app persists its own values in MANAGED_CONF.
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::privileged::{self, RootWrite};
+
+// -----------------------------------------------------------------------------
+// Configuration
+// -----------------------------------------------------------------------------
+const SYSCTL_CATALOG: &str = include_str!("../data/sysctl-catalog.json");
+const PROC_SYS: &str = "/proc/sys";
+/// sysctl.d(5): a file in an earlier directory hides one with the same name in a later one.
+const SYSCTL_DIRS: [&str; 4] = ["/etc/sysctl.d", "/run/sysctl.d", "/usr/local/lib/sysctl.d", "/usr/lib/sysctl.d"];
+/// Read after all sysctl.d files by `sysctl --system`, but never by
+/// systemd-sysctl at boot.
+const SYSCTL_CONF: &str = "/etc/sysctl.conf";
+/// Sorts after the common 99-sysctl.conf so the app's values take effect.
+const MANAGED_CONF: &str = "/etc/sysctl.d/99-zz-linuxhub.conf";
+const MANAGED_HEADER: &str = "# Managed by linuxhub; edited from the sysctl manager.";
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TunableKind {
+    Int,
+    Bool,
+    Enum,
+    String,
+    /// Whitespace-separated integers, e.g. net.ipv4.tcp_rmem; min/max apply to each.
+    IntList,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Tunable {
+    pub key: String,
+    pub kind: TunableKind,
+    #[serde(default)]
+    pub values: Vec<String>,
+    pub min: Option<i64>,
+    pub max: Option<i64>,
+    pub count: Option<usize>,
+    pub category: String,
+    pub description: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Catalog {
+    tunables: Vec<Tunable>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct SysctlAssignment {
+    pub file: String,
+    pub line: usize,
+    pub value: String,
+    /// A leading '-' on the key: failures to set it are ignored.
+    pub ignore_errors: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SysctlSetting {
+    pub key: String,
+    pub live_value: Option<String>,
+    pub tunable: Option<Tunable>,
+    /// Every persistent assignment in read order; the last one wins.
+    pub assignments: Vec<SysctlAssignment>,
+    pub winner: Option<SysctlAssignment>,
+    /// Assignments in /etc/sysctl.conf; only `sysctl --system` applies them.
+    pub sysctl_conf: Vec<SysctlAssignment>,
+    /// The assignments disagree about the value.
+    pub conflict: bool,
+    /// The winning persistent value differs from the live one.
+    pub pending: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SysctlOverview {
+    pub managed_file: String,
+    /// Configuration files in the order they are applied.
+    pub files: Vec<String>,
+    /// /etc/sysctl.conf when it exists outside sysctl.d; applied only by
+    /// `sysctl --system`, so it is not part of `files`.
+    pub sysctl_conf: Option<String>,
+    pub settings: Vec<SysctlSetting>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SysctlChange {
+    pub key: String,
+    /// None drops the key from MANAGED_CONF; the live value is left alone.
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SysctlApplyResult {
+    pub overview: SysctlOverview,
+    pub warnings: Vec<String>,
+}
+
+// -----------------------------------------------------------------------------
+// Helpers
+// -----------------------------------------------------------------------------
+fn catalog() -> Catalog {
+    serde_json::from_str(SYSCTL_CATALOG).expect("bundled sysctl catalog is valid")
+}
+
+/// sysctl.d accepts '/' as the separator; when the first separator is a '/',
+/// dots and slashes swap roles ("net/ipv4/conf/eth0.100/rp_filter").
+pub fn normalize_key(key: &str) -> String {
+    let key = key.trim();
+    match key.find(['.', '/']) {
+        Some(i) if key[i..].starts_with('/') => swap_separators(key),
+        _ => key.to_string(),
+    }
+}
+
+/// Swaps dots and slashes; a slash left in a normalized key is a dot in the
+/// file name (e.g. an interface called "eth0.100").
+fn swap_separators(key: &str) -> String {
+    key.chars()
+        .map(|c| match c {
+            '/' => '.',
+            '.' => '/',
+            c => c,
+        })
+        .collect()
+}
+
+/// Keys end up in a root-written drop-in and as a path under /proc/sys, so
+/// only name characters are accepted and no segment may be empty or "..".
+fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || "_.-/".contains(c))
+        && key.split(['.', '/']).all(|segment| !segment.is_empty() && segment != "..")
+}
+
+fn proc_path(key: &str) -> PathBuf {
+    Path::new(PROC_SYS).join(swap_separators(key))
+}
+
+/// The kernel separates multi-value entries with tabs; compare them with single spaces.
+fn normalize_value(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn live_value(key: &str) -> Option<String> {
+    if key.contains('*') {
+        return None;
+    }
+    std::fs::read_to_string(proc_path(key)).ok().map(|v| normalize_value(&v))
+}
+
+/// Effective sysctl.d files in application order.
+fn config_files() -> Vec<PathBuf> {
+    let mut by_name: BTreeMap<String, PathBuf> = BTreeMap::new();
+    for dir in SYSCTL_DIRS.iter().rev() {
+        let Ok(entries) = std::fs::read_dir(dir) else { continue };
+        for name in entries.filter_map(|e| e.ok()).map(|e| e.file_name().to_string_lossy().to_string()) {
+            if name.ends_with(".conf") {
+                by_name.insert(name.clone(), Path::new(dir).join(name));
+            }
+        }
+    }
+    by_name.into_values().collect()
+}
+
+/// /etc/sysctl.conf unless a sysctl.d file links to it, as 99-sysctl.conf
+/// does on Debian-based systems; then it already is among `files`.
+fn standalone_sysctl_conf(files: &[PathBuf]) -> Option<PathBuf> {
+    let links_to_conf = files.iter().any(|f| f.canonicalize().is_ok_and(|c| c == Path::new(SYSCTL_CONF)));
+    (Path::new(SYSCTL_CONF).exists() && !links_to_conf).then(|| PathBuf::from(SYSCTL_CONF))
+}
+
+pub fn parse_sysctl_conf(contents: &str) -> Vec<(String, usize, String, bool)> {
+    contents
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                return None;
+            }
+            let (key, value) = line.split_once('=')?;
+            let (key, ignore_errors) = match key.trim().strip_prefix('-') {
+                Some(k) => (k, true),
+                None => (key.trim(), false),
+            };
+            Some((normalize_key(key), i + 1, normalize_value(value), ignore_errors))
+        })
+        .collect()
+}
+
+/// Persistent assignments per key, in application order.
+fn read_assignments(files: &[PathBuf]) -> BTreeMap<String, Vec<SysctlAssignment>> {
+    let mut assignments: BTreeMap<String, Vec<SysctlAssignment>> = BTreeMap::new();
+    for file in files {
+        let Ok(contents) = std::fs::read_to_string(file) else { continue };
+        for (key, line, value, ignore_errors) in parse_sysctl_conf(&contents) {
+            assignments.entry(key).or_default().push(SysctlAssignment {
+                file: file.to_string_lossy().to_string(),
+                line,
+                value,
+                ignore_errors,
+            });
+        }
+    }
+    assignments
+}
+
+fn overview() -> SysctlOverview {
+    let files = config_files();
+    let sysctl_conf = standalone_sysctl_conf(&files);
+    let mut assignments = read_assignments(&files);
+    let mut conf_assignments = read_assignments(sysctl_conf.as_slice());
+    let tunables = catalog().tunables;
+
+    let mut keys: Vec<String> = tunables.iter().map(|t| t.key.clone()).collect();
+    for key in assignments.keys().chain(conf_assignments.keys()) {
+        if !keys.contains(key) {
+            keys.push(key.clone());
+        }
+    }
+
+    let settings = keys
+        .into_iter()
+        .filter_map(|key| {
+            let live_value = live_value(&key);
+            let key_assignments = assignments.remove(&key).unwrap_or_default();
+            let sysctl_conf = conf_assignments.remove(&key).unwrap_or_default();
+            // Catalog entries this kernel doesn't have (e.g. Yama not built in) are skipped.
+            if live_value.is_none() && key_assignments.is_empty() && sysctl_conf.is_empty() {
+                return None;
+            }
+            let winner = key_assignments.last().cloned();
+            Some(SysctlSetting {
+                conflict: key_assignments.iter().any(|a| winner.as_ref().is_some_and(|w| w.value != a.value)),
+                pending: matches!((&winner, &live_value), (Some(w), Some(live)) if &w.value != live),
+                tunable: tunables.iter().find(|t| t.key == key).cloned(),
+                assignments: key_assignments,
+                sysctl_conf,
+                winner,
+                live_value,
+                key,
+            })
+        })
+        .collect();
+
+    SysctlOverview {
+        managed_file: MANAGED_CONF.to_string(),
+        files: files.iter().map(|f| f.to_string_lossy().to_string()).collect(),
+        sysctl_conf: sysctl_conf.map(|f| f.to_string_lossy().to_string()),
+        settings,
+    }
+}
+
+fn validate(key: &str, value: &str, tunable: Option<&Tunable>) -> Result<(), String> {
+    if value.is_empty() || value.contains(['\n', '\r']) {
+        return Err(format!("Invalid value for {}", key));
+    }
+    let Some(tunable) = tunable else { return Ok(()) };
+    let range = || {
+        format!(
+            "between {} and {}",
+            tunable.min.map_or("-".into(), |m| m.to_string()),
+            tunable.max.map_or("-".into(), |m| m.to_string())
+        )
+    };
+    let in_range = |n: i64| !(tunable.min.is_some_and(|m| n < m) || tunable.max.is_some_and(|m| n > m));
+
+    match tunable.kind {
+        TunableKind::Int => match value.parse::<i64>() {
+            Err(_) => Err(format!("{} must be a number", key)),
+            Ok(n) if !in_range(n) => Err(format!("{} must be {}", key, range())),
+            Ok(_) => Ok(()),
+        },
+        TunableKind::IntList => {
+            let numbers: Result<Vec<i64>, _> = value.split_whitespace().map(str::parse::<i64>).collect();
+            match numbers {
+                Err(_) => Err(format!("{} must be a list of numbers", key)),
+                Ok(n) if tunable.count.is_some_and(|c| n.len() != c) => {
+                    Err(format!("{} needs exactly {} numbers", key, tunable.count.unwrap_or_default()))
+                }
+                Ok(n) if !n.iter().all(|&v| in_range(v)) => Err(format!("Each value of {} must be {}", key, range())),
+                Ok(_) => Ok(()),
+            }
+        }
+        TunableKind::Bool if value != "0" && value != "1" => Err(format!("{} must be 0 or 1", key)),
+        TunableKind::Enum if !tunable.values.iter().any(|v| v == value) => {
+            Err(format!("{} must be one of: {}", key, tunable.values.join(", ")))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Rewrites MANAGED_CONF with `changes` applied, keeping other lines.
+fn edit_managed_conf(contents: &str, changes: &[(String, Option<String>)]) -> String {
+    let mut lines: Vec<String> = contents
+        .lines()
+        .filter(|line| {
+            parse_sysctl_conf(line)
+                .first()
+                .is_none_or(|(key, ..)| !changes.iter().any(|(k, _)| k == key))
+        })
+        .map(str::to_string)
+        .collect();
+    if lines.is_empty() {
+        lines.push(MANAGED_HEADER.to_string());
+    }
+    for (key, value) in changes {
+        if let Some(value) = value {
+            lines.push(format!("{} = {}", key, value));
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: live and persistent settings
+// -----------------------------------------------------------------------------
+/// Catalog tunables plus every key set in a configuration file.
+#[tauri::command]
+pub async fn get_sysctl_settings() -> Result<SysctlOverview, String> {
+    Ok(overview())
+}
+
+// -----------------------------------------------------------------------------
+// Tauri command: apply (and optionally persist) changes
+// -----------------------------------------------------------------------------
+/// Sets each value at runtime with `sysctl -w`; with `persist` the values are
+/// also written to MANAGED_CONF. Warns when another file still overrides it.
+#[tauri::command]
+pub async fn apply_sysctl_changes(changes: Vec<SysctlChange>, persist: bool) -> Result<SysctlApplyResult, String> {
+    let tunables = catalog().tunables;
+    let mut warnings = Vec::new();
+    let mut commands = Vec::new();
+    let mut edits = Vec::new();
+
+    for change in &changes {
+        let key = normalize_key(&change.key);
+        if !is_valid_key(&key) {
+            return Err(format!("Invalid sysctl key: {}", key.escape_debug()));
+        }
+        if !proc_path(&key).exists() {
+            return Err(format!("{} does not exist on this kernel", key));
+        }
+        let value = change.value.as_deref().map(normalize_value);
+        let tunable = tunables.iter().find(|t| t.key == key);
+        if let Some(value) = &value {
+            validate(&key, value, tunable)?;
+            if tunable.is_none() {
+                warnings.push(format!("{} is not in the catalog; its value was not range-checked", key));
+            }
+            commands.push(vec!["sysctl".to_string(), "-w".to_string(), format!("{}={}", key, value)]);
+        } else if !persist {
+            return Err(format!("No value given for {}", key));
+        }
+        edits.push((key, value));
+    }
+    if edits.is_empty() {
+        return Err("No changes given".to_string());
+    }
+
+    let mut writes = Vec::new();
+    if persist {
+        let current = std::fs::read_to_string(MANAGED_CONF).unwrap_or_default();
+        writes.push(RootWrite::config(MANAGED_CONF, edit_managed_conf(&current, &edits)));
+    }
+    privileged::apply_as_root(&writes, &commands).await?;
+
+    let overview = overview();
+    if persist {
+        for (key, value) in edits.iter().filter_map(|(k, v)| Some((k, v.as_ref()?))) {
+            let setting = overview.settings.iter().find(|s| &s.key == key);
+            if let Some(w) = setting.and_then(|s| s.winner.as_ref()).filter(|w| w.file != MANAGED_CONF) {
+                warnings.push(format!("{} is overridden at boot by {}:{} ({})", key, w.file, w.line, w.value));
+            }
+            if let Some(c) = setting.and_then(|s| s.sysctl_conf.last()).filter(|c| &c.value != value) {
+                warnings.push(format!(
+                    "{} is also set in {}:{} ({}), which `sysctl --system` applies after sysctl.d",
+                    key, c.file, c.line, c.value
+                ));
+            }
+        }
+    }
+    Ok(SysctlApplyResult { overview, warnings })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tunable(kind: TunableKind, min: Option<i64>, max: Option<i64>) -> Tunable {
+        Tunable {
+            key: "vm.swappiness".to_string(),
+            kind,
+            values: Vec::new(),
+            min,
+            max,
+            count: None,
+            category: "memory".to_string(),
+            description: String::new(),
+        }
+    }
+
+    #[test]
+    fn parses_sysctl_conf_lines() {
+        let conf = "# comment\n; also a comment\n\nvm.swappiness = 10\n-net/ipv4/conf/eth0.100/rp_filter=2\nnet.ipv4.tcp_rmem = 4096\t131072  6291456\nno equals sign\n";
+        assert_eq!(
+            parse_sysctl_conf(conf),
+            [
+                ("vm.swappiness".to_string(), 4, "10".to_string(), false),
+                ("net.ipv4.conf.eth0/100.rp_filter".to_string(), 5, "2".to_string(), true),
+                ("net.ipv4.tcp_rmem".to_string(), 6, "4096 131072 6291456".to_string(), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn managed_conf_edits_replace_and_drop_keys() {
+        let current = "# Managed by linuxhub; edited from the sysctl manager.\nvm.swappiness = 60\nkernel.sysrq = 16\n";
+        let changes = [("vm.swappiness".to_string(), Some("10".to_string())), ("kernel.sysrq".to_string(), None)];
+        assert_eq!(
+            edit_managed_conf(current, &changes),
+            "# Managed by linuxhub; edited from the sysctl manager.\nvm.swappiness = 10\n"
+        );
+        assert_eq!(
+            edit_managed_conf("", &[("vm.swappiness".to_string(), Some("10".to_string()))]),
+            format!("{}\nvm.swappiness = 10\n", MANAGED_HEADER)
+        );
+    }
+
+    #[test]
+    fn rejects_keys_outside_proc_sys() {
+        assert!(is_valid_key("vm.swappiness"));
+        assert!(is_valid_key(&normalize_key("net/ipv4/conf/eth0.100/rp_filter")));
+        assert!(!is_valid_key(&normalize_key("vm/../../../etc/hostname")));
+        assert!(!is_valid_key("vm...etc"));
+        assert!(!is_valid_key(&normalize_key("/etc/hostname")));
+        assert!(!is_valid_key(".vm.swappiness"));
+        assert!(!is_valid_key("net.ipv4.conf.*.rp_filter"));
+        assert!(!is_valid_key("vm.swappiness = 1"));
+        assert!(!is_valid_key("vm.swappiness\nkernel.sysrq"));
+        assert!(!is_valid_key(""));
+    }
+
+    #[test]
+    fn validates_against_the_tunable_kind() {
+        let int = tunable(TunableKind::Int, Some(0), Some(200));
+        assert!(validate("vm.swappiness", "10", Some(&int)).is_ok());
+        assert!(validate("vm.swappiness", "201", Some(&int)).unwrap_err().contains("between 0 and 200"));
+        assert!(validate("vm.swappiness", "ten", Some(&int)).is_err());
+        assert!(validate("vm.swappiness", "1\nkernel.sysrq = 1", None).is_err());
+        assert!(validate("vm.swappiness", "", None).is_err());
+        assert!(validate("vm.unknown", "anything", None).is_ok());
+
+        assert!(validate("k", "2", Some(&tunable(TunableKind::Bool, None, None))).is_err());
+
+        let list = Tunable { count: Some(3), ..tunable(TunableKind::IntList, Some(1), None) };
+        assert!(validate("k", "4096 131072 6291456", Some(&list)).is_ok());
+        assert!(validate("k", "4096 131072", Some(&list)).unwrap_err().contains("exactly 3"));
+        assert!(validate("k", "0 131072 6291456", Some(&list)).is_err());
+
+        let choice = Tunable { values: vec!["fq".into(), "fq_codel".into()], ..tunable(TunableKind::Enum, None, None) };
+        assert!(validate("k", "fq", Some(&choice)).is_ok());
+        assert!(validate("k", "cake", Some(&choice)).unwrap_err().contains("fq, fq_codel"));
+    }
+}
@@ -0,0 +1,253 @@
+// src/usb.rs
+//
+// USB devices and their interfaces read from sysfs, with names resolved from
+// usb.ids. Drivers bind to interfaces rather than devices, so an interface
+// without a driver is what points at missing support.
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::hwids::IdDatabase;
+use crate::pci::{link_name, read_attr};
+
+// -----------------------------------------------------------------------------
+// Configuration
+// -----------------------------------------------------------------------------
+/// hwdata on Arch, usbutils on Debian-based systems.
+pub const USB_IDS: [&str; 3] = ["/usr/share/hwdata/usb.ids", "/usr/share/misc/usb.ids", "/var/lib/usbutils/usb.ids"];
+
+// -----------------------------------------------------------------------------
+// Data structures
+// -----------------------------------------------------------------------------
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct UsbInterface {
+    /// sysfs name, e.g. "1-4:1.0" (bus-port:config.interface).
+    pub name: String,
+    pub number: String,
+    /// Two-digit hex codes, e.g. "0e", "01", "00".
+    pub class: String,
+    pub subclass: String,
+    pub protocol: String,
+    pub class_name: Option<String>,
+    pub driver: Option<String>,
+    pub modalias: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct UsbDevice {
+    /// sysfs name, e.g. "1-4", or "usb1" for a root hub.
+    pub name: String,
+    pub bus: u32,
+    pub device_number: u32,
+    /// Lower-case hex IDs, e.g. "27c6".
+    pub vendor_id: String,
+    pub product_id: String,
+    pub vendor_name: Option<String>,
+    pub product_name: Option<String>,
+    /// String descriptors reported by the device itself.
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    pub class: String,
+    pub class_name: Option<String>,
+    /// Negotiated speed in Mbit/s, e.g. "480".
+    pub speed: Option<String>,
+    /// e.g. "High Speed"
+    pub speed_name: Option<String>,
+    /// USB version the device implements, e.g. "2.00".
+    pub version: Option<String>,
+    /// Power drawn from the bus, e.g. "500mA".
+    pub max_power: Option<String>,
+    pub self_powered: bool,
+    /// Runtime PM status, e.g. "active" or "suspended".
+    pub runtime_status: Option<String>,
+    /// Whether the kernel may autosuspend the device.
+    pub autosuspend: Option<bool>,
+    pub interfaces: Vec<UsbInterface>,
+}
+
+impl UsbDevice {
+    /// "vendor:product", as lsusb prints it.
+    pub fn id(&self) -> String {
+        format!("{}:{}", self.vendor_id, self.product_id)
+    }
+
+    pub fn model(&self) -> String {
+        self.product_name.clone().or_else(|| self.product.clone()).unwrap_or_else(|| format!("Device {}", self.product_id))
+    }
+
+    /// Interfaces no driver has claimed.
+    pub fn unbound_interfaces(&self) -> impl Iterator<Item = &UsbInterface> {
+        self.interfaces.iter().filter(|i| i.driver.is_none())
+    }
+
+    /// A line in the style of `lsusb`.
+    pub fn describe(&self) -> String {
+        format!(
+            "Bus {:03} Device {:03}: ID {} {} {}",
+            self.bus,
+            self.device_number,
+            self.id(),
+            self.vendor_name.as_deref().or(self.manufacturer.as_deref()).unwrap_or("Unknown vendor"),
+            self.model()
+        )
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Helpers
+// -----------------------------------------------------------------------------
+fn hex_u16(value: &str) -> Option<u16> {
+    u16::from_str_radix(value, 16).ok()
+}
+
+fn hex_u8(value: &str) -> u8 {
+    u8::from_str_radix(value, 16).unwrap_or(0)
+}
+
+/// Marketing name of a speed in Mbit/s.
+fn speed_name(speed: &str) -> Option<String> {
+    let name = match speed {
+        "1.5" => "Low Speed",
+        "12" => "Full Speed",
+        "480" => "High Speed",
+        "5000" => "SuperSpeed",
+        "10000" => "SuperSpeed+",
+        "20000" => "SuperSpeed+ 20Gbps",
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+fn read_interface(dir: &Path, ids: &IdDatabase) -> Option<UsbInterface> {
+    let class = read_attr(dir, "bInterfaceClass")?.to_lowercase();
+    let subclass = read_attr(dir, "bInterfaceSubClass").unwrap_or_default().to_lowercase();
+    Some(UsbInterface {
+        name: dir.file_name()?.to_string_lossy().to_string(),
+        number: read_attr(dir, "bInterfaceNumber").unwrap_or_default(),
+        class_name: ids.class(hex_u8(&class), hex_u8(&subclass)).map(str::to_string),
+        protocol: read_attr(dir, "bInterfaceProtocol").unwrap_or_default().to_lowercase(),
+        driver: link_name(&dir.join("driver")),
+        modalias: read_attr(dir, "modalias"),
+        class,
+        subclass,
+    })
+}
+
+pub fn read_device(dir: &Path, ids: &IdDatabase) -> Option<UsbDevice> {
+    let name = dir.file_name()?.to_string_lossy().to_string();
+    let vendor_id = read_attr(dir, "idVendor")?.to_lowercase();
+    let product_id = read_attr(dir, "idProduct")?.to_lowercase();
+    let class = read_attr(dir, "bDeviceClass").unwrap_or_default().to_lowercase();
+    let (vendor, product) = (hex_u16(&vendor_id), hex_u16(&product_id));
+    // Bit 6 of bmAttributes is "self-powered".
+    let attributes = read_attr(dir, "bmAttributes").and_then(|a| u8::from_str_radix(&a, 16).ok()).unwrap_or(0);
+
+    Some(UsbDevice {
+        bus: read_attr(dir, "busnum").and_then(|n| n.parse().ok()).unwrap_or(0),
+        device_number: read_attr(dir, "devnum").and_then(|n| n.parse().ok()).unwrap_or(0),
+        vendor_name: vendor.and_then(|v| ids.vendor(v)).map(str::to_string),
+        product_name: vendor.zip(product).and_then(|(v, p)| ids.device(v, p)).map(str::to_string),
+        manufacturer: read_attr(dir, "manufacturer"),
+        product: read_attr(dir, "product"),
+        class_name: ids.base_class(hex_u8(&class)).map(str::to_string),
+        speed_name: read_attr(dir, "speed").as_deref().and_then(speed_name),
+        speed: read_attr(dir, "speed"),
+        version: read_attr(dir, "version"),
+        max_power: read_attr(dir, "bMaxPower"),
+        self_powered: attributes & 0x40 != 0,
+        runtime_status: read_attr(dir, "power/runtime_status"),
+        autosuspend: read_attr(dir, "power/control").map(|c| c == "auto"),
+        interfaces: Vec::new(),
+        name,
+        vendor_id,
+        product_id,
+        class,
+    })
+}
+
+/// Interface directories are named after their device ("1-4" -> "1-4:1.0");
+/// root hub "usbN" interfaces are "N-0:1.0".
+fn owner_of(interface: &str) -> String {
+    let device = interface.split(':').next().unwrap_or(interface);
+    match device.strip_suffix("-0") {
+        Some(bus) => format!("usb{}", bus),
+        None => device.to_string(),
+    }
+}
+
+/// All devices under `<sysfs_root>/bus/usb/devices` with their interfaces,
+/// sorted by bus and device number.
+pub fn enumerate(sysfs_root: &Path, ids: &IdDatabase) -> Vec<UsbDevice> {
+    let Ok(entries) = std::fs::read_dir(sysfs_root.join("bus/usb/devices")) else { return Vec::new() };
+    let paths: Vec<_> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+    let is_interface = |p: &Path| p.file_name().is_some_and(|n| n.to_string_lossy().contains(':'));
+
+    let mut devices: Vec<UsbDevice> = paths.iter().filter(|p| !is_interface(p)).filter_map(|p| read_device(p, ids)).collect();
+    for path in paths.iter().filter(|p| is_interface(p)) {
+        let Some(interface) = read_interface(path, ids) else { continue };
+        if let Some(device) = devices.iter_mut().find(|d| d.name == owner_of(&interface.name)) {
+            device.interfaces.push(interface);
+        }
+    }
+    for device in devices.iter_mut() {
+        device.interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+    devices.sort_by_key(|d| (d.bus, d.device_number));
+    devices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci::fixture_root;
+
+    const USB_IDS_FIXTURE: &str = include_str!("../tests/fixtures/usb.ids");
+
+    fn devices() -> Vec<UsbDevice> {
+        let root = fixture_root();
+        enumerate(&root, &IdDatabase::parse(USB_IDS_FIXTURE))
+    }
+
+    #[test]
+    fn enumerates_devices_with_their_interfaces() {
+        let devices = devices();
+        let names: Vec<&str> = devices.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, ["usb1", "1-4", "1-5", "2-1"]);
+
+        let hub = &devices[0];
+        assert_eq!(hub.class_name.as_deref(), Some("Hub"));
+        assert_eq!(hub.interfaces.len(), 1);
+        assert_eq!(hub.interfaces[0].driver.as_deref(), Some("hub"));
+        assert!(hub.self_powered);
+
+        let webcam = &devices[2];
+        assert_eq!(webcam.describe(), "Bus 001 Device 004: ID 04f2:b604 Chicony Electronics Co., Ltd Integrated Camera (1280x720@30)");
+        assert_eq!(webcam.speed_name.as_deref(), Some("High Speed"));
+        assert_eq!(webcam.max_power.as_deref(), Some("500mA"));
+        assert_eq!(webcam.autosuspend, Some(true));
+        let classes: Vec<_> = webcam.interfaces.iter().map(|i| i.class_name.as_deref()).collect();
+        assert_eq!(classes, [Some("Video Control"), Some("Video Streaming")]);
+        assert_eq!(webcam.unbound_interfaces().count(), 0);
+    }
+
+    #[test]
+    fn unbound_interfaces_are_reported() {
+        let devices = devices();
+        let fingerprint = &devices[1];
+        assert_eq!(fingerprint.model(), "Fingerprint Reader");
+        assert_eq!(fingerprint.runtime_status.as_deref(), Some("suspended"));
+        let unbound: Vec<&str> = fingerprint.unbound_interfaces().map(|i| i.name.as_str()).collect();
+        assert_eq!(unbound, ["1-4:1.0"]);
+
+        let wifi = &devices[3];
+        assert_eq!(wifi.unbound_interfaces().next().map(|i| i.class.as_str()), Some("ff"));
+        assert_eq!(wifi.autosuspend, Some(false));
+    }
+
+    #[test]
+    fn unknown_ids_fall_back_to_descriptors() {
+        let root = fixture_root();
+        let devices = enumerate(&root, &IdDatabase::default());
+        assert_eq!(devices[1].model(), "Goodix USB2.0 MISC");
+        assert_eq!(devices[3].describe(), "Bus 002 Device 002: ID 0bda:8812 Realtek 802.11n NIC");
+    }
+}
@@ -0,0 +1,194 @@
+// src/vercmp.rs
+//
+// Port of libalpm's version comparison (`alpm_pkg_vercmp` / `rpmvercmp`), so
+// we can order pacman versions without shelling out to `vercmp` for every pair.
+use std::cmp::Ordering;
+
+/// Compares two full pacman versions (`[epoch:]pkgver[-pkgrel]`), exactly like `vercmp(8)`.
+pub fn vercmp(a: &str, b: &str) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+
+    let (epoch_a, ver_a, rel_a) = parse_evr(a);
+    let (epoch_b, ver_b, rel_b) = parse_evr(b);
+
+    let mut ret = rpmvercmp(epoch_a, epoch_b);
+    if ret == Ordering::Equal {
+        ret = rpmvercmp(ver_a, ver_b);
+        if ret == Ordering::Equal {
+            // The release is only compared when both sides carry one.
+            if let (Some(ra), Some(rb)) = (rel_a, rel_b) {
+                ret = rpmvercmp(ra, rb);
+            }
+        }
+    }
+    ret
+}
+
+/// Splits `epoch:version-release` into its parts. A missing epoch is "0".
+fn parse_evr(evr: &str) -> (&str, &str, Option<&str>) {
+    let digits = evr.bytes().take_while(|b| b.is_ascii_digit()).count();
+    let (epoch, rest) = if evr.as_bytes().get(digits) == Some(&b':') {
+        let epoch = if digits == 0 { "0" } else { &evr[..digits] };
+        (epoch, &evr[digits + 1..])
+    } else {
+        ("0", evr)
+    };
+
+    match rest.rfind('-') {
+        Some(idx) => (epoch, &rest[..idx], Some(&rest[idx + 1..])),
+        None => (epoch, rest, None),
+    }
+}
+
+/// Segment-wise comparison of a single version component (libalpm's `rpmvercmp`).
+fn rpmvercmp(a: &str, b: &str) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+
+    let a = a.as_bytes();
+    let b = b.as_bytes();
+    // `one`/`two` walk the strings, `ptr1`/`ptr2` mark the end of the last segment.
+    let (mut one, mut two) = (0usize, 0usize);
+    let (mut ptr1, mut ptr2) = (0usize, 0usize);
+
+    while one < a.len() && two < b.len() {
+        while one < a.len() && !a[one].is_ascii_alphanumeric() {
+            one += 1;
+        }
+        while two < b.len() && !b[two].is_ascii_alphanumeric() {
+            two += 1;
+        }
+
+        // Ran off the end of either string: leave the loop.
+        if one >= a.len() || two >= b.len() {
+            break;
+        }
+
+        // Different separator lengths decide the comparison on their own.
+        if one - ptr1 != two - ptr2 {
+            return if one - ptr1 < two - ptr2 { Ordering::Less } else { Ordering::Greater };
+        }
+
+        ptr1 = one;
+        ptr2 = two;
+
+        // Grab the first completely numeric or completely alphabetic segment.
+        let is_num = a[ptr1].is_ascii_digit();
+        if is_num {
+            while ptr1 < a.len() && a[ptr1].is_ascii_digit() {
+                ptr1 += 1;
+            }
+            while ptr2 < b.len() && b[ptr2].is_ascii_digit() {
+                ptr2 += 1;
+            }
+        } else {
+            while ptr1 < a.len() && a[ptr1].is_ascii_alphabetic() {
+                ptr1 += 1;
+            }
+            while ptr2 < b.len() && b[ptr2].is_ascii_alphabetic() {
+                ptr2 += 1;
+            }
+        }
+
+        // Segments of different types: numeric is always newer than alpha.
+        if two == ptr2 {
+            return if is_num { Ordering::Greater } else { Ordering::Less };
+        }
+
+        let mut seg1 = &a[one..ptr1];
+        let mut seg2 = &b[two..ptr2];
+
+        if is_num {
+            // Leading zeros don't matter; the longer number wins.
+            while seg1.first() == Some(&b'0') {
+                seg1 = &seg1[1..];
+            }
+            while seg2.first() == Some(&b'0') {
+                seg2 = &seg2[1..];
+            }
+            match seg1.len().cmp(&seg2.len()) {
+                Ordering::Equal => {}
+                other => return other,
+            }
+        }
+
+        match seg1.cmp(seg2) {
+            Ordering::Equal => {}
+            other => return other,
+        }
+
+        one = ptr1;
+        two = ptr2;
+    }
+
+    // All segments compared equal but the separators differed.
+    if one >= a.len() && two >= b.len() {
+        return Ordering::Equal;
+    }
+
+    // A remaining alpha segment never beats an empty string:
+    // - if `a` is exhausted and `b` does not continue with a letter, `b` is newer;
+    // - if `a` continues with a letter, `b` is newer;
+    // - otherwise `a` is newer.
+    let a_alpha = one < a.len() && a[one].is_ascii_alphabetic();
+    let b_alpha = two < b.len() && b[two].is_ascii_alphabetic();
+    if (one >= a.len() && !b_alpha) || a_alpha {
+        Ordering::Less
+    } else {
+        Ordering::Greater
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::vercmp;
+    use std::cmp::Ordering::{Equal, Greater, Less};
+
+    #[test]
+    fn matches_pacman_vercmp() {
+        // Cases taken from pacman's own test/util/vercmptest.sh.
+        let cases = [
+            ("1.5.0", "1.5.0", Equal),
+            ("1.5.1", "1.5.0", Greater),
+            ("1.5.1", "1.5", Greater),
+            ("1.5.0-1", "1.5.0-1", Equal),
+            ("1.5.0-1", "1.5.0-2", Less),
+            ("1.5.0-1", "1.5.1-1", Less),
+            ("1.5.0-2", "1.5.1-1", Less),
+            ("1.5-1", "1.5", Equal),
+            ("1.5b-1", "1.5-1", Less),
+            ("1.5b", "1.5", Less),
+            ("1.5b-1", "1.5", Less),
+            ("1.5b", "1.5.1", Less),
+            ("1.0a", "1.0alpha", Less),
+            ("1.0alpha", "1.0b", Less),
+            ("1.0b", "1.0beta", Less),
+            ("1.0beta", "1.0rc", Less),
+            ("1.0rc", "1.0", Less),
+            ("1.5.a", "1.5", Greater),
+            ("1.5.b", "1.5.a", Greater),
+            ("1.5.1", "1.5.b", Greater),
+            ("1.5.b-1", "1.5.b", Equal),
+            ("1.5-1", "1.5.b", Less),
+            ("0:1.0", "1.0", Equal),
+            ("1:1.0", "1.0", Greater),
+            ("1:1.0", "1:1.1", Less),
+            ("1:1.1", "2:1.1", Less),
+            ("2:1.0", "1:2.0", Greater),
+            ("1.0", "1.0.0", Less),
+            ("1.0.", "1.0", Greater),
+            ("1..0", "1.0", Greater),
+            ("1..0", "1..0", Equal),
+            ("1.0-1", "1.0-1.1", Less),
+            ("1.0-1.1", "1.0-1.2", Less),
+        ];
+
+        for (a, b, expected) in cases {
+            assert_eq!(vercmp(a, b), expected, "vercmp({a}, {b})");
+            assert_eq!(vercmp(b, a), expected.reverse(), "vercmp({b}, {a})");
+        }
+    }
+}
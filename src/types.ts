@@ -97,16 +97,139 @@ export interface SystemLocale {
 export interface Gpu {
     vendor: string;
     model: string;
-    driver_type: 'open_source' | 'proprietary';
+    driver_type: 'open_source' | 'proprietary' | 'none';
     driver_module: string;
     in_use: boolean;
+    pci_address: string;
+    device_id: string;
+    drm_card: string | null;
+    monitors: DrmConnector[];
+}
+
+export interface DisplayMode {
+    width: number;
+    height: number;
+    refresh: number;
+    interlaced: boolean;
+}
+
+export interface RangeLimits {
+    min_vertical_hz: number;
+    max_vertical_hz: number;
+    min_horizontal_khz: number;
+    max_horizontal_khz: number;
+    max_pixel_clock_mhz: number | null;
+}
+
+export interface Chromaticity {
+    red: [number, number];
+    green: [number, number];
+    blue: [number, number];
+    white: [number, number];
+}
+
+export interface HdrInfo {
+    eotfs: string[];
+    max_luminance: number | null;
+    max_frame_average: number | null;
+    min_luminance: number | null;
+}
+
+export interface ColourInfo {
+    bit_depth: number | null;
+    srgb: boolean;
+    chromaticity: Chromaticity;
+    bt2020: boolean;
+    dci_p3: boolean;
+    hdr: HdrInfo | null;
+}
+
+export interface Edid {
+    version: string;
+    manufacturer_id: string;
+    manufacturer: string | null;
+    product_code: number;
+    model: string | null;
+    serial: string | null;
+    manufacture_year: number | null;
+    manufacture_week: number | null;
+    digital: boolean;
+    interface: string | null;
+    width_mm: number | null;
+    height_mm: number | null;
+    diagonal_inches: number | null;
+    native_mode: DisplayMode | null;
+    modes: DisplayMode[];
+    range: RangeLimits | null;
+    colour: ColourInfo;
+    extensions: number;
+    checksum_valid: boolean;
+}
+
+export interface DrmConnector {
+    name: string;
+    status: 'connected' | 'disconnected' | 'unknown';
+    enabled: boolean;
+    connector_type: string;
+    edid: Edid | null;
+}
+
+export interface DrmCard {
+    name: string;
+    pci_address: string | null;
+    driver: string | null;
+    boot_vga: boolean;
+    connectors: DrmConnector[];
+    active: boolean;
+}
+
+export interface PciDevice {
+    address: string;
+    class: string;
+    class_name: string | null;
+    vendor_id: string;
+    device_id: string;
+    subsystem_vendor_id: string | null;
+    subsystem_device_id: string | null;
+    revision: string | null;
+    vendor_name: string | null;
+    device_name: string | null;
+    subsystem_name: string | null;
+    driver: string | null;
+    modalias: string | null;
+    boot_vga: boolean;
+}
+
+export interface RuntimePower {
+    status: string | null;
+    control: string | null;
+}
+
+export interface SwitchingTool {
+    name: 'prime-offload' | 'switcheroo-control' | 'optimus-manager' | 'envycontrol';
+    installed: boolean;
+    active: boolean;
+    mode: string | null;
 }
 
 export interface HybridInfo {
     primary: string;
     secondary: string;
-    switch_method: string;
+    switch_method: SwitchingTool['name'] | 'none';
     recommended_variant: string;
+    integrated: string;
+    discrete: string;
+    discrete_driver: string | null;
+    discrete_power: RuntimePower;
+    tools: SwitchingTool[];
+    offload_env: string[];
+    offload_launch_option: string;
+}
+
+export interface AppOffload {
+    desktop_file: string;
+    enabled: boolean;
+    exec: string[];
 }
 
 export interface DriverVariant {
@@ -130,16 +253,206 @@ export interface NetworkCard {
     device: string;
 }
 
-export interface OtherCard {
-    raw: string;
+export interface SoundCodec {
+    address: number;
+    name: string;
+    vendor_id: string | null;
+    subsystem_id: string | null;
+    hdmi: boolean;
+}
+
+export interface SoundCard {
+    index: number;
+    id: string;
+    alsa_driver: string;
+    name: string;
+    long_name: string | null;
+    device: string | null;
+    driver: string | null;
+    codecs: SoundCodec[];
+}
+
+export interface AudioInfo {
+    cards: SoundCard[];
+    hints: string[];
+}
+
+export type InputKind = 'keyboard' | 'mouse' | 'touchpad' | 'touchscreen' | 'tablet' | 'joystick' | 'switch' | 'other';
+
+export interface InputDevice {
+    name: string;
+    kind: InputKind;
+    bus: string;
+    vendor_id: string;
+    product_id: string;
+    phys: string | null;
+    sysfs_path: string | null;
+    handlers: string[];
+    driver: string | null;
+    hints: string[];
+}
+
+export interface UsbInterface {
+    name: string;
+    number: string;
+    class: string;
+    subclass: string;
+    protocol: string;
+    class_name: string | null;
+    driver: string | null;
+    modalias: string | null;
+}
+
+export interface UsbDevice {
+    name: string;
+    bus: number;
+    device_number: number;
+    vendor_id: string;
+    product_id: string;
+    vendor_name: string | null;
+    product_name: string | null;
+    manufacturer: string | null;
+    product: string | null;
+    class: string;
+    class_name: string | null;
+    speed: string | null;
+    speed_name: string | null;
+    version: string | null;
+    max_power: string | null;
+    self_powered: boolean;
+    runtime_status: string | null;
+    autosuspend: boolean | null;
+    interfaces: UsbInterface[];
+}
+
+export interface ProfileStep {
+    description: string;
+    run: string[];
+}
+
+export interface DriverProfile {
+    id: string;
+    name: string;
+    description: string;
+    bus: 'pci' | 'usb';
+    class_ids: string[];
+    vendor_ids: string[];
+    device_ids: string[];
+    priority: number;
+    free: boolean;
+    source: 'repo' | 'aur';
+    packages: string[];
+    conflicts: string[];
+    pre_install: ProfileStep[];
+    post_install: ProfileStep[];
+    modprobe: string | null;
+    mkinitcpio_modules: string[];
+    xorg: string | null;
+    wiki_url: string | null;
+}
+
+export interface DeviceProfiles {
+    bus: 'pci' | 'usb';
+    address: string; // PCI address, or USB device name such as "1-4"
+    device: string;
+    recommended: string | null;
+    recommended_free: string | null;
+    installed: string | null;
+    profiles: DriverProfile[];
+}
+
+export interface InstalledProfile {
+    id: string;
+    name: string;
+    database_version: string;
+    installed_at: string;
+    packages: string[];
+    files: string[];
+    mkinitcpio_modules: string[];
+}
+
+export interface DriverTransaction {
+    action: 'install' | 'switch' | 'remove';
+    profile: string;
+    replaces: string[];
+    install_packages: string[];
+    remove_packages: string[];
+    write_files: string[];
+    remove_files: string[];
+    regenerate_initramfs: boolean;
+    pre_install: ProfileStep[];
+    post_install: ProfileStep[];
+    warnings: string[];
+    blockers: string[];
+    planned_only: boolean;
+}
+
+export interface TpmInfo {
+    device: string;
+    version: string | null;
+}
+
+export interface PlatformInfo {
+    system_vendor: string | null;
+    product_name: string | null;
+    product_version: string | null;
+    product_family: string | null;
+    board_vendor: string | null;
+    board_name: string | null;
+    board_version: string | null;
+    chassis: string | null;
+    bios_vendor: string | null;
+    bios_version: string | null;
+    bios_date: string | null;
+    boot_mode: 'uefi' | 'bios';
+    efi_platform_size: number | null;
+    secure_boot: boolean | null;
+    setup_mode: boolean | null;
+    tpm: TpmInfo | null;
+    virtualization: string | null; // systemd-detect-virt name, e.g. 'kvm'
+}
+
+export interface GuestPackage {
+    name: string;
+    installed: boolean;
+}
+
+export interface GuestService {
+    name: string;
+    enabled: string; // systemctl is-enabled output
+    active: boolean;
+}
+
+export interface GuestModule {
+    name: string;
+    loaded: boolean;
+}
+
+export interface GuestTools {
+    virtualization: string;
+    name: string;
+    packages: GuestPackage[];
+    services: GuestService[];
+    modules: GuestModule[];
+    wiki_url: string;
+    complete: boolean;
 }
 
 export interface HardwareInfo {
     gpus: Gpu[];
     network_cards: NetworkCard[];
-    other_cards: OtherCard[];
+    audio: AudioInfo;
+    input_devices: InputDevice[];
     hybrid: HybridInfo | null;
     driver_packages: Record<string, DriverPackage>;
+    pci_devices: PciDevice[];
+    usb_devices: UsbDevice[];
+    platform: PlatformInfo;
+    guest_tools: GuestTools | null;
+    displays: DrmCard[];
+    driver_profiles: DeviceProfiles[];
+    installed_profiles: InstalledProfile[];
+    renderer: string | null;
 }
 
 // --- Live System Info Types ---
@@ -13,11 +13,13 @@ import { translations } from "../../data/translations";
 import {
   HardwareInfo,
   Gpu,
+  DrmConnector,
   DriverPackage,
   DriverVariant,
   PackageStatus,
   NetworkCard,
-  OtherCard,
+  SoundCard,
+  InputDevice,
 } from "../../types";
 
 const VENDOR_MAP: Record<string, { name: string; icon: string }> = {
@@ -27,6 +29,26 @@ const VENDOR_MAP: Record<string, { name: string; icon: string }> = {
   "10ec": { name: "Realtek", icon: "network" }, // Added for network card
 };
 
+/** e.g. "Dell U2720Q · HDMI · 3840×2160@60 Hz · 27″ · HDR" */
+const describeMonitor = (monitor: DrmConnector): string => {
+  const edid = monitor.edid;
+  if (!edid) return `${monitor.name} · ${monitor.connector_type}`;
+  const maker = edid.manufacturer ?? edid.manufacturer_id;
+  const name = edid.model
+    ? edid.model.toLowerCase().startsWith(maker.toLowerCase())
+      ? edid.model
+      : `${maker} ${edid.model}`
+    : maker;
+  const parts = [name, monitor.connector_type];
+  if (edid.native_mode) {
+    const { width, height, refresh } = edid.native_mode;
+    parts.push(`${width}×${height}@${Math.round(refresh)} Hz`);
+  }
+  if (edid.diagonal_inches) parts.push(`${edid.diagonal_inches}″`);
+  if (edid.colour.hdr) parts.push("HDR");
+  return parts.join(" · ");
+};
+
 const GpuCard: React.FC<{ gpu: Gpu }> = ({ gpu }) => {
   const vendorId = gpu.vendor.startsWith("10de") ? "10de" : gpu.vendor; // Handle NVIDIA model name in vendor
   const vendorInfo = VENDOR_MAP[vendorId.toLowerCase()] || {
@@ -47,6 +69,15 @@ const GpuCard: React.FC<{ gpu: Gpu }> = ({ gpu }) => {
         <p className="text-sm text-gray-500 dark:text-gray-400 capitalize">
           {gpu.driver_type} ({gpu.driver_module})
         </p>
+        {gpu.monitors.map((monitor) => (
+          <p
+            key={monitor.name}
+            className="text-xs text-gray-500 dark:text-gray-400 truncate"
+            title={monitor.edid?.serial ? `Serial ${monitor.edid.serial}` : undefined}
+          >
+            {describeMonitor(monitor)}
+          </p>
+        ))}
       </div>
       {gpu.in_use && (
         <span className="text-xs font-bold px-2 py-0.5 rounded-full bg-green-200 dark:bg-green-900/50 text-green-800 dark:text-green-300">
@@ -320,16 +351,55 @@ const NetworkCardComponent: React.FC<{ card: NetworkCard }> = ({ card }) => {
   );
 };
 
-const OtherCardComponent: React.FC<{ card: OtherCard }> = ({ card }) => {
+const HintList: React.FC<{ hints: string[] }> = ({ hints }) => (
+  <>
+    {hints.map((hint) => (
+      <p key={hint} className="text-xs text-amber-700 dark:text-amber-400">
+        {hint}
+      </p>
+    ))}
+  </>
+);
+
+const SoundCardComponent: React.FC<{ card: SoundCard }> = ({ card }) => {
+  const codecs = card.codecs.map((codec) => codec.name).join(", ");
   return (
     <div className="p-2 rounded-lg hover:bg-gray-100/80 dark:hover:bg-gray-700/50">
-      <p className="text-xs font-mono text-gray-600 dark:text-gray-400">
-        {card.raw}
+      <p className="font-semibold text-gray-800 dark:text-gray-200 truncate">
+        {card.name}
+      </p>
+      <p className="text-sm text-gray-500 dark:text-gray-400">
+        {card.driver ?? card.alsa_driver}
+        {codecs && ` · ${codecs}`}
       </p>
     </div>
   );
 };
 
+const INPUT_KIND_LABELS: Record<InputDevice["kind"], string> = {
+  keyboard: "Keyboard",
+  mouse: "Mouse",
+  touchpad: "Touchpad",
+  touchscreen: "Touchscreen",
+  tablet: "Tablet",
+  joystick: "Game controller",
+  switch: "Switch",
+  other: "Other",
+};
+
+const InputDeviceComponent: React.FC<{ device: InputDevice }> = ({ device }) => (
+  <div className="p-2 rounded-lg hover:bg-gray-100/80 dark:hover:bg-gray-700/50">
+    <p className="font-semibold text-gray-800 dark:text-gray-200 truncate">
+      {device.name}
+    </p>
+    <p className="text-sm text-gray-500 dark:text-gray-400">
+      {INPUT_KIND_LABELS[device.kind]} · {device.bus}
+      {device.driver && ` · ${device.driver}`}
+    </p>
+    <HintList hints={device.hints} />
+  </div>
+);
+
 const HardwarePanel: React.FC = () => {
   const [hardwareInfo, setHardwareInfo] = useState<HardwareInfo | null>(null);
   const [loading, setLoading] = useState(true);
@@ -455,13 +525,33 @@ const HardwarePanel: React.FC = () => {
             </BlurredCard>
           )}
 
-        {hardwareInfo.other_cards && hardwareInfo.other_cards.length > 0 && (
+        {(hardwareInfo.audio.cards.length > 0 ||
+          hardwareInfo.audio.hints.length > 0) && (
           <BlurredCard className="p-4 md:p-6">
-            <h2 className="text-xl font-bold mb-4">Other Hardware</h2>
-            <div className="space-y-1 max-h-64 overflow-y-auto pr-2">
-              {hardwareInfo.other_cards.map((card, index) => (
-                <OtherCardComponent key={index} card={card} />
+            <h2 className="text-xl font-bold mb-4">Sound</h2>
+            <div className="space-y-2">
+              {hardwareInfo.audio.cards.map((card) => (
+                <SoundCardComponent key={card.index} card={card} />
               ))}
+              <HintList hints={hardwareInfo.audio.hints} />
+            </div>
+          </BlurredCard>
+        )}
+
+        {hardwareInfo.input_devices.some(
+          (d) => d.kind !== "other" && d.kind !== "switch"
+        ) && (
+          <BlurredCard className="p-4 md:p-6">
+            <h2 className="text-xl font-bold mb-4">Input Devices</h2>
+            <div className="space-y-1 max-h-64 overflow-y-auto pr-2">
+              {hardwareInfo.input_devices
+                .filter((d) => d.kind !== "other" && d.kind !== "switch")
+                .map((device) => (
+                  <InputDeviceComponent
+                    key={device.sysfs_path ?? device.name}
+                    device={device}
+                  />
+                ))}
             </div>
           </BlurredCard>
         )}
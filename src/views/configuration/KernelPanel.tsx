@@ -16,6 +16,30 @@ interface InstalledKernel {
   name: string;
   version: string;
   flavor: string;
+  package_version: string | null;
+  pkgbase: string | null;
+  headers_package: string;
+  headers_installed: boolean;
+  modules_dir: string;
+  running: boolean;
+  lifecycle: KernelLifecycle | null;
+}
+
+interface KernelLifecycle {
+  series: string;
+  lts: boolean;
+  released: string | null;
+  eol_date: string | null;
+  eol: boolean;
+  near_eol: boolean;
+  days_until_eol: number | null;
+  experimental: boolean;
+}
+
+interface OrphanedModuleDir {
+  path: string;
+  pkgbase: string | null;
+  reason: string;
 }
 
 interface InstallableKernel {
@@ -23,12 +47,14 @@ interface InstallableKernel {
   version: string;
   description: string;
   flavor: string;
+  lifecycle: KernelLifecycle | null;
 }
 
 interface KernelData {
   running_kernel: string;
   installed_kernels: InstalledKernel[];
   installable_kernels: InstallableKernel[];
+  orphaned_module_dirs: OrphanedModuleDir[];
 }
 
 const formatKernelPackageName = (pkg: string): string => {
@@ -100,7 +126,6 @@ const KernelPanel: React.FC = () => {
         const result: string = await invoke("get_system_kernels");
         const kernelData: KernelData = JSON.parse(result);
 
-        const runningVersion = kernelData.running_kernel;
         setInstalledKernels(kernelData.installed_kernels);
 
         const kernelMap = new Map<string, Kernel>();
@@ -108,7 +133,7 @@ const KernelPanel: React.FC = () => {
         // Process installed kernels
         kernelData.installed_kernels.forEach((k) => {
           let releaseType: Kernel["releaseType"] = "stable";
-          if (k.flavor === "lts") releaseType = "lts";
+          if (k.flavor === "lts" || k.lifecycle?.lts) releaseType = "lts";
           else if (k.flavor === "default") releaseType = "stable";
 
           const key = `${k.name}-${k.version}`;
@@ -116,7 +141,7 @@ const KernelPanel: React.FC = () => {
             version: k.version,
             pkg: k.name,
             releaseType,
-            running: k.version === runningVersion,
+            running: k.running,
           });
         });
 
@@ -132,7 +157,7 @@ const KernelPanel: React.FC = () => {
             const key = k.package_name;
             if (!kernelMap.has(key)) {
               let releaseType: Kernel["releaseType"] = "stable";
-              if (k.flavor === "lts") releaseType = "lts";
+              if (k.flavor === "lts" || k.lifecycle?.lts) releaseType = "lts";
               kernelMap.set(key, {
                 version: k.version,
                 pkg: k.package_name,